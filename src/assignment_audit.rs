@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::group_metadata::{GroupMetadata, TopicPartitions};
+
+/// Number of partitions assigned to a member of a group.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberLoad {
+    /// [`crate::MemberMetadata::id`] of the member.
+    pub member_id: String,
+
+    /// [`crate::MemberMetadata::client_id`] of the member.
+    pub client_id: String,
+
+    /// [`crate::MemberMetadata::client_host`] of the member.
+    pub client_host: String,
+
+    /// Number of partitions assigned to the member, across all topics.
+    pub partitions: usize,
+}
+
+/// How unevenly partitions are spread across the members of a group.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssignmentSkew {
+    /// Fewest partitions assigned to a member.
+    pub min: usize,
+
+    /// Most partitions assigned to a member.
+    pub max: usize,
+
+    /// Average number of partitions assigned to a member.
+    pub mean: f64,
+
+    /// Standard deviation of the number of partitions assigned to a member.
+    pub stddev: f64,
+}
+
+impl AssignmentSkew {
+    /// Difference between the most and fewest partitions assigned to a member.
+    ///
+    /// A balanced assignment has a spread of at most `1`.
+    pub fn spread(&self) -> usize {
+        self.max - self.min
+    }
+
+    /// Ratio between the most partitions assigned to a member and the average: `1.0` means balanced.
+    pub fn max_to_mean(&self) -> f64 {
+        if self.mean > 0.0 {
+            self.max as f64 / self.mean
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A partition assigned to more than one member of a group.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoubleAssignedPartition {
+    /// Topic the partition belongs to.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// [`crate::MemberMetadata::id`] of the members the partition is assigned to.
+    pub members: Vec<String>,
+}
+
+/// Audit of the partition assignment of a group, as found in its [`GroupMetadata`].
+///
+/// It answers questions like _"why is one consumer hot while the others idle?"_: the data is all
+/// in the [`crate::ConsumerProtocolAssignment`] (and [`crate::ConsumerProtocolSubscription`])
+/// of each member.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssignmentAudit {
+    /// Group.
+    pub group: String,
+
+    /// [`GroupMetadata::generation`] the assignment belongs to.
+    pub generation: i32,
+
+    /// Partitions assigned to each member, in the order members appear in the [`GroupMetadata`].
+    pub members: Vec<MemberLoad>,
+
+    /// How unevenly partitions are spread across [`Self::members`]: `None` if the group has no members.
+    pub skew: Option<AssignmentSkew>,
+
+    /// Partitions assigned to more than one member, sorted by topic and partition.
+    pub double_assigned: Vec<DoubleAssignedPartition>,
+
+    /// [`crate::MemberMetadata::id`] of the members that have no partition assigned.
+    pub members_without_assignment: Vec<String>,
+
+    /// Topics subscribed by (at least) a member, that have no partition assigned to any member, sorted.
+    pub subscribed_topics_without_assignment: Vec<String>,
+
+    /// Partitions of subscribed topics that are not assigned to any member, sorted by topic.
+    ///
+    /// This is reported only for the topics whose partition count is known
+    /// (see [`AssignmentAudit::new`]).
+    pub unowned_partitions: Vec<TopicPartitions>,
+}
+
+impl AssignmentAudit {
+    /// Audits the partition assignment of the given [`GroupMetadata`].
+    ///
+    /// # Arguments
+    ///
+    /// * `group_metadata` - Group to audit.
+    /// * `partition_counts` - Optional number of partitions of each topic, to find the partitions
+    ///   that nobody owns. This is **not** in `__consumer_offsets`: it has to be fetched from the
+    ///   Kafka cluster (ex. via a `MetadataRequest`).
+    pub fn new(group_metadata: &GroupMetadata, partition_counts: Option<&HashMap<String, i32>>) -> Self {
+        let members: Vec<MemberLoad> = group_metadata
+            .members
+            .iter()
+            .map(|m| MemberLoad {
+                member_id: m.id.clone(),
+                client_id: m.client_id.clone(),
+                client_host: m.client_host.clone(),
+                partitions: m.assignment.assigned_topic_partitions.iter().map(|tp| tp.partitions.len()).sum(),
+            })
+            .collect();
+
+        let mut owners: BTreeMap<(&str, i32), Vec<String>> = BTreeMap::new();
+        for member in &group_metadata.members {
+            for tp in &member.assignment.assigned_topic_partitions {
+                for partition in &tp.partitions {
+                    owners.entry((tp.topic.as_str(), *partition)).or_default().push(member.id.clone());
+                }
+            }
+        }
+
+        let subscribed: BTreeSet<&str> =
+            group_metadata.members.iter().flat_map(|m| &m.subscription.subscribed_topics).map(String::as_str).collect();
+        let assigned: BTreeSet<&str> = owners.keys().map(|(topic, _)| *topic).collect();
+
+        let unowned_partitions = partition_counts
+            .map(|counts| {
+                subscribed
+                    .iter()
+                    .filter_map(|topic| {
+                        let partitions: Vec<i32> =
+                            (0..*counts.get(*topic)?).filter(|p| !owners.contains_key(&(*topic, *p))).collect();
+                        (!partitions.is_empty()).then(|| TopicPartitions {
+                            topic: topic.to_string(),
+                            partitions,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            group: group_metadata.group.clone(),
+            generation: group_metadata.generation,
+            skew: skew(&members),
+            members_without_assignment: members
+                .iter()
+                .filter(|m| m.partitions == 0)
+                .map(|m| m.member_id.clone())
+                .collect(),
+            members,
+            double_assigned: owners
+                .into_iter()
+                .filter(|(_, members)| members.len() > 1)
+                .map(|((topic, partition), members)| DoubleAssignedPartition {
+                    topic: topic.to_string(),
+                    partition,
+                    members,
+                })
+                .collect(),
+            subscribed_topics_without_assignment: subscribed
+                .difference(&assigned)
+                .map(|topic| topic.to_string())
+                .collect(),
+            unowned_partitions,
+        }
+    }
+
+    /// Whether any of the checks found a problem.
+    ///
+    /// Skew is not taken into account: use [`AssignmentSkew::spread`] to decide what's acceptable.
+    pub fn has_issues(&self) -> bool {
+        !self.double_assigned.is_empty()
+            || !self.members_without_assignment.is_empty()
+            || !self.subscribed_topics_without_assignment.is_empty()
+            || !self.unowned_partitions.is_empty()
+    }
+}
+
+fn skew(members: &[MemberLoad]) -> Option<AssignmentSkew> {
+    let loads = members.iter().map(|m| m.partitions);
+    let mean = loads.clone().sum::<usize>() as f64 / members.len() as f64;
+    let variance = loads.clone().map(|l| (l as f64 - mean).powi(2)).sum::<f64>() / members.len() as f64;
+
+    Some(AssignmentSkew {
+        min: loads.clone().min()?,
+        max: loads.max()?,
+        mean,
+        stddev: variance.sqrt(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{group, is_thread_safe, member, read_fixture};
+    use crate::KonsumerOffsetsData;
+
+    #[test]
+    fn test_balanced_assignment() {
+        let gm = group("g1", vec![member("a", &["t1"], &[("t1", &[0, 1])]), member("b", &["t1"], &[("t1", &[2, 3])])]);
+        let counts = HashMap::from([("t1".to_string(), 4)]);
+
+        let audit = AssignmentAudit::new(&gm, Some(&counts));
+        assert!(!audit.has_issues());
+        let skew = audit.skew.unwrap();
+        assert_eq!((skew.min, skew.max, skew.spread()), (2, 2, 0));
+        assert_eq!(skew.stddev, 0.0);
+        assert_eq!(skew.max_to_mean(), 1.0);
+    }
+
+    #[test]
+    fn test_issues() {
+        let gm = group(
+            "g1",
+            vec![
+                member("a", &["t1", "t2"], &[("t1", &[0, 1, 2])]),
+                member("b", &["t1", "t2"], &[("t1", &[2])]),
+                member("c", &["t1", "t2"], &[]),
+            ],
+        );
+        let counts = HashMap::from([("t1".to_string(), 5), ("t2".to_string(), 2)]);
+
+        let audit = AssignmentAudit::new(&gm, Some(&counts));
+        assert!(audit.has_issues());
+        assert_eq!(audit.members.iter().map(|m| m.partitions).collect::<Vec<_>>(), vec![3, 1, 0]);
+        assert_eq!(audit.skew.as_ref().unwrap().spread(), 3);
+        assert_eq!(
+            audit.double_assigned,
+            vec![DoubleAssignedPartition {
+                topic: "t1".to_string(),
+                partition: 2,
+                members: vec!["a".to_string(), "b".to_string()],
+            }]
+        );
+        assert_eq!(audit.members_without_assignment, vec!["c".to_string()]);
+        assert_eq!(audit.subscribed_topics_without_assignment, vec!["t2".to_string()]);
+        assert_eq!(
+            audit.unowned_partitions,
+            vec![
+                TopicPartitions {
+                    topic: "t1".to_string(),
+                    partitions: vec![3, 4],
+                },
+                TopicPartitions {
+                    topic: "t2".to_string(),
+                    partitions: vec![0, 1],
+                },
+            ]
+        );
+
+        assert!(AssignmentAudit::new(&gm, None).unowned_partitions.is_empty());
+    }
+
+    #[test]
+    fn test_empty_group() {
+        let audit = AssignmentAudit::new(&group("g1", vec![]), None);
+        assert_eq!(audit.skew, None);
+        assert!(!audit.has_issues());
+    }
+
+    #[test]
+    fn test_fixture() {
+        let KonsumerOffsetsData::GroupMetadata(gm) = read_fixture("group_metadata", 4) else {
+            panic!("Returned wrong enum value!");
+        };
+
+        let audit = AssignmentAudit::new(&gm, None);
+        assert_eq!(audit.members.len(), gm.members.len());
+        assert!(audit.double_assigned.is_empty());
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<MemberLoad>();
+        is_thread_safe::<AssignmentSkew>();
+        is_thread_safe::<DoubleAssignedPartition>();
+        is_thread_safe::<AssignmentAudit>();
+    }
+}
@@ -0,0 +1,600 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::group_metadata::{ConsumerProtocolAssignment, ConsumerProtocolSubscription, GroupMetadata, TopicPartitions};
+
+/// A partition of a topic, borrowing the topic name.
+type TopicPartition<'a> = (&'a str, i32);
+
+/// Partitions assigned to each member, keyed by member id.
+type MemberPartitions<'a> = BTreeMap<&'a str, Vec<TopicPartition<'a>>>;
+
+/// Partition assignment strategy, as implemented by the Kafka Consumer (i.e. `partition.assignment.strategy`).
+///
+/// The Group Leader runs the assignor on the [`ConsumerProtocolSubscription`] of each member,
+/// producing the [`ConsumerProtocolAssignment`] of each member: implementations of this trait
+/// do the same, so that _"what if"_ questions can be answered offline
+/// (ex. _"what would the assignment be if we switched to cooperative-sticky, or added two members?"_).
+pub trait PartitionAssignor: Send + Sync {
+    /// Name of the assignor, as it appears in [`GroupMetadata::protocol`].
+    fn name(&self) -> &'static str;
+
+    /// Assigns the partitions of the subscribed topics to the members of a group.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition_counts` - Number of partitions of each topic. This is **not** in `__consumer_offsets`:
+    ///   it has to be fetched from the Kafka cluster (ex. via a `MetadataRequest`).
+    ///   Subscribed topics missing from it, or with no partitions (i.e. a count `<= 0`), are not assigned.
+    /// * `subscriptions` - [`ConsumerProtocolSubscription`] of each member, keyed by member id.
+    ///
+    /// Returns the [`ConsumerProtocolAssignment`] of each member, keyed by member id:
+    /// every member in `subscriptions` is present, even if it has no partition assigned.
+    fn assign(
+        &self,
+        partition_counts: &HashMap<String, i32>,
+        subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>,
+    ) -> BTreeMap<String, ConsumerProtocolAssignment>;
+}
+
+/// Assigns to each member a contiguous range of partitions of each topic it subscribes to.
+///
+/// Same as Kafka's `RangeAssignor`: for each topic, members subscribed to it are sorted by id,
+/// and the first `partitions % members` receive one extra partition.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct RangeAssignor;
+
+/// Assigns partitions one by one to members in circular order.
+///
+/// Same as Kafka's `RoundRobinAssignor`: all partitions, sorted by topic and partition, are dealt
+/// to the members sorted by id, skipping those not subscribed to the topic of the partition.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct RoundRobinAssignor;
+
+/// Assigns partitions as evenly as possible, while moving as few as possible from their current owner.
+///
+/// It pursues the goals of Kafka's `StickyAssignor`, relying on
+/// [`ConsumerProtocolSubscription::owned_topic_partitions`] to know the current owners:
+///
+/// 1. partitions claimed by a member still subscribed to their topic are retained (if more
+///    members claim the same partition, the one with the highest
+///    [`ConsumerProtocolSubscription::generation_id`] wins; ties make all claims void)
+/// 2. the remaining partitions are assigned, in order, to the eligible member with the fewest partitions
+/// 3. partitions are moved from the most to the least loaded members, until no member has
+///    more than one partition over another member that could take it
+///
+/// The result is balanced and sticky like Kafka's, but for complex subscriptions it can differ in
+/// _which_ partitions move.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct StickyAssignor;
+
+/// Like [`StickyAssignor`], but following the _incremental cooperative_ rebalance protocol.
+///
+/// Same as Kafka's `CooperativeStickyAssignor`: a partition that moves to a different member is
+/// first only revoked from its current owner, and left unassigned. It will be assigned in the follow-up
+/// rebalance, that members trigger once they have revoked it.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct CooperativeStickyAssignor;
+
+impl PartitionAssignor for RangeAssignor {
+    fn name(&self) -> &'static str {
+        "range"
+    }
+
+    fn assign(
+        &self,
+        partition_counts: &HashMap<String, i32>,
+        subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>,
+    ) -> BTreeMap<String, ConsumerProtocolAssignment> {
+        let mut assigned = empty_assignment(subscriptions);
+
+        for (topic, count) in subscribed_topics(partition_counts, subscriptions) {
+            let members: Vec<&str> = subscriptions
+                .iter()
+                .filter(|(_, s)| s.subscribed_topics.iter().any(|t| t == topic))
+                .map(|(id, _)| id.as_str())
+                .collect();
+
+            let per_member = count as usize / members.len();
+            let extra = count as usize % members.len();
+            for (i, member) in members.into_iter().enumerate() {
+                let start = i * per_member + i.min(extra);
+                let end = start + per_member + usize::from(i < extra);
+                assigned.get_mut(member).unwrap().extend((start..end).map(|p| (topic, p as i32)));
+            }
+        }
+
+        to_assignments(assigned)
+    }
+}
+
+impl PartitionAssignor for RoundRobinAssignor {
+    fn name(&self) -> &'static str {
+        "roundrobin"
+    }
+
+    fn assign(
+        &self,
+        partition_counts: &HashMap<String, i32>,
+        subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>,
+    ) -> BTreeMap<String, ConsumerProtocolAssignment> {
+        let mut assigned = empty_assignment(subscriptions);
+        let members: Vec<&str> = assigned.keys().copied().collect();
+
+        let mut next = 0;
+        for (topic, count) in subscribed_topics(partition_counts, subscriptions) {
+            for partition in 0..count {
+                // At least a member is subscribed to the topic, or it would not be here
+                let idx = (0..members.len())
+                    .map(|i| (next + i) % members.len())
+                    .find(|idx| is_subscribed(&subscriptions[members[*idx]], topic))
+                    .unwrap();
+                assigned.get_mut(members[idx]).unwrap().push((topic, partition));
+                next = idx + 1;
+            }
+        }
+
+        to_assignments(assigned)
+    }
+}
+
+impl PartitionAssignor for StickyAssignor {
+    fn name(&self) -> &'static str {
+        "sticky"
+    }
+
+    fn assign(
+        &self,
+        partition_counts: &HashMap<String, i32>,
+        subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>,
+    ) -> BTreeMap<String, ConsumerProtocolAssignment> {
+        let owners = current_owners(partition_counts, subscriptions);
+        to_assignments(sticky_assign(partition_counts, subscriptions, &owners))
+    }
+}
+
+impl PartitionAssignor for CooperativeStickyAssignor {
+    fn name(&self) -> &'static str {
+        "cooperative-sticky"
+    }
+
+    fn assign(
+        &self,
+        partition_counts: &HashMap<String, i32>,
+        subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>,
+    ) -> BTreeMap<String, ConsumerProtocolAssignment> {
+        let owners = current_owners(partition_counts, subscriptions);
+        let mut assigned = sticky_assign(partition_counts, subscriptions, &owners);
+
+        // Partitions changing owner are only revoked in this round
+        for (member, partitions) in assigned.iter_mut() {
+            partitions.retain(|tp| owners.get(tp).map_or(true, |owner| owner == member));
+        }
+
+        to_assignments(assigned)
+    }
+}
+
+/// Returns the [`PartitionAssignor`] with the given [`PartitionAssignor::name`], if implemented.
+///
+/// Useful to get the assignor a group currently uses, from its [`GroupMetadata::protocol`].
+pub fn assignor_by_name(name: &str) -> Option<Box<dyn PartitionAssignor>> {
+    match name {
+        "range" => Some(Box::new(RangeAssignor)),
+        "roundrobin" => Some(Box::new(RoundRobinAssignor)),
+        "sticky" => Some(Box::new(StickyAssignor)),
+        "cooperative-sticky" => Some(Box::new(CooperativeStickyAssignor)),
+        _ => None,
+    }
+}
+
+/// Subscriptions of the members of a group, as they would be sent at the next rebalance.
+///
+/// This is the [`ConsumerProtocolSubscription`] of each member of the [`GroupMetadata`], with the
+/// current [`ConsumerProtocolAssignment`] as [`ConsumerProtocolSubscription::owned_topic_partitions`]
+/// and the current [`GroupMetadata::generation`] as [`ConsumerProtocolSubscription::generation_id`].
+///
+/// The result, keyed by member id, can be edited (ex. adding members) and then given to a [`PartitionAssignor`].
+pub fn group_subscriptions(group_metadata: &GroupMetadata) -> BTreeMap<String, ConsumerProtocolSubscription> {
+    group_metadata
+        .members
+        .iter()
+        .map(|m| {
+            let subscription = ConsumerProtocolSubscription {
+                owned_topic_partitions: m.assignment.assigned_topic_partitions.clone(),
+                generation_id: group_metadata.generation,
+                ..m.subscription.clone()
+            };
+            (m.id.clone(), subscription)
+        })
+        .collect()
+}
+
+/// Difference between the actual and a proposed assignment, for a member of a group.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberAssignmentChange {
+    /// Member id.
+    pub member_id: String,
+
+    /// Partitions assigned to the member in both the actual and the proposed assignment.
+    pub kept: Vec<TopicPartitions>,
+
+    /// Partitions assigned to the member only in the proposed assignment.
+    pub added: Vec<TopicPartitions>,
+
+    /// Partitions assigned to the member only in the actual assignment.
+    pub removed: Vec<TopicPartitions>,
+}
+
+/// Comparison between the actual assignment of a group, and one proposed by a [`PartitionAssignor`].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssignmentComparison {
+    /// Changes for each member, sorted by member id.
+    ///
+    /// It includes members found only in the actual or only in the proposed assignment.
+    pub members: Vec<MemberAssignmentChange>,
+
+    /// Number of partitions that the proposed assignment gives to a different member.
+    pub reassigned_partitions: usize,
+}
+
+impl AssignmentComparison {
+    /// Compares the actual assignment of the given [`GroupMetadata`], with the proposed one.
+    ///
+    /// # Arguments
+    ///
+    /// * `actual` - Group, with its actual assignment.
+    /// * `proposed` - [`ConsumerProtocolAssignment`] of each member, keyed by member id
+    ///   (i.e. as returned by [`PartitionAssignor::assign`]).
+    pub fn new(actual: &GroupMetadata, proposed: &BTreeMap<String, ConsumerProtocolAssignment>) -> Self {
+        let mut sides: BTreeMap<&str, (BTreeSet<TopicPartition>, BTreeSet<TopicPartition>)> = BTreeMap::new();
+        for m in &actual.members {
+            sides.entry(m.id.as_str()).or_default().0.extend(flatten(&m.assignment.assigned_topic_partitions));
+        }
+        for (id, assignment) in proposed {
+            sides.entry(id.as_str()).or_default().1.extend(flatten(&assignment.assigned_topic_partitions));
+        }
+
+        let members: Vec<MemberAssignmentChange> = sides
+            .into_iter()
+            .map(|(id, (before, after))| MemberAssignmentChange {
+                member_id: id.to_string(),
+                kept: group_by_topic(before.intersection(&after).copied()),
+                added: group_by_topic(after.difference(&before).copied()),
+                removed: group_by_topic(before.difference(&after).copied()),
+            })
+            .collect();
+
+        Self {
+            reassigned_partitions: members.iter().flat_map(|m| &m.added).map(|tp| tp.partitions.len()).sum(),
+            members,
+        }
+    }
+
+    /// Whether the proposed assignment is the same as the actual one.
+    pub fn is_unchanged(&self) -> bool {
+        self.members.iter().all(|m| m.added.is_empty() && m.removed.is_empty())
+    }
+}
+
+fn is_subscribed(subscription: &ConsumerProtocolSubscription, topic: &str) -> bool {
+    subscription.subscribed_topics.iter().any(|t| t == topic)
+}
+
+/// Topics subscribed by at least a member, with a known partition count, sorted by name.
+fn subscribed_topics<'a>(
+    partition_counts: &HashMap<String, i32>,
+    subscriptions: &'a BTreeMap<String, ConsumerProtocolSubscription>,
+) -> BTreeMap<&'a str, i32> {
+    subscriptions
+        .values()
+        .flat_map(|s| &s.subscribed_topics)
+        .filter_map(|t| Some((t.as_str(), *partition_counts.get(t)?)))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+fn empty_assignment(subscriptions: &BTreeMap<String, ConsumerProtocolSubscription>) -> MemberPartitions<'_> {
+    subscriptions.keys().map(|id| (id.as_str(), Vec::new())).collect()
+}
+
+/// Valid owner of each partition, according to [`ConsumerProtocolSubscription::owned_topic_partitions`].
+fn current_owners<'a>(
+    partition_counts: &HashMap<String, i32>,
+    subscriptions: &'a BTreeMap<String, ConsumerProtocolSubscription>,
+) -> BTreeMap<TopicPartition<'a>, &'a str> {
+    let mut claims: BTreeMap<TopicPartition, (i32, Vec<&str>)> = BTreeMap::new();
+    for (id, subscription) in subscriptions {
+        for (topic, partition) in flatten(&subscription.owned_topic_partitions) {
+            let exists = partition_counts.get(topic).is_some_and(|count| (0..*count).contains(&partition));
+            if !exists || !is_subscribed(subscription, topic) {
+                continue;
+            }
+
+            let claim = claims.entry((topic, partition)).or_insert((subscription.generation_id, Vec::new()));
+            if subscription.generation_id > claim.0 {
+                *claim = (subscription.generation_id, vec![id]);
+            } else if subscription.generation_id == claim.0 {
+                claim.1.push(id);
+            }
+        }
+    }
+
+    claims.into_iter().filter_map(|(tp, (_, claimants))| (claimants.len() == 1).then(|| (tp, claimants[0]))).collect()
+}
+
+fn sticky_assign<'a>(
+    partition_counts: &HashMap<String, i32>,
+    subscriptions: &'a BTreeMap<String, ConsumerProtocolSubscription>,
+    owners: &BTreeMap<TopicPartition<'a>, &'a str>,
+) -> MemberPartitions<'a> {
+    let mut assigned = empty_assignment(subscriptions);
+    for (tp, owner) in owners {
+        assigned.get_mut(owner).unwrap().push(*tp);
+    }
+
+    // Assign the partitions nobody owns
+    for (topic, count) in subscribed_topics(partition_counts, subscriptions) {
+        for partition in (0..count).filter(|p| !owners.contains_key(&(topic, *p))) {
+            let member = assigned
+                .iter()
+                .filter(|(id, _)| is_subscribed(&subscriptions[**id], topic))
+                .min_by_key(|(id, partitions)| (partitions.len(), **id))
+                .map(|(id, _)| *id)
+                .unwrap();
+            assigned.get_mut(member).unwrap().push((topic, partition));
+        }
+    }
+
+    // Balance, moving one partition at a time from the most to the least loaded members:
+    // every move reduces the sum of squared loads, so this terminates
+    loop {
+        let mut by_load: Vec<&str> = assigned.keys().copied().collect();
+        by_load.sort_by_key(|id| (assigned[id].len(), *id));
+
+        let next_move = by_load.iter().rev().find_map(|from| {
+            by_load.iter().take_while(|to| assigned[*to].len() + 1 < assigned[from].len()).find_map(|to| {
+                let idx = assigned[from].iter().rposition(|(topic, _)| is_subscribed(&subscriptions[*to], topic))?;
+                Some((*from, *to, idx))
+            })
+        });
+        let Some((from, to, idx)) = next_move else {
+            break;
+        };
+
+        let tp = assigned.get_mut(from).unwrap().remove(idx);
+        assigned.get_mut(to).unwrap().push(tp);
+    }
+
+    assigned
+}
+
+fn flatten(topic_partitions: &[TopicPartitions]) -> impl Iterator<Item = TopicPartition<'_>> {
+    topic_partitions.iter().flat_map(|tp| tp.partitions.iter().map(|p| (tp.topic.as_str(), *p)))
+}
+
+/// Groups partitions by topic, sorting both topics and partitions.
+pub(crate) fn group_by_topic<'a>(partitions: impl IntoIterator<Item = TopicPartition<'a>>) -> Vec<TopicPartitions> {
+    let mut by_topic: BTreeMap<&str, Vec<i32>> = BTreeMap::new();
+    for (topic, partition) in partitions {
+        by_topic.entry(topic).or_default().push(partition);
+    }
+
+    by_topic
+        .into_iter()
+        .map(|(topic, mut partitions)| {
+            partitions.sort_unstable();
+            TopicPartitions {
+                topic: topic.to_string(),
+                partitions,
+            }
+        })
+        .collect()
+}
+
+fn to_assignments(assigned: MemberPartitions) -> BTreeMap<String, ConsumerProtocolAssignment> {
+    assigned
+        .into_iter()
+        .map(|(id, partitions)| {
+            let assignment = ConsumerProtocolAssignment {
+                assigned_topic_partitions: group_by_topic(partitions),
+                ..Default::default()
+            };
+            (id.to_string(), assignment)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::is_thread_safe;
+    use crate::MemberMetadata;
+
+    fn tps(assigned: &[(&str, &[i32])]) -> Vec<TopicPartitions> {
+        assigned
+            .iter()
+            .map(|(topic, partitions)| TopicPartitions {
+                topic: topic.to_string(),
+                partitions: partitions.to_vec(),
+            })
+            .collect()
+    }
+
+    fn subscription(topics: &[&str], owned: &[(&str, &[i32])], generation_id: i32) -> ConsumerProtocolSubscription {
+        ConsumerProtocolSubscription {
+            subscribed_topics: topics.iter().map(|t| t.to_string()).collect(),
+            owned_topic_partitions: tps(owned),
+            generation_id,
+            ..Default::default()
+        }
+    }
+
+    fn counts(counts: &[(&str, i32)]) -> HashMap<String, i32> {
+        counts.iter().map(|(t, c)| (t.to_string(), *c)).collect()
+    }
+
+    fn assigned(result: &BTreeMap<String, ConsumerProtocolAssignment>, member: &str) -> Vec<TopicPartitions> {
+        result[member].assigned_topic_partitions.clone()
+    }
+
+    #[test]
+    fn test_range() {
+        let subs = BTreeMap::from([
+            ("a".to_string(), subscription(&["t1", "t2"], &[], -1)),
+            ("b".to_string(), subscription(&["t1", "t2"], &[], -1)),
+        ]);
+        let result = RangeAssignor.assign(&counts(&[("t1", 3), ("t2", 3)]), &subs);
+
+        assert_eq!(assigned(&result, "a"), tps(&[("t1", &[0, 1]), ("t2", &[0, 1])]));
+        assert_eq!(assigned(&result, "b"), tps(&[("t1", &[2]), ("t2", &[2])]));
+    }
+
+    #[test]
+    fn test_invalid_partition_counts() {
+        let subs = BTreeMap::from([
+            ("a".to_string(), subscription(&["t1", "t2"], &[], -1)),
+            ("b".to_string(), subscription(&["t1", "t2"], &[], -1)),
+        ]);
+        let partition_counts = counts(&[("t1", -3), ("t2", 0)]);
+
+        for name in ["range", "roundrobin", "sticky", "cooperative-sticky"] {
+            let result = assignor_by_name(name).unwrap().assign(&partition_counts, &subs);
+            assert_eq!(assigned(&result, "a"), vec![], "{name}");
+            assert_eq!(assigned(&result, "b"), vec![], "{name}");
+        }
+    }
+
+    #[test]
+    fn test_roundrobin() {
+        let subs = BTreeMap::from([
+            ("a".to_string(), subscription(&["t1", "t2"], &[], -1)),
+            ("b".to_string(), subscription(&["t1", "t2"], &[], -1)),
+            ("c".to_string(), subscription(&["t1"], &[], -1)),
+        ]);
+        let result = RoundRobinAssignor.assign(&counts(&[("t1", 3), ("t2", 2), ("unknown", 1)]), &subs);
+
+        assert_eq!(assigned(&result, "a"), tps(&[("t1", &[0]), ("t2", &[0])]));
+        assert_eq!(assigned(&result, "b"), tps(&[("t1", &[1]), ("t2", &[1])]));
+        assert_eq!(assigned(&result, "c"), tps(&[("t1", &[2])]));
+    }
+
+    #[test]
+    fn test_sticky_keeps_owned_partitions() {
+        // "b" joins a group where "a" owns everything
+        let subs = BTreeMap::from([
+            ("a".to_string(), subscription(&["t1"], &[("t1", &[0, 1, 2, 3])], 5)),
+            ("b".to_string(), subscription(&["t1"], &[], -1)),
+        ]);
+        let result = StickyAssignor.assign(&counts(&[("t1", 4)]), &subs);
+
+        assert_eq!(assigned(&result, "a"), tps(&[("t1", &[0, 1])]));
+        assert_eq!(assigned(&result, "b"), tps(&[("t1", &[2, 3])]));
+
+        // Cooperative: "b" gets nothing until "a" revokes
+        let result = CooperativeStickyAssignor.assign(&counts(&[("t1", 4)]), &subs);
+        assert_eq!(assigned(&result, "a"), tps(&[("t1", &[0, 1])]));
+        assert_eq!(assigned(&result, "b"), vec![]);
+    }
+
+    #[test]
+    fn test_sticky_claims() {
+        let subs = BTreeMap::from([
+            ("a".to_string(), subscription(&["t1"], &[("t1", &[0, 1])], 4)),
+            ("b".to_string(), subscription(&["t1"], &[("t1", &[1, 2])], 5)),
+            ("c".to_string(), subscription(&["t1"], &[("t1", &[3])], 5)),
+        ]);
+        let owners = current_owners(&counts(&[("t1", 6)]), &subs);
+
+        // "b" has the highest generation
+        assert_eq!(owners.get(&("t1", 1)), Some(&"b"));
+        assert_eq!(owners.get(&("t1", 0)), Some(&"a"));
+
+        let result = StickyAssignor.assign(&counts(&[("t1", 6)]), &subs);
+        for member in ["a", "b", "c"] {
+            assert_eq!(result[member].assigned_topic_partitions[0].partitions.len(), 2);
+        }
+        assert_eq!(assigned(&result, "b"), tps(&[("t1", &[1, 2])]));
+    }
+
+    #[test]
+    fn test_compare_with_group() {
+        let gm = GroupMetadata {
+            generation: 3,
+            protocol: "range".to_string(),
+            members: ["a", "b"]
+                .into_iter()
+                .zip([tps(&[("t1", &[0, 1, 2])]), tps(&[("t1", &[3])])])
+                .map(|(id, assigned_topic_partitions)| MemberMetadata {
+                    id: id.to_string(),
+                    subscription: subscription(&["t1"], &[], -1),
+                    assignment: ConsumerProtocolAssignment {
+                        assigned_topic_partitions,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let partition_counts = counts(&[("t1", 4)]);
+
+        // Same assignor, on a skewed assignment: partition 2 moves from "a" to "b"
+        let mut subs = group_subscriptions(&gm);
+        assert_eq!(subs["a"].generation_id, 3);
+        let range = assignor_by_name(&gm.protocol).unwrap();
+        let proposed = range.assign(&partition_counts, &subs);
+        assert_eq!(assigned(&proposed, "a"), tps(&[("t1", &[0, 1])]));
+        let comparison = AssignmentComparison::new(&gm, &proposed);
+        assert_eq!(comparison.reassigned_partitions, 1);
+        assert_eq!(comparison.members[1].added, tps(&[("t1", &[2])]));
+        assert!(!comparison.is_unchanged());
+
+        // Same assignor, on the assignment it produced: nothing changes
+        let mut balanced = gm.clone();
+        for member in balanced.members.iter_mut() {
+            member.assignment = proposed[&member.id].clone();
+        }
+        assert!(AssignmentComparison::new(&balanced, &range.assign(&partition_counts, &subs)).is_unchanged());
+
+        // Adding a member with sticky
+        subs.insert("c".to_string(), subscription(&["t1"], &[], -1));
+        let proposed = StickyAssignor.assign(&partition_counts, &subs);
+        let comparison = AssignmentComparison::new(&gm, &proposed);
+        assert_eq!(comparison.reassigned_partitions, 1);
+        assert_eq!(
+            comparison.members[0],
+            MemberAssignmentChange {
+                member_id: "a".to_string(),
+                kept: tps(&[("t1", &[0, 1])]),
+                added: vec![],
+                removed: tps(&[("t1", &[2])]),
+            }
+        );
+        assert_eq!(comparison.members[2].added, tps(&[("t1", &[2])]));
+
+        // Comparing with itself
+        let actual = gm.members.iter().map(|m| (m.id.clone(), m.assignment.clone())).collect();
+        assert!(AssignmentComparison::new(&gm, &actual).is_unchanged());
+    }
+
+    #[test]
+    fn test_assignor_by_name() {
+        for name in ["range", "roundrobin", "sticky", "cooperative-sticky"] {
+            assert_eq!(assignor_by_name(name).unwrap().name(), name);
+        }
+        assert!(assignor_by_name("consumer").is_none());
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<RangeAssignor>();
+        is_thread_safe::<RoundRobinAssignor>();
+        is_thread_safe::<StickyAssignor>();
+        is_thread_safe::<CooperativeStickyAssignor>();
+        is_thread_safe::<Box<dyn PartitionAssignor>>();
+        is_thread_safe::<MemberAssignmentChange>();
+        is_thread_safe::<AssignmentComparison>();
+    }
+}
@@ -0,0 +1,423 @@
+//! [Avro] schema mirroring the data model of this crate, and conversions from and to it.
+//!
+//! The schema is defined in [`AVRO_SCHEMA`] (also published as `schemas/konsumer_offsets.avsc`):
+//! [`encode`] and [`decode`] convert a whole [`KonsumerOffsetsData`] from and to a datum of it,
+//! failing with [`ConversionError`]:
+//!
+//! ```
+//! # use konsumer_offsets::{avro, KonsumerOffsetsData, GroupMetadata};
+//! let data = KonsumerOffsetsData::GroupMetadata(GroupMetadata::default());
+//! let bytes = avro::encode(&data);
+//! assert_eq!(avro::decode(&bytes).unwrap(), data);
+//! ```
+//!
+//! **NOTE:** Timestamps are encoded as `timestamp-millis`.
+//!
+//! [Avro]: https://avro.apache.org/
+
+use crate::errors::ConversionError;
+use crate::group_metadata::{
+    ConsumerProtocolAssignment, ConsumerProtocolSubscription, GroupMetadata, MemberMetadata, TopicPartitions,
+};
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::{timestamp_millis, try_timestamp_from_millis};
+
+/// [Avro] schema of the data model of this crate (also published as `schemas/konsumer_offsets.avsc`).
+///
+/// It mirrors [`KonsumerOffsetsData`] and all the types nested in it, field by field,
+/// with 16-bits integers encoded as `int` and timestamps as `timestamp-millis`.
+///
+/// [Avro]: https://avro.apache.org/
+pub const AVRO_SCHEMA: &str = include_str!("../schemas/konsumer_offsets.avsc");
+
+/// Encodes a [`KonsumerOffsetsData`] as an [Avro] datum of [`AVRO_SCHEMA`], using the binary encoding.
+///
+/// Only the datum is produced: wrapping it in an Object Container File, or prefixing it with
+/// a schema registry identifier, is up to the caller.
+///
+/// [Avro]: https://avro.apache.org/docs/current/specification/#binary-encoding
+pub fn encode(data: &KonsumerOffsetsData) -> Vec<u8> {
+    let mut w = AvroWriter::default();
+    match data {
+        KonsumerOffsetsData::OffsetCommit(oc) => {
+            w.long(0); //< union branch
+            write_offset_commit(&mut w, oc);
+        },
+        KonsumerOffsetsData::GroupMetadata(gm) => {
+            w.long(1); //< union branch
+            write_group_metadata(&mut w, gm);
+        },
+    }
+    w.buf
+}
+
+/// Decodes an [Avro] datum of [`AVRO_SCHEMA`], in binary encoding, into a [`KonsumerOffsetsData`].
+///
+/// This is the inverse of [`encode`].
+///
+/// [Avro]: https://avro.apache.org/docs/current/specification/#binary-encoding
+pub fn decode(bytes: &[u8]) -> Result<KonsumerOffsetsData, ConversionError> {
+    let mut r = AvroReader {
+        bytes,
+        pos: 0,
+    };
+    let data = match r.long()? {
+        0 => KonsumerOffsetsData::OffsetCommit(read_offset_commit(&mut r)?),
+        1 => KonsumerOffsetsData::GroupMetadata(read_group_metadata(&mut r)?),
+        b => return Err(ConversionError::Decoding(format!("invalid union branch {b}"))),
+    };
+
+    if r.pos != bytes.len() {
+        return Err(ConversionError::Decoding(format!("{} trailing bytes", bytes.len() - r.pos)));
+    }
+    Ok(data)
+}
+
+fn write_offset_commit(w: &mut AvroWriter, oc: &OffsetCommit) {
+    w.long(oc.message_version as i64);
+    w.string(&oc.group);
+    w.string(&oc.topic);
+    w.long(oc.partition as i64);
+    w.boolean(oc.is_tombstone);
+    w.long(oc.schema_version as i64);
+    w.long(oc.offset);
+    w.long(oc.leader_epoch as i64);
+    w.string(&oc.metadata);
+    w.long(timestamp_millis(&oc.commit_timestamp));
+    w.long(timestamp_millis(&oc.expire_timestamp));
+}
+
+fn read_offset_commit(r: &mut AvroReader) -> Result<OffsetCommit, ConversionError> {
+    Ok(OffsetCommit {
+        message_version: r.short("OffsetCommit.message_version")?,
+        group: r.string()?,
+        topic: r.string()?,
+        partition: r.int("OffsetCommit.partition")?,
+        is_tombstone: r.boolean()?,
+        schema_version: r.short("OffsetCommit.schema_version")?,
+        offset: r.long()?,
+        leader_epoch: r.int("OffsetCommit.leader_epoch")?,
+        metadata: r.string()?,
+        commit_timestamp: try_timestamp_from_millis(r.long()?)?,
+        expire_timestamp: try_timestamp_from_millis(r.long()?)?,
+    })
+}
+
+fn write_group_metadata(w: &mut AvroWriter, gm: &GroupMetadata) {
+    w.long(gm.message_version as i64);
+    w.string(&gm.group);
+    w.boolean(gm.is_tombstone);
+    w.long(gm.schema_version as i64);
+    w.string(&gm.protocol_type);
+    w.long(gm.generation as i64);
+    w.string(&gm.protocol);
+    w.string(&gm.leader);
+    w.long(timestamp_millis(&gm.current_state_timestamp));
+    w.array(&gm.members, |w, member| {
+        w.string(&member.id);
+        w.string(&member.group_instance_id);
+        w.string(&member.client_id);
+        w.string(&member.client_host);
+        w.long(member.rebalance_timeout as i64);
+        w.long(member.session_timeout as i64);
+
+        let subscription = &member.subscription;
+        w.long(subscription.schema_version as i64);
+        w.array(&subscription.subscribed_topics, |w, topic| w.string(topic));
+        w.bytes(&subscription.user_data);
+        w.array(&subscription.owned_topic_partitions, write_topic_partitions);
+        w.long(subscription.generation_id as i64);
+        w.string(&subscription.rack_id);
+
+        let assignment = &member.assignment;
+        w.long(assignment.schema_version as i64);
+        w.array(&assignment.assigned_topic_partitions, write_topic_partitions);
+        w.bytes(&assignment.user_data);
+    });
+}
+
+fn read_group_metadata(r: &mut AvroReader) -> Result<GroupMetadata, ConversionError> {
+    Ok(GroupMetadata {
+        message_version: r.short("GroupMetadata.message_version")?,
+        group: r.string()?,
+        is_tombstone: r.boolean()?,
+        schema_version: r.short("GroupMetadata.schema_version")?,
+        protocol_type: r.string()?,
+        generation: r.int("GroupMetadata.generation")?,
+        protocol: r.string()?,
+        leader: r.string()?,
+        current_state_timestamp: try_timestamp_from_millis(r.long()?)?,
+        members: r.array(|r| {
+            Ok(MemberMetadata {
+                id: r.string()?,
+                group_instance_id: r.string()?,
+                client_id: r.string()?,
+                client_host: r.string()?,
+                rebalance_timeout: r.int("MemberMetadata.rebalance_timeout")?,
+                session_timeout: r.int("MemberMetadata.session_timeout")?,
+                subscription: ConsumerProtocolSubscription {
+                    schema_version: r.short("ConsumerProtocolSubscription.schema_version")?,
+                    subscribed_topics: r.array(AvroReader::string)?,
+                    user_data: r.bytes()?.to_vec(),
+                    owned_topic_partitions: r.array(read_topic_partitions)?,
+                    generation_id: r.int("ConsumerProtocolSubscription.generation_id")?,
+                    rack_id: r.string()?,
+                },
+                assignment: ConsumerProtocolAssignment {
+                    schema_version: r.short("ConsumerProtocolAssignment.schema_version")?,
+                    assigned_topic_partitions: r.array(read_topic_partitions)?,
+                    user_data: r.bytes()?.to_vec(),
+                },
+            })
+        })?,
+    })
+}
+
+fn write_topic_partitions(w: &mut AvroWriter, tp: &TopicPartitions) {
+    w.string(&tp.topic);
+    w.array(&tp.partitions, |w, p| w.long(*p as i64));
+}
+
+fn read_topic_partitions(r: &mut AvroReader) -> Result<TopicPartitions, ConversionError> {
+    Ok(TopicPartitions {
+        topic: r.string()?,
+        partitions: r.array(|r| r.int("TopicPartitions.partitions"))?,
+    })
+}
+
+/// Writes primitive types in Avro binary encoding.
+#[derive(Default)]
+struct AvroWriter {
+    buf: Vec<u8>,
+}
+
+impl AvroWriter {
+    /// `int` and `long` are both written as zig-zag encoded variable-length integers.
+    fn long(&mut self, value: i64) {
+        let mut n = ((value << 1) ^ (value >> 63)) as u64;
+        while n >= 0x80 {
+            self.buf.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        self.buf.push(n as u8);
+    }
+
+    fn boolean(&mut self, value: bool) {
+        self.buf.push(value as u8);
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.long(value.len() as i64);
+        self.buf.extend_from_slice(value);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    /// Arrays are written as a single block, followed by the empty block that terminates them.
+    fn array<T>(&mut self, items: &[T], mut write_item: impl FnMut(&mut Self, &T)) {
+        if !items.is_empty() {
+            self.long(items.len() as i64);
+            for item in items {
+                write_item(self, item);
+            }
+        }
+        self.long(0);
+    }
+}
+
+/// Reads primitive types in Avro binary encoding.
+struct AvroReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> AvroReader<'a> {
+    fn eof() -> ConversionError {
+        ConversionError::Decoding("unexpected end of data".to_string())
+    }
+
+    fn byte(&mut self) -> Result<u8, ConversionError> {
+        let b = *self.bytes.get(self.pos).ok_or_else(Self::eof)?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn long(&mut self) -> Result<i64, ConversionError> {
+        let mut n: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= ((b & 0x7F) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok((n >> 1) as i64 ^ -((n & 1) as i64));
+            }
+        }
+        Err(ConversionError::Decoding("variable-length integer is too long".to_string()))
+    }
+
+    fn int(&mut self, field: &'static str) -> Result<i32, ConversionError> {
+        let value = self.long()?;
+        i32::try_from(value).map_err(|_| ConversionError::OutOfRange(field, value))
+    }
+
+    fn short(&mut self, field: &'static str) -> Result<i16, ConversionError> {
+        let value = self.long()?;
+        i16::try_from(value).map_err(|_| ConversionError::OutOfRange(field, value))
+    }
+
+    fn boolean(&mut self) -> Result<bool, ConversionError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(ConversionError::Decoding(format!("invalid boolean {b}"))),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], ConversionError> {
+        let len =
+            usize::try_from(self.long()?).map_err(|_| ConversionError::Decoding("negative length".to_string()))?;
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len()).ok_or_else(Self::eof)?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn string(&mut self) -> Result<String, ConversionError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|e| ConversionError::Decoding(e.to_string()))
+    }
+
+    /// Arrays are read block by block, until the empty block:
+    /// a negative block count is followed by the size in bytes of the block, that is ignored.
+    fn array<T>(
+        &mut self,
+        mut read_item: impl FnMut(&mut Self) -> Result<T, ConversionError>,
+    ) -> Result<Vec<T>, ConversionError> {
+        let mut items = Vec::new();
+        loop {
+            let count = match self.long()? {
+                0 => return Ok(items),
+                c if c < 0 => {
+                    self.long()?;
+                    c.unsigned_abs()
+                },
+                c => c as u64,
+            };
+            for _ in 0..count {
+                items.push(read_item(self)?);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::Value;
+
+    use super::AvroReader;
+    use super::{decode, encode, AVRO_SCHEMA};
+    use crate::utils::{is_thread_safe, read_fixtures};
+    use crate::{ConversionError, KonsumerOffsetsData};
+
+    /// Collects the named types defined in the schema, so they can be referenced by name.
+    fn collect_named(schema: &Value, named: &mut HashMap<String, Value>) {
+        match schema {
+            Value::Array(branches) => branches.iter().for_each(|b| collect_named(b, named)),
+            Value::Object(obj) => {
+                if obj["type"] == "record" {
+                    named.insert(obj["name"].as_str().unwrap().to_string(), schema.clone());
+                    obj["fields"].as_array().unwrap().iter().for_each(|f| collect_named(&f["type"], named));
+                } else if obj["type"] == "array" {
+                    collect_named(&obj["items"], named);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Generic decoder driven by the JSON schema, used to verify that the encoding
+    /// matches [`AVRO_SCHEMA`], independently of [`decode`].
+    fn decode_with_schema(r: &mut AvroReader, schema: &Value, named: &HashMap<String, Value>) -> Value {
+        match schema {
+            Value::String(name) => match name.as_str() {
+                "int" | "long" => r.long().unwrap().into(),
+                "boolean" => r.boolean().unwrap().into(),
+                "string" => r.string().unwrap().into(),
+                "bytes" => r.bytes().unwrap().len().into(),
+                other => decode_with_schema(r, &named[other], named),
+            },
+            Value::Array(branches) => {
+                let branch = r.long().unwrap() as usize;
+                decode_with_schema(r, &branches[branch], named)
+            },
+            Value::Object(obj) => match obj["type"].as_str().unwrap() {
+                "record" => {
+                    let mut record = serde_json::Map::new();
+                    for field in obj["fields"].as_array().unwrap() {
+                        let value = decode_with_schema(r, &field["type"], named);
+                        record.insert(field["name"].as_str().unwrap().to_string(), value);
+                    }
+                    Value::Object(record)
+                },
+                "array" => Value::Array(r.array(|r| Ok(decode_with_schema(r, &obj["items"], named))).unwrap()),
+                primitive => decode_with_schema(r, &Value::String(primitive.to_string()), named),
+            },
+            _ => panic!("unexpected schema {schema}"),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for data in read_fixtures() {
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_encoding_matches_schema() {
+        let schema: Value = serde_json::from_str(AVRO_SCHEMA).unwrap();
+        let mut named = HashMap::new();
+        collect_named(&schema, &mut named);
+
+        for data in read_fixtures() {
+            let bytes = encode(&data);
+            let mut r = AvroReader {
+                bytes: &bytes,
+                pos: 0,
+            };
+            let decoded = decode_with_schema(&mut r, &schema, &named);
+            assert_eq!(r.pos, bytes.len());
+
+            match data {
+                KonsumerOffsetsData::OffsetCommit(oc) => {
+                    assert_eq!(decoded["data"]["group"], oc.group.as_str());
+                    assert_eq!(decoded["data"]["offset"], oc.offset);
+                },
+                KonsumerOffsetsData::GroupMetadata(gm) => {
+                    assert_eq!(decoded["data"]["leader"], gm.leader.as_str());
+                    assert_eq!(decoded["data"]["members"].as_array().unwrap().len(), gm.members.len());
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(decode(&[]), Err(ConversionError::Decoding(_))));
+        assert!(matches!(decode(&[4]), Err(ConversionError::Decoding(_))));
+
+        let mut bytes = encode(&read_fixtures()[0]);
+        bytes.push(0);
+        assert!(matches!(decode(&bytes), Err(ConversionError::Decoding(_))));
+        bytes.truncate(bytes.len() - 2);
+        assert!(matches!(decode(&bytes), Err(ConversionError::Decoding(_))));
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ConversionError>();
+    }
+}
@@ -0,0 +1,32 @@
+use std::io;
+
+use konsumer_offsets::KonsumerOffsetsError;
+use thiserror::Error;
+
+/// Errors variants that can be encountered by the `konsumer-offsets` command line tool.
+#[derive(Error, Debug)]
+pub enum CliError {
+    /// No input was provided.
+    #[error("No input provided: use one of --key, --key-file or --dump")]
+    MissingInput,
+
+    /// Input text could not be decoded to bytes.
+    #[error("Failed to decode input: {0}")]
+    Decoding(String),
+
+    /// An I/O error occurred.
+    #[error("I/O error on {0}: {1}")]
+    Io(String, #[source] io::Error),
+
+    /// A record could not be read.
+    #[error("Invalid record at {0}: {1}")]
+    Record(String, String),
+
+    /// A record could not be parsed.
+    #[error("Failed to parse record at {0}: {1}")]
+    Parsing(String, #[source] KonsumerOffsetsError),
+
+    /// Output could not be serialized.
+    #[error("Failed to serialize output: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
@@ -0,0 +1,96 @@
+use clap::{Args, ValueEnum};
+use konsumer_offsets::KonsumerOffsetsData;
+
+/// Kind of record of `__consumer_offsets`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum RecordKind {
+    /// Records containing an `OffsetCommit`.
+    OffsetCommit,
+    /// Records containing a `GroupMetadata`.
+    GroupMetadata,
+}
+
+/// Filters applied to parsed records: only records matching all of them are output.
+#[derive(Debug, Args)]
+pub struct FilterArgs {
+    /// Only output records of this group (can be repeated).
+    #[arg(long = "group")]
+    groups: Vec<String>,
+
+    /// Only output records about this topic (can be repeated): the topic of an `OffsetCommit`,
+    /// or any topic subscribed or assigned to the members of a `GroupMetadata`.
+    #[arg(long = "topic")]
+    topics: Vec<String>,
+
+    /// Only output records of this kind.
+    #[arg(long, value_enum)]
+    kind: Option<RecordKind>,
+}
+
+impl FilterArgs {
+    /// `true` if the given [`KonsumerOffsetsData`] matches all the filters.
+    pub fn matches(&self, data: &KonsumerOffsetsData) -> bool {
+        if !self.groups.is_empty() && !self.groups.iter().any(|g| g == data.group()) {
+            return false;
+        }
+
+        match (self.kind, data) {
+            (Some(RecordKind::OffsetCommit), KonsumerOffsetsData::GroupMetadata(_)) => return false,
+            (Some(RecordKind::GroupMetadata), KonsumerOffsetsData::OffsetCommit(_)) => return false,
+            _ => {},
+        }
+
+        self.topics.is_empty() || self.topics.iter().any(|t| refers_to_topic(data, t))
+    }
+}
+
+fn refers_to_topic(data: &KonsumerOffsetsData, topic: &str) -> bool {
+    match data {
+        KonsumerOffsetsData::OffsetCommit(offset_commit) => offset_commit.topic == topic,
+        KonsumerOffsetsData::GroupMetadata(group_metadata) => group_metadata.members.iter().any(|m| {
+            m.subscription.subscribed_topics.iter().any(|t| t == topic)
+                || m.assignment.assigned_topic_partitions.iter().any(|tp| tp.topic == topic)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use konsumer_offsets::{GroupMetadata, OffsetCommit};
+
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let offset_commit = KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+            group: "g1".to_string(),
+            topic: "t1".to_string(),
+            ..Default::default()
+        });
+        let group_metadata = KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: "g2".to_string(),
+            ..Default::default()
+        });
+
+        let filter = FilterArgs {
+            groups: vec![],
+            topics: vec![],
+            kind: None,
+        };
+        assert!(filter.matches(&offset_commit) && filter.matches(&group_metadata));
+
+        let filter = FilterArgs {
+            groups: vec!["g1".to_string()],
+            topics: vec!["t1".to_string()],
+            kind: Some(RecordKind::OffsetCommit),
+        };
+        assert!(filter.matches(&offset_commit) && !filter.matches(&group_metadata));
+
+        let filter = FilterArgs {
+            groups: vec![],
+            topics: vec![],
+            kind: Some(RecordKind::GroupMetadata),
+        };
+        assert!(!filter.matches(&offset_commit) && filter.matches(&group_metadata));
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Lines, Read};
+use std::iter::{self, Enumerate};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use clap::{Args, ValueEnum};
+
+use crate::errors::CliError;
+
+/// Encoding of keys and payloads passed as text.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum Encoding {
+    /// Hexadecimal (case insensitive, optionally `0x` prefixed).
+    Hex,
+    /// Standard Base64 (with padding).
+    Base64,
+}
+
+impl Encoding {
+    /// Decodes the given text, according to [`Self`].
+    pub fn decode(&self, text: &str) -> Result<Vec<u8>, CliError> {
+        match self {
+            Encoding::Hex => decode_hex(text),
+            Encoding::Base64 => base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map_err(|e| CliError::Decoding(format!("invalid base64: {e}"))),
+        }
+    }
+}
+
+/// Where to read key/payload pairs from.
+#[derive(Debug, Args)]
+pub struct InputArgs {
+    /// Key of the record, encoded as per `--encoding`.
+    #[arg(long, group = "source")]
+    key: Option<String>,
+
+    /// Payload of the record, encoded as per `--encoding`. If omitted, the record is a tombstone.
+    #[arg(long, requires = "key")]
+    payload: Option<String>,
+
+    /// File containing the raw key bytes of the record.
+    #[arg(long, group = "source")]
+    key_file: Option<PathBuf>,
+
+    /// File containing the raw payload bytes of the record. If omitted, the record is a tombstone.
+    #[arg(long, requires = "key_file")]
+    payload_file: Option<PathBuf>,
+
+    /// Line-oriented dump file (`-` for stdin): one record per line, as `KEY PAYLOAD`
+    /// encoded as per `--encoding`, separated by whitespace. A missing payload,
+    /// `-` or `null` indicates a tombstone. Empty lines and lines starting with `#` are ignored.
+    #[arg(long, group = "source")]
+    dump: Option<PathBuf>,
+
+    /// Encoding of `--key`, `--payload` and of the `--dump` lines.
+    #[arg(long, value_enum, default_value_t = Encoding::Hex)]
+    encoding: Encoding,
+}
+
+/// A record of `__consumer_offsets`, as raw bytes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawRecord {
+    /// Where the record was read from (ex. line of a dump), used in error messages.
+    pub origin: String,
+    /// Raw key bytes.
+    pub key: Vec<u8>,
+    /// Raw payload bytes: `None` if tombstone.
+    pub payload: Option<Vec<u8>>,
+}
+
+impl InputArgs {
+    /// Reads the [`RawRecord`]s from the source selected by the arguments, one at a time.
+    ///
+    /// Failing to open the source is an error, while failing to read a single record
+    /// of the source (ex. a line of a dump) is reported as an error item.
+    /// A [`CliError::Io`] item means the source can't be read any further, and ends the iteration.
+    pub fn read(&self) -> Result<Box<dyn Iterator<Item = Result<RawRecord, CliError>>>, CliError> {
+        if let Some(key) = &self.key {
+            return Ok(Box::new(iter::once(Ok(RawRecord {
+                origin: "--key".to_string(),
+                key: self.encoding.decode(key)?,
+                payload: self.payload.as_deref().map(|p| self.encoding.decode(p)).transpose()?,
+            }))));
+        }
+
+        if let Some(key_file) = &self.key_file {
+            return Ok(Box::new(iter::once(Ok(RawRecord {
+                origin: key_file.display().to_string(),
+                key: read_file(key_file)?,
+                payload: self.payload_file.as_deref().map(read_file).transpose()?,
+            }))));
+        }
+
+        if let Some(dump) = &self.dump {
+            let reader: Box<dyn BufRead> = if dump.as_os_str() == "-" {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                Box::new(BufReader::new(fs::File::open(dump).map_err(|e| CliError::Io(dump.display().to_string(), e))?))
+            };
+            return Ok(Box::new(DumpRecords::new(reader, &dump.display().to_string(), self.encoding)));
+        }
+
+        Err(CliError::MissingInput)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, CliError> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .map_err(|e| CliError::Io(path.display().to_string(), e))?;
+    Ok(bytes)
+}
+
+/// Reads a line-oriented dump, one [`RawRecord`] per line: see [`InputArgs`] for the format.
+///
+/// Failing to read a line is reported as a [`CliError::Io`] item, after which the iteration ends.
+pub struct DumpRecords<R> {
+    lines: Enumerate<Lines<R>>,
+    name: String,
+    encoding: Encoding,
+    failed: bool,
+}
+
+impl<R: BufRead> DumpRecords<R> {
+    /// Create [`Self`], reading the dump called `name` from `reader`.
+    pub fn new(reader: R, name: &str, encoding: Encoding) -> Self {
+        Self {
+            lines: reader.lines().enumerate(),
+            name: name.to_string(),
+            encoding,
+            failed: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for DumpRecords<R> {
+    type Item = Result<RawRecord, CliError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        for (idx, line) in self.lines.by_ref() {
+            let origin = format!("{}:{}", self.name, idx + 1);
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(CliError::Io(origin, e)));
+                },
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            return Some(read_dump_line(line, origin, self.encoding));
+        }
+
+        None
+    }
+}
+
+fn read_dump_line(line: &str, origin: String, encoding: Encoding) -> Result<RawRecord, CliError> {
+    let decode = |text: &str| encoding.decode(text).map_err(|e| CliError::Record(origin.clone(), e.to_string()));
+
+    let mut fields = line.split_whitespace();
+    let key = fields.next().map(decode).transpose()?.unwrap_or_default();
+    let payload = match fields.next() {
+        None | Some("-") | Some("null") => None,
+        Some(p) => Some(decode(p)?),
+    };
+
+    Ok(RawRecord {
+        origin,
+        key,
+        payload,
+    })
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, CliError> {
+    let text = text.trim();
+    let text = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    if let Some(i) = text.bytes().position(|b| !b.is_ascii_hexdigit()) {
+        return Err(CliError::Decoding(format!("invalid hex: not a digit at {i}")));
+    }
+    if text.len() % 2 != 0 {
+        return Err(CliError::Decoding("invalid hex: odd number of digits".to_string()));
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|e| CliError::Decoding(format!("invalid hex: {e}"))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(Encoding::Hex.decode("0x00ff10").unwrap(), vec![0x00, 0xff, 0x10]);
+        assert_eq!(Encoding::Hex.decode("ABcd").unwrap(), vec![0xab, 0xcd]);
+        assert!(Encoding::Hex.decode("abc").is_err());
+        assert!(Encoding::Hex.decode("zz").is_err());
+        assert!(Encoding::Hex.decode("+f").is_err());
+        assert!(Encoding::Hex.decode("0x+f+f").is_err());
+        assert!(Encoding::Hex.decode("é0").is_err());
+        assert_eq!(Encoding::Base64.decode("AP8Q").unwrap(), vec![0x00, 0xff, 0x10]);
+    }
+
+    #[test]
+    fn test_read_dump() {
+        let dump = "# comment\n0001 0002\n\n0003\n0004 null\nzz\n";
+        let records = DumpRecords::new(dump.as_bytes(), "dump", Encoding::Hex).collect::<Vec<_>>();
+        assert_eq!(records.len(), 4);
+        assert!(records[3].is_err());
+
+        let records: Vec<RawRecord> = records.into_iter().filter_map(Result::ok).collect();
+        assert_eq!(records[0].origin, "dump:2");
+        assert_eq!(records[0].payload, Some(vec![0x00, 0x02]));
+        assert_eq!(records[1].payload, None);
+        assert_eq!(records[2].key, vec![0x00, 0x04]);
+        assert_eq!(records[2].payload, None);
+    }
+}
@@ -0,0 +1,145 @@
+//! # `konsumer-offsets`: decode Kafka `__consumer_offsets` records from the command line
+//!
+//! A thin command line tool around the `konsumer_offsets` crate, for when one needs to look at
+//! raw records of `__consumer_offsets`, without writing a throwaway program.
+//!
+//! Requires the `cli` feature:
+//!
+//! ```text
+//! cargo install konsumer_offsets --features cli
+//! konsumer-offsets decode --key 0001... --payload 0003... --format json
+//! ```
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use konsumer_offsets::{explain, KonsumerOffsetsData};
+
+use crate::errors::CliError;
+use crate::filter::FilterArgs;
+use crate::input::InputArgs;
+use crate::output::{OutputFormat, RecordWriter};
+
+mod errors;
+mod filter;
+mod input;
+mod output;
+
+/// Decode the content of the Kafka `__consumer_offsets` internal topic.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Parse key/payload pairs of `__consumer_offsets` records, and print them.
+    Decode(DecodeArgs),
+
+    /// Print an annotated byte map of key/payload pairs of `__consumer_offsets` records,
+    /// showing how each field was parsed (up to the failure point, for records that fail to parse).
+    Explain(ExplainArgs),
+}
+
+#[derive(Debug, Args)]
+struct DecodeArgs {
+    #[command(flatten)]
+    input: InputArgs,
+
+    #[command(flatten)]
+    filter: FilterArgs,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+    format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+struct ExplainArgs {
+    #[command(flatten)]
+    input: InputArgs,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Decode(args) => decode(&args),
+        Command::Explain(args) => explain_records(&args),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+/// Runs the `decode` subcommand.
+///
+/// Records that fail to parse are reported on stderr, without stopping the others from being output:
+/// returns `Ok(false)` if any such failure occurred.
+fn decode(args: &DecodeArgs) -> Result<bool, CliError> {
+    let mut all_parsed = true;
+    let mut writer = RecordWriter::new(io::stdout().lock(), args.format);
+
+    for raw in args.input.read()? {
+        let raw = match raw {
+            Ok(raw) => raw,
+            Err(e @ CliError::Io(..)) => return Err(e),
+            Err(e) => {
+                eprintln!("{e}");
+                all_parsed = false;
+                continue;
+            },
+        };
+
+        match KonsumerOffsetsData::try_from_bytes(Some(&raw.key), raw.payload.as_deref()) {
+            Ok(data) if args.filter.matches(&data) => writer.write(&data)?,
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("{}", CliError::Parsing(raw.origin, e));
+                all_parsed = false;
+            },
+        }
+    }
+
+    writer.finish()?;
+
+    Ok(all_parsed)
+}
+
+/// Runs the `explain` subcommand.
+///
+/// Returns `Ok(false)` if any record failed to be read or parsed.
+fn explain_records(args: &ExplainArgs) -> Result<bool, CliError> {
+    let mut all_parsed = true;
+    let mut stdout = io::stdout().lock();
+    let io_err = |e| CliError::Io("output".to_string(), e);
+
+    for raw in args.input.read()? {
+        let raw = match raw {
+            Ok(raw) => raw,
+            Err(e @ CliError::Io(..)) => return Err(e),
+            Err(e) => {
+                eprintln!("{e}");
+                all_parsed = false;
+                continue;
+            },
+        };
+
+        let explanation = explain(Some(&raw.key), raw.payload.as_deref());
+        all_parsed &= explanation.is_ok();
+        writeln!(stdout, "# {}\n{explanation}", raw.origin).map_err(io_err)?;
+    }
+
+    stdout.flush().map_err(io_err)?;
+
+    Ok(all_parsed)
+}
@@ -0,0 +1,179 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use konsumer_offsets::{JsonEnvelope, KonsumerOffsetsData};
+
+use crate::errors::CliError;
+
+/// Format used to output parsed records.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text, one record after the other.
+    Pretty,
+    /// A pretty-printed JSON array of records.
+    Json,
+    /// JSON Lines: one JSON record per line.
+    Jsonl,
+    /// JSON Lines of versioned envelopes, wrapping each record (see `JsonEnvelope`).
+    Envelope,
+    /// The same text printed by the Kafka `OffsetsMessageFormatter` and `GroupMetadataMessageFormatter`.
+    Kafka,
+}
+
+/// Writes records to an output as they come, in the given [`OutputFormat`].
+///
+/// [`Self::finish`] must be called once all records are written, to complete the output
+/// (ex. close the JSON array) and flush it.
+pub struct RecordWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    written: usize,
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// Create [`Self`], writing to `out` in the given [`OutputFormat`].
+    pub fn new(out: W, format: OutputFormat) -> Self {
+        Self {
+            out,
+            format,
+            written: 0,
+        }
+    }
+
+    /// Writes a single record.
+    pub fn write(&mut self, record: &KonsumerOffsetsData) -> Result<(), CliError> {
+        let out = &mut self.out;
+
+        match self.format {
+            OutputFormat::Pretty => {
+                writeln!(out, "{record:#?}").map_err(io_err)?;
+            },
+            OutputFormat::Json => {
+                // Same layout as `serde_json::to_writer_pretty` of the whole array.
+                let separator = if self.written == 0 {
+                    "["
+                } else {
+                    ","
+                };
+                let json = serde_json::to_string_pretty(record)?;
+                write!(out, "{separator}\n  {}", json.replace('\n', "\n  ")).map_err(io_err)?;
+            },
+            OutputFormat::Jsonl => {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out).map_err(io_err)?;
+            },
+            OutputFormat::Envelope => {
+                serde_json::to_writer(&mut *out, &JsonEnvelope::from(record.clone()))?;
+                writeln!(out).map_err(io_err)?;
+            },
+            OutputFormat::Kafka => {
+                let line = match record {
+                    KonsumerOffsetsData::OffsetCommit(oc) => oc.to_offsets_message_format(),
+                    KonsumerOffsetsData::GroupMetadata(gm) => gm.to_group_metadata_message_format(),
+                };
+                out.write_all(line.as_bytes()).map_err(io_err)?;
+            },
+        }
+
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Completes the output, and flushes it.
+    pub fn finish(mut self) -> Result<(), CliError> {
+        if self.format == OutputFormat::Json {
+            let close = if self.written == 0 {
+                "[]\n"
+            } else {
+                "\n]\n"
+            };
+            self.out.write_all(close.as_bytes()).map_err(io_err)?;
+        }
+
+        self.out.flush().map_err(io_err)
+    }
+}
+
+fn io_err(e: std::io::Error) -> CliError {
+    CliError::Io("output".to_string(), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use konsumer_offsets::GroupMetadata;
+
+    use super::*;
+
+    fn write_records(format: OutputFormat, records: &[KonsumerOffsetsData]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut writer = RecordWriter::new(&mut out, format);
+        for record in records {
+            writer.write(record).unwrap();
+        }
+        writer.finish().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_write_json() {
+        let records = [
+            KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                group: "g1".to_string(),
+                ..Default::default()
+            }),
+            KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                group: "g2".to_string(),
+                ..Default::default()
+            }),
+        ];
+
+        for count in 0..=records.len() {
+            let out = write_records(OutputFormat::Json, &records[..count]);
+            let expected = serde_json::to_string_pretty(&records[..count]).unwrap() + "\n";
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_write_jsonl() {
+        let records = [
+            KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                group: "g1".to_string(),
+                ..Default::default()
+            }),
+            KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                group: "g2".to_string(),
+                ..Default::default()
+            }),
+        ];
+
+        let out = write_records(OutputFormat::Jsonl, &records);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.starts_with("{\"GroupMetadata\":{\"message_version\":0,\"group\":\"g1\""));
+    }
+
+    #[test]
+    fn test_write_envelope() {
+        let records = [KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: "g1".to_string(),
+            ..Default::default()
+        })];
+
+        let out = write_records(OutputFormat::Envelope, &records);
+        let envelope: JsonEnvelope = serde_json::from_slice(&out).unwrap();
+        assert_eq!(envelope, JsonEnvelope::from(records[0].clone()));
+    }
+
+    #[test]
+    fn test_write_kafka() {
+        let records = [KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: "g1".to_string(),
+            is_tombstone: true,
+            ..Default::default()
+        })];
+
+        let out = write_records(OutputFormat::Kafka, &records);
+        assert_eq!(String::from_utf8(out).unwrap(), "g1::NULL\n");
+    }
+}
@@ -0,0 +1,859 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::errors::CaptureError;
+use crate::record::KonsumerOffsetsRecord;
+
+/// Magic bytes at the beginning (and end, if indexed) of a capture.
+pub const CAPTURE_MAGIC: [u8; 4] = *b"KOCF";
+
+/// Version of the capture format written by [`CaptureWriter`].
+pub const CAPTURE_VERSION: u16 = 1;
+
+/// Default number of records per block.
+pub const DEFAULT_CAPTURE_BLOCK_RECORDS: usize = 4096;
+
+/// Maximum length of the records bytes of a block, before compression (64 MiB).
+///
+/// [`CaptureWriter`] starts a new block before exceeding it, and [`CaptureReader`] rejects blocks
+/// declaring a larger length as corrupted: this bounds the memory used to read a capture.
+pub const MAX_CAPTURE_BLOCK_LEN: usize = 64 * 1024 * 1024;
+
+const BLOCK_TAG: u8 = b'B';
+const INDEX_TAG: u8 = b'I';
+const HEADER_LEN: u64 = 8;
+const FOOTER_LEN: i64 = 12;
+const INDEX_ENTRY_LEN: usize = 28;
+
+/// Compression applied to each block of a capture.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaptureCompression {
+    /// Blocks are stored as they are.
+    None,
+
+    /// Blocks are compressed with [LZ4](https://lz4.org/) (block format).
+    ///
+    /// Requires the `lz4` feature, both for writing and reading.
+    Lz4,
+}
+
+impl CaptureCompression {
+    fn id(&self) -> u8 {
+        match self {
+            CaptureCompression::None => 0,
+            CaptureCompression::Lz4 => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, CaptureError> {
+        match id {
+            0 => Ok(CaptureCompression::None),
+            1 => Ok(CaptureCompression::Lz4),
+            _ => Err(CaptureError::UnsupportedCompression(id)),
+        }
+    }
+
+    fn compress(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CaptureError> {
+        match self {
+            CaptureCompression::None => Ok(bytes),
+            #[cfg(feature = "lz4")]
+            CaptureCompression::Lz4 => Ok(lz4_flex::block::compress(&bytes)),
+            #[cfg(not(feature = "lz4"))]
+            CaptureCompression::Lz4 => Err(CaptureError::UnsupportedCompression(self.id())),
+        }
+    }
+
+    /// Maximum length, as stored, of a block of `uncompressed_len` bytes.
+    fn max_stored_len(&self, uncompressed_len: usize) -> usize {
+        match self {
+            CaptureCompression::None => uncompressed_len,
+            // Worst case of incompressible data, as computed by LZ4 `LZ4_compressBound`.
+            CaptureCompression::Lz4 => uncompressed_len + uncompressed_len / 255 + 16,
+        }
+    }
+
+    fn decompress(&self, bytes: Vec<u8>, uncompressed_len: usize) -> Result<Vec<u8>, CaptureError> {
+        match self {
+            CaptureCompression::None => Ok(bytes),
+            #[cfg(feature = "lz4")]
+            CaptureCompression::Lz4 => lz4_flex::block::decompress(&bytes, uncompressed_len)
+                .map_err(|e| CaptureError::Corrupted(format!("lz4 decompression failed: {e}"))),
+            #[cfg(not(feature = "lz4"))]
+            CaptureCompression::Lz4 => {
+                let _ = uncompressed_len;
+                Err(CaptureError::UnsupportedCompression(self.id()))
+            },
+        }
+    }
+}
+
+/// Configuration of a [`CaptureWriter`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaptureConfig {
+    /// Compression applied to each block.
+    ///
+    /// Default is [`CaptureCompression::None`].
+    pub compression: CaptureCompression,
+
+    /// Number of records per block: larger blocks compress better,
+    /// smaller blocks make seeking more precise.
+    ///
+    /// A block is also written earlier, if its records would exceed [`MAX_CAPTURE_BLOCK_LEN`] bytes.
+    ///
+    /// Default is [`DEFAULT_CAPTURE_BLOCK_RECORDS`].
+    pub block_records: usize,
+
+    /// Whether to write an index (and footer) when [`CaptureWriter::finish`] is called.
+    ///
+    /// Default is `true`.
+    pub index: bool,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            compression: CaptureCompression::None,
+            block_records: DEFAULT_CAPTURE_BLOCK_RECORDS,
+            index: true,
+        }
+    }
+}
+
+/// A raw record of `__consumer_offsets`, as stored in a capture.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CaptureRecord {
+    /// Partition of `__consumer_offsets` the record was read from.
+    pub partition: i32,
+
+    /// Offset of the record in [`Self::partition`].
+    pub offset: i64,
+
+    /// Timestamp of the record (milliseconds since Unix Epoch).
+    pub timestamp: i64,
+
+    /// Key of the record.
+    pub key: Option<Vec<u8>>,
+
+    /// Payload of the record: `None` if the record is a tombstone.
+    pub payload: Option<Vec<u8>>,
+}
+
+impl CaptureRecord {
+    /// Parses [`Self`] into a [`KonsumerOffsetsRecord`].
+    pub fn parse(&self) -> Result<KonsumerOffsetsRecord, CaptureError> {
+        Ok(KonsumerOffsetsRecord::try_from_bytes(
+            self.partition,
+            self.offset,
+            self.timestamp,
+            self.key.as_deref(),
+            self.payload.as_deref(),
+        )?)
+    }
+}
+
+/// Entry of the index of a capture: describes one block.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaptureIndexEntry {
+    /// Position of the block, from the start of the capture.
+    pub position: u64,
+
+    /// Number of records in the block.
+    pub record_count: u32,
+
+    /// Lowest record timestamp in the block.
+    pub min_timestamp: i64,
+
+    /// Highest record timestamp in the block.
+    pub max_timestamp: i64,
+}
+
+/// Streaming writer of captures.
+///
+/// Records are buffered until a block is complete, then written to the underlying [`Write`].
+/// Call [`Self::finish`] once done: it writes the last (partial) block and the index.
+/// A writer dropped without calling [`Self::finish`] loses the records of the last block.
+///
+/// # Format
+///
+/// A _capture_ stores a window of raw `__consumer_offsets` records, so that it can be replayed
+/// later (ex. in tests). Records are stored exactly as they were read from Kafka, and are parsed
+/// into [`KonsumerOffsetsRecord`] only when read back.
+///
+/// All integers are big-endian, like in the Kafka protocol. A capture (version `1`) is laid out as:
+///
+/// ```text
+/// Header:
+///   magic              [u8; 4] = "KOCF"
+///   version            u16     = 1
+///   compression        u8      (0 = none, 1 = lz4)
+///   flags              u8      = 0 (reserved)
+///
+/// Block (repeated):
+///   tag                u8      = 'B'
+///   record_count       u32
+///   uncompressed_len   u32     (length of the records bytes, before compression)
+///   stored_len         u32     (length of the records bytes, as stored)
+///   crc32              u32     (CRC-32/ISO-HDLC of the stored bytes)
+///   records            [u8; stored_len]
+///
+/// Record (repeated record_count times, inside the uncompressed records bytes):
+///   partition          i32
+///   offset             i64
+///   timestamp          i64     (milliseconds since Unix Epoch)
+///   key_len            i32     (-1 if the record has no key)
+///   key                [u8; key_len]
+///   payload_len        i32     (-1 if the record is a tombstone)
+///   payload            [u8; payload_len]
+///
+/// Index (optional, once, after the last block):
+///   tag                u8      = 'I'
+///   entry_count        u32
+///   entries            [IndexEntry; entry_count]
+///   crc32              u32     (CRC-32/ISO-HDLC of the entries bytes)
+///
+/// IndexEntry:
+///   position           u64     (position of the block tag, from the start of the capture)
+///   record_count       u32
+///   min_timestamp      i64
+///   max_timestamp      i64
+///
+/// Footer (only if there is an index):
+///   index_position     u64
+///   magic              [u8; 4] = "KOCF"
+/// ```
+///
+/// A capture that was not finished (ex. the process capturing it crashed) lacks the index and the footer:
+/// all its complete blocks can still be read sequentially.
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+    config: CaptureConfig,
+    position: u64,
+    block: Vec<u8>,
+    block_index: Option<CaptureIndexEntry>,
+    index: Vec<CaptureIndexEntry>,
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Create [`Self`], writing the capture header to `writer`.
+    pub fn new(mut writer: W, config: CaptureConfig) -> Result<Self, CaptureError> {
+        // Fail early if compression is not available.
+        config.compression.compress(Vec::new())?;
+
+        writer.write_all(&CAPTURE_MAGIC)?;
+        writer.write_all(&CAPTURE_VERSION.to_be_bytes())?;
+        writer.write_all(&[config.compression.id(), 0])?;
+
+        Ok(Self {
+            writer,
+            config,
+            position: HEADER_LEN,
+            block: Vec::new(),
+            block_index: None,
+            index: Vec::new(),
+        })
+    }
+
+    /// Appends a raw record to the capture.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition` - Partition of `__consumer_offsets` the record was read from.
+    /// * `offset` - Offset of the record.
+    /// * `timestamp` - Timestamp of the record (milliseconds since Unix Epoch).
+    /// * `key` - Key of the record.
+    /// * `payload` - Payload of the record: `None` if the record is a tombstone.
+    ///
+    /// Fails with [`CaptureError::RecordTooLarge`] if the record alone exceeds [`MAX_CAPTURE_BLOCK_LEN`].
+    pub fn append(
+        &mut self,
+        partition: i32,
+        offset: i64,
+        timestamp: i64,
+        key: Option<&[u8]>,
+        payload: Option<&[u8]>,
+    ) -> Result<(), CaptureError> {
+        let record_len = 4 + 8 + 8 + 4 + key.map_or(0, <[u8]>::len) + 4 + payload.map_or(0, <[u8]>::len);
+        if record_len > MAX_CAPTURE_BLOCK_LEN {
+            return Err(CaptureError::RecordTooLarge(record_len));
+        }
+        if self.block.len() + record_len > MAX_CAPTURE_BLOCK_LEN {
+            self.flush_block()?;
+        }
+
+        self.block.extend_from_slice(&partition.to_be_bytes());
+        self.block.extend_from_slice(&offset.to_be_bytes());
+        self.block.extend_from_slice(&timestamp.to_be_bytes());
+        write_optional_bytes(&mut self.block, key);
+        write_optional_bytes(&mut self.block, payload);
+
+        let entry = self.block_index.get_or_insert(CaptureIndexEntry {
+            position: self.position,
+            record_count: 0,
+            min_timestamp: timestamp,
+            max_timestamp: timestamp,
+        });
+        entry.record_count += 1;
+        entry.min_timestamp = entry.min_timestamp.min(timestamp);
+        entry.max_timestamp = entry.max_timestamp.max(timestamp);
+
+        if entry.record_count as usize >= self.config.block_records.max(1) {
+            self.flush_block()?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends a [`CaptureRecord`] to the capture.
+    pub fn append_record(&mut self, record: &CaptureRecord) -> Result<(), CaptureError> {
+        self.append(record.partition, record.offset, record.timestamp, record.key.as_deref(), record.payload.as_deref())
+    }
+
+    /// Writes the last block and, if configured, the index and footer, returning the underlying [`Write`].
+    pub fn finish(mut self) -> Result<W, CaptureError> {
+        self.flush_block()?;
+
+        if self.config.index {
+            let mut entries = Vec::with_capacity(self.index.len() * INDEX_ENTRY_LEN);
+            for entry in &self.index {
+                entries.extend_from_slice(&entry.position.to_be_bytes());
+                entries.extend_from_slice(&entry.record_count.to_be_bytes());
+                entries.extend_from_slice(&entry.min_timestamp.to_be_bytes());
+                entries.extend_from_slice(&entry.max_timestamp.to_be_bytes());
+            }
+
+            self.writer.write_all(&[INDEX_TAG])?;
+            self.writer.write_all(&(self.index.len() as u32).to_be_bytes())?;
+            self.writer.write_all(&entries)?;
+            self.writer.write_all(&crc32(&entries).to_be_bytes())?;
+            self.writer.write_all(&self.position.to_be_bytes())?;
+            self.writer.write_all(&CAPTURE_MAGIC)?;
+        }
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn flush_block(&mut self) -> Result<(), CaptureError> {
+        let Some(entry) = self.block_index.take() else {
+            return Ok(());
+        };
+
+        let uncompressed_len = self.block.len() as u32;
+        let stored = self.config.compression.compress(std::mem::take(&mut self.block))?;
+
+        self.writer.write_all(&[BLOCK_TAG])?;
+        self.writer.write_all(&entry.record_count.to_be_bytes())?;
+        self.writer.write_all(&uncompressed_len.to_be_bytes())?;
+        self.writer.write_all(&(stored.len() as u32).to_be_bytes())?;
+        self.writer.write_all(&crc32(&stored).to_be_bytes())?;
+        self.writer.write_all(&stored)?;
+
+        self.position += 17 + stored.len() as u64;
+        self.index.push(entry);
+
+        Ok(())
+    }
+}
+
+/// Streaming reader of captures.
+///
+/// Iterating over it yields the raw [`CaptureRecord`]s, in the order they were written;
+/// use [`Self::parsed`] to iterate over [`KonsumerOffsetsRecord`]s instead.
+pub struct CaptureReader<R: Read> {
+    reader: R,
+    compression: CaptureCompression,
+    block: Vec<u8>,
+    cursor: usize,
+    remaining: u32,
+    skip_before: Option<i64>,
+    done: bool,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Create [`Self`], reading and validating the capture header from `reader`.
+    pub fn new(mut reader: R) -> Result<Self, CaptureError> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != CAPTURE_MAGIC {
+            return Err(CaptureError::InvalidMagic);
+        }
+
+        let version = u16::from_be_bytes([header[4], header[5]]);
+        if version != CAPTURE_VERSION {
+            return Err(CaptureError::UnsupportedVersion(version));
+        }
+
+        Ok(Self {
+            reader,
+            compression: CaptureCompression::from_id(header[6])?,
+            block: Vec::new(),
+            cursor: 0,
+            remaining: 0,
+            skip_before: None,
+            done: false,
+        })
+    }
+
+    /// Compression used by the capture.
+    pub fn compression(&self) -> CaptureCompression {
+        self.compression
+    }
+
+    /// Turns [`Self`] into an iterator of parsed [`KonsumerOffsetsRecord`]s.
+    pub fn parsed(self) -> impl Iterator<Item = Result<KonsumerOffsetsRecord, CaptureError>> {
+        self.map(|record| record.and_then(|r| r.parse()))
+    }
+
+    /// Reads the next block, returning `false` if there are no more blocks.
+    fn read_block(&mut self) -> Result<bool, CaptureError> {
+        let mut tag = [0u8; 1];
+        if self.reader.read(&mut tag)? == 0 || tag[0] == INDEX_TAG {
+            return Ok(false);
+        }
+        if tag[0] != BLOCK_TAG {
+            return Err(CaptureError::Corrupted(format!("unexpected section tag {:#04x}", tag[0])));
+        }
+
+        let mut header = [0u8; 16];
+        self.reader.read_exact(&mut header)?;
+        let record_count = u32::from_be_bytes(header[0..4].try_into().unwrap());
+        let uncompressed_len = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        let stored_len = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+        let checksum = u32::from_be_bytes(header[12..16].try_into().unwrap());
+
+        // Validate lengths before allocating for them.
+        if uncompressed_len > MAX_CAPTURE_BLOCK_LEN || stored_len > self.compression.max_stored_len(uncompressed_len) {
+            return Err(CaptureError::Corrupted(format!(
+                "block lengths out of bounds: {uncompressed_len} bytes, {stored_len} stored"
+            )));
+        }
+
+        let mut stored = vec![0u8; stored_len];
+        self.reader.read_exact(&mut stored)?;
+        if crc32(&stored) != checksum {
+            return Err(CaptureError::Corrupted("block checksum mismatch".to_string()));
+        }
+
+        self.block = self.compression.decompress(stored, uncompressed_len)?;
+        if self.block.len() != uncompressed_len {
+            return Err(CaptureError::Corrupted("block length mismatch".to_string()));
+        }
+        self.cursor = 0;
+        self.remaining = record_count;
+
+        Ok(true)
+    }
+
+    fn read_record(&mut self) -> Result<CaptureRecord, CaptureError> {
+        let partition = i32::from_be_bytes(self.take::<4>()?);
+        let offset = i64::from_be_bytes(self.take::<8>()?);
+        let timestamp = i64::from_be_bytes(self.take::<8>()?);
+        let key = self.take_optional_bytes()?;
+        let payload = self.take_optional_bytes()?;
+        self.remaining -= 1;
+
+        Ok(CaptureRecord {
+            partition,
+            offset,
+            timestamp,
+            key,
+            payload,
+        })
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], CaptureError> {
+        let bytes = self
+            .block
+            .get(self.cursor..self.cursor + N)
+            .ok_or_else(|| CaptureError::Corrupted("record exceeds block".to_string()))?;
+        self.cursor += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn take_optional_bytes(&mut self) -> Result<Option<Vec<u8>>, CaptureError> {
+        let len = i32::from_be_bytes(self.take::<4>()?);
+        if len < 0 {
+            return Ok(None);
+        }
+
+        let bytes = self
+            .block
+            .get(self.cursor..self.cursor + len as usize)
+            .ok_or_else(|| CaptureError::Corrupted("record exceeds block".to_string()))?;
+        self.cursor += len as usize;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+impl<R: Read + Seek> CaptureReader<R> {
+    /// Reads the index of the capture, leaving the position of the reader unchanged.
+    ///
+    /// Returns `None` if the capture has no index (ex. it was not finished).
+    pub fn index(&mut self) -> Result<Option<Vec<CaptureIndexEntry>>, CaptureError> {
+        let current = self.reader.stream_position()?;
+        let index = self.read_index();
+        self.reader.seek(SeekFrom::Start(current))?;
+        index
+    }
+
+    /// Positions the reader at the first block that contains records with timestamp `>= timestamp`.
+    ///
+    /// Records of that block with an earlier timestamp are skipped. Requires the capture to have an index:
+    /// returns `false` (and leaves the reader unchanged) if it has none.
+    pub fn seek_timestamp(&mut self, timestamp: i64) -> Result<bool, CaptureError> {
+        let Some(index) = self.index()? else {
+            return Ok(false);
+        };
+
+        match index.iter().find(|entry| entry.max_timestamp >= timestamp) {
+            Some(entry) => {
+                self.reader.seek(SeekFrom::Start(entry.position))?;
+                self.done = false;
+            },
+            None => self.done = true,
+        }
+        self.block.clear();
+        self.remaining = 0;
+        self.skip_before = Some(timestamp);
+
+        Ok(true)
+    }
+
+    fn read_index(&mut self) -> Result<Option<Vec<CaptureIndexEntry>>, CaptureError> {
+        let len = self.reader.seek(SeekFrom::End(0))?;
+        if len < HEADER_LEN + FOOTER_LEN as u64 {
+            return Ok(None);
+        }
+
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        self.reader.seek(SeekFrom::End(-FOOTER_LEN))?;
+        self.reader.read_exact(&mut footer)?;
+        if footer[8..12] != CAPTURE_MAGIC {
+            return Ok(None);
+        }
+
+        let index_position = u64::from_be_bytes(footer[0..8].try_into().unwrap());
+        self.reader.seek(SeekFrom::Start(index_position))?;
+
+        let mut header = [0u8; 5];
+        self.reader.read_exact(&mut header)?;
+        if header[0] != INDEX_TAG {
+            return Err(CaptureError::Corrupted("footer does not point to the index".to_string()));
+        }
+
+        // Validate the length of the entries against what is left of the capture, before allocating for them.
+        let entry_count = u32::from_be_bytes(header[1..5].try_into().unwrap()) as usize;
+        let entries_len = entry_count
+            .checked_mul(INDEX_ENTRY_LEN)
+            .filter(|entries_len| index_position.saturating_add(5 + *entries_len as u64 + 4) <= len - FOOTER_LEN as u64)
+            .ok_or_else(|| CaptureError::Corrupted(format!("index entry count out of bounds: {entry_count}")))?;
+        let mut entries = vec![0u8; entries_len];
+        self.reader.read_exact(&mut entries)?;
+        let mut checksum = [0u8; 4];
+        self.reader.read_exact(&mut checksum)?;
+        if crc32(&entries) != u32::from_be_bytes(checksum) {
+            return Err(CaptureError::Corrupted("index checksum mismatch".to_string()));
+        }
+
+        Ok(Some(
+            entries
+                .chunks_exact(INDEX_ENTRY_LEN)
+                .map(|e| CaptureIndexEntry {
+                    position: u64::from_be_bytes(e[0..8].try_into().unwrap()),
+                    record_count: u32::from_be_bytes(e[8..12].try_into().unwrap()),
+                    min_timestamp: i64::from_be_bytes(e[12..20].try_into().unwrap()),
+                    max_timestamp: i64::from_be_bytes(e[20..28].try_into().unwrap()),
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CaptureRecord, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            if self.remaining == 0 {
+                // Earlier records are skipped only within the block `seek_timestamp` landed on.
+                if !self.block.is_empty() {
+                    self.skip_before = None;
+                }
+
+                match self.read_block() {
+                    Ok(true) => continue,
+                    Ok(false) => {
+                        self.done = true;
+                        return None;
+                    },
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    },
+                }
+            }
+
+            match self.read_record() {
+                Ok(record) if self.skip_before.is_some_and(|ts| record.timestamp < ts) => continue,
+                Ok(record) => return Some(Ok(record)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                },
+            }
+        }
+    }
+}
+
+fn write_optional_bytes(buffer: &mut Vec<u8>, bytes: Option<&[u8]>) {
+    match bytes {
+        Some(bytes) => {
+            buffer.extend_from_slice(&(bytes.len() as i32).to_be_bytes());
+            buffer.extend_from_slice(bytes);
+        },
+        None => buffer.extend_from_slice(&(-1i32).to_be_bytes()),
+    }
+}
+
+/// CRC-32/ISO-HDLC (the one of zlib, PNG, etc.).
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::crc32;
+    use crate::utils::{is_thread_safe, read_fixture_bytes};
+    use crate::{
+        CaptureCompression, CaptureConfig, CaptureError, CaptureReader, CaptureRecord, CaptureWriter,
+        KonsumerOffsetsData, MAX_CAPTURE_BLOCK_LEN,
+    };
+
+    fn fixture_records() -> Vec<CaptureRecord> {
+        let mut records = Vec::new();
+        for (idx, (name, id)) in
+            [("offset_commit", 1), ("group_metadata", 1), ("offset_commit", 2), ("group_metadata", 4)]
+                .iter()
+                .enumerate()
+        {
+            let (key, payload) = read_fixture_bytes(name, *id);
+            records.push(CaptureRecord {
+                partition: idx as i32 % 2,
+                offset: idx as i64,
+                timestamp: 1000 + idx as i64 * 10,
+                key: Some(key),
+                payload: Some(payload),
+            });
+        }
+        records.push(CaptureRecord {
+            payload: None,
+            offset: 4,
+            timestamp: 1040,
+            ..records[0].clone()
+        });
+        records
+    }
+
+    fn write_capture(records: &[CaptureRecord], config: CaptureConfig) -> Vec<u8> {
+        let mut writer = CaptureWriter::new(Vec::new(), config).unwrap();
+        for record in records {
+            writer.append_record(record).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let records = fixture_records();
+        let capture = write_capture(
+            &records,
+            CaptureConfig {
+                block_records: 2,
+                ..Default::default()
+            },
+        );
+
+        let read = CaptureReader::new(Cursor::new(&capture)).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read, records);
+
+        let parsed =
+            CaptureReader::new(Cursor::new(&capture)).unwrap().parsed().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(parsed.len(), 5);
+        assert!(matches!(parsed[1].data, KonsumerOffsetsData::GroupMetadata(_)));
+        assert!(parsed[4].data.is_tombstone());
+        assert_eq!((parsed[3].partition, parsed[3].offset, parsed[3].timestamp), (1, 3, 1030));
+    }
+
+    #[test]
+    fn test_index_and_seek() {
+        let records = fixture_records();
+        let capture = write_capture(
+            &records,
+            CaptureConfig {
+                block_records: 2,
+                ..Default::default()
+            },
+        );
+
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        let index = reader.index().unwrap().unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.iter().map(|e| e.record_count).sum::<u32>(), 5);
+        assert_eq!((index[1].min_timestamp, index[1].max_timestamp), (1020, 1030));
+
+        // Reading the index does not move the reader.
+        assert_eq!(reader.next().unwrap().unwrap(), records[0]);
+
+        assert!(reader.seek_timestamp(1030).unwrap());
+        let offsets = reader.map(|r| r.unwrap().offset).collect::<Vec<_>>();
+        assert_eq!(offsets, vec![3, 4]);
+
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert!(reader.seek_timestamp(5000).unwrap());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_unfinished_capture() {
+        let records = fixture_records();
+        let mut writer = CaptureWriter::new(
+            Vec::new(),
+            CaptureConfig {
+                block_records: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for record in &records {
+            writer.append_record(record).unwrap();
+        }
+        // Simulate a crash: only the complete blocks made it to the underlying writer.
+        let capture = writer.writer.clone();
+
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert_eq!(reader.index().unwrap(), None);
+        assert!(!reader.seek_timestamp(1000).unwrap());
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), records[..4]);
+    }
+
+    #[test]
+    fn test_without_index() {
+        let records = fixture_records();
+        let capture = write_capture(
+            &records,
+            CaptureConfig {
+                index: false,
+                ..Default::default()
+            },
+        );
+
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert_eq!(reader.index().unwrap(), None);
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), records);
+    }
+
+    #[test]
+    fn test_invalid_captures() {
+        assert!(matches!(CaptureReader::new(Cursor::new(b"NOPE\x00\x01\x00\x00")), Err(CaptureError::InvalidMagic)));
+        assert!(matches!(
+            CaptureReader::new(Cursor::new(b"KOCF\x00\x09\x00\x00")),
+            Err(CaptureError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            CaptureReader::new(Cursor::new(b"KOCF\x00\x01\x07\x00")),
+            Err(CaptureError::UnsupportedCompression(7))
+        ));
+
+        let mut capture = write_capture(&fixture_records(), CaptureConfig::default());
+        capture[30] ^= 0xFF;
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert!(matches!(reader.next(), Some(Err(CaptureError::Corrupted(_)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_out_of_bounds_lengths() {
+        // Block declaring 4 GiB of records
+        let mut capture = write_capture(&fixture_records(), CaptureConfig::default());
+        capture[13..17].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert!(matches!(reader.next(), Some(Err(CaptureError::Corrupted(_)))));
+
+        // Index declaring more entries than the capture can hold
+        let mut capture = write_capture(&fixture_records(), CaptureConfig::default());
+        let index_position = u64::from_be_bytes(capture[capture.len() - 12..capture.len() - 4].try_into().unwrap());
+        let entry_count_position = index_position as usize + 1;
+        capture[entry_count_position..entry_count_position + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut reader = CaptureReader::new(Cursor::new(&capture)).unwrap();
+        assert!(matches!(reader.index(), Err(CaptureError::Corrupted(_))));
+    }
+
+    #[test]
+    fn test_record_too_large() {
+        let mut writer = CaptureWriter::new(Vec::new(), CaptureConfig::default()).unwrap();
+        let payload = vec![0u8; MAX_CAPTURE_BLOCK_LEN];
+        assert!(matches!(
+            writer.append(0, 0, 0, Some(b"key"), Some(&payload)),
+            Err(CaptureError::RecordTooLarge(len)) if len == MAX_CAPTURE_BLOCK_LEN + 31
+        ));
+        // Nothing was written, but the header and the empty index
+        assert_eq!(writer.finish().unwrap().len(), 8 + 1 + 4 + 4 + 12);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn test_lz4_round_trip() {
+        let records = fixture_records();
+        let config = CaptureConfig {
+            compression: CaptureCompression::Lz4,
+            ..Default::default()
+        };
+        let compressed = write_capture(&records, config);
+        let uncompressed = write_capture(&records, CaptureConfig::default());
+        assert!(compressed.len() < uncompressed.len());
+
+        let reader = CaptureReader::new(Cursor::new(&compressed)).unwrap();
+        assert_eq!(reader.compression(), CaptureCompression::Lz4);
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), records);
+    }
+
+    #[cfg(not(feature = "lz4"))]
+    #[test]
+    fn test_lz4_unavailable() {
+        let config = CaptureConfig {
+            compression: CaptureCompression::Lz4,
+            ..Default::default()
+        };
+        assert!(matches!(CaptureWriter::new(Vec::new(), config), Err(CaptureError::UnsupportedCompression(1))));
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<CaptureCompression>();
+        is_thread_safe::<CaptureConfig>();
+        is_thread_safe::<CaptureRecord>();
+        is_thread_safe::<CaptureWriter<Vec<u8>>>();
+        is_thread_safe::<CaptureReader<Cursor<Vec<u8>>>>();
+    }
+}
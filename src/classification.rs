@@ -0,0 +1,420 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bytes_parser::BytesParser;
+
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::utils::{parse_i16, parse_str};
+
+/// Types of the records that only the new Group Coordinator writes, with the [`GroupKind`] of the groups
+/// they are written for: [KIP-848] consumer groups and [KIP-932] share groups.
+///
+/// The comment on each type is the name of the record key, as in the `group-coordinator` module of Kafka.
+///
+/// [KIP-848]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-848%3A+The+Next+Generation+of+the+Consumer+Rebalance+Protocol
+/// [KIP-932]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-932%3A+Queues+for+Kafka
+const COORDINATOR_RECORD_TYPES: [(i16, GroupKind); 14] = [
+    (3, GroupKind::Kip848Consumer),  // ConsumerGroupMetadataKey
+    (4, GroupKind::Kip848Consumer),  // ConsumerGroupPartitionMetadataKey
+    (5, GroupKind::Kip848Consumer),  // ConsumerGroupMemberMetadataKey
+    (6, GroupKind::Kip848Consumer),  // ConsumerGroupTargetAssignmentMetadataKey
+    (7, GroupKind::Kip848Consumer),  // ConsumerGroupTargetAssignmentMemberKey
+    (8, GroupKind::Kip848Consumer),  // ConsumerGroupCurrentMemberAssignmentKey
+    (9, GroupKind::Share),           // ShareGroupPartitionMetadataKey
+    (10, GroupKind::Share),          // ShareGroupMemberMetadataKey
+    (11, GroupKind::Share),          // ShareGroupMetadataKey
+    (12, GroupKind::Share),          // ShareGroupTargetAssignmentMetadataKey
+    (13, GroupKind::Share),          // ShareGroupTargetAssignmentMemberKey
+    (14, GroupKind::Share),          // ShareGroupCurrentMemberAssignmentKey
+    (15, GroupKind::Share),          // ShareGroupStatePartitionMetadataKey
+    (16, GroupKind::Kip848Consumer), // ConsumerGroupRegularExpressionKey
+];
+
+/// [`GroupKind`] of the groups a record type, only written by the new Group Coordinator, is written for.
+fn coordinator_record_kind(record_type: i16) -> Option<GroupKind> {
+    COORDINATOR_RECORD_TYPES.iter().find(|(t, _)| *t == record_type).map(|(_, kind)| *kind)
+}
+
+/// Suffix of the internal repartition topics that Kafka Streams applications consume from.
+const STREAMS_REPARTITION_TOPIC_SUFFIX: &str = "-repartition";
+
+/// What kind of client a group is used by, as told by [`GroupClassifier`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroupKind {
+    /// Consumers using the _classic_ rebalance protocol (`protocol_type == consumer`).
+    ClassicConsumer,
+
+    /// Consumers that `assign()` partitions manually, and use the group only to commit offsets
+    /// (ex. Flink, Spark): the group has no members, so only [`KonsumerOffsetsData::OffsetCommit`] are found.
+    Simple,
+
+    /// Kafka Connect workers (`protocol_type == connect`).
+    Connect,
+
+    /// Kafka Streams application (`protocol == stream`, the `StreamsPartitionAssignor`).
+    Streams,
+
+    /// Confluent Schema Registry instances, electing their leader (`protocol_type == sr`).
+    SchemaRegistry,
+
+    /// Consumers using the [KIP-848] rebalance protocol (`group.protocol=consumer`).
+    ///
+    /// [KIP-848]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-848%3A+The+Next+Generation+of+the+Consumer+Rebalance+Protocol
+    Kip848Consumer,
+
+    /// Share group ([KIP-932]).
+    ///
+    /// [KIP-932]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-932%3A+Queues+for+Kafka
+    Share,
+
+    /// Not enough (or unexpected) evidence to tell.
+    Unknown,
+}
+
+/// A fact observed in `__consumer_offsets`, used by [`GroupClassifier`] to tell the [`GroupKind`] of a group.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassificationEvidence {
+    /// [`GroupMetadata::protocol_type`](crate::GroupMetadata::protocol_type) of the latest group metadata.
+    ProtocolType(String),
+
+    /// Latest non-empty [`GroupMetadata::protocol`](crate::GroupMetadata::protocol).
+    Protocol(String),
+
+    /// Number of offset commits found, without any group metadata.
+    OffsetCommitsWithoutGroupMetadata(usize),
+
+    /// Offsets are committed for this Kafka Streams repartition topic.
+    RepartitionTopic(String),
+
+    /// Type (i.e. key version) of a record, not parsed by [`KonsumerOffsetsData`],
+    /// that only the new Group Coordinator writes.
+    RecordType(i16),
+}
+
+/// The [`GroupKind`] of a group, and the [`ClassificationEvidence`] it was told from.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupClassification {
+    /// Group.
+    pub group: String,
+
+    /// Kind of the group.
+    pub kind: GroupKind,
+
+    /// Evidence the kind was told from.
+    pub evidence: Vec<ClassificationEvidence>,
+}
+
+/// What was observed about a group so far.
+#[derive(Debug, Default, Clone)]
+struct GroupObservations {
+    /// `None` until a (non-tombstone) group metadata is found.
+    protocol_type: Option<String>,
+    protocol: String,
+    offset_commits: usize,
+    repartition_topic: Option<String>,
+    record_types: BTreeSet<i16>,
+}
+
+impl GroupObservations {
+    fn classify(&self, group: &str) -> GroupClassification {
+        let of_kind = |kind: GroupKind| -> Vec<ClassificationEvidence> {
+            self.record_types
+                .iter()
+                .filter(|record_type| coordinator_record_kind(**record_type) == Some(kind))
+                .map(|record_type| ClassificationEvidence::RecordType(*record_type))
+                .collect()
+        };
+
+        let share = of_kind(GroupKind::Share);
+        let kip848 = of_kind(GroupKind::Kip848Consumer);
+        let (kind, evidence) = if !share.is_empty() {
+            (GroupKind::Share, share)
+        } else if !kip848.is_empty() {
+            (GroupKind::Kip848Consumer, kip848)
+        } else if let Some(protocol_type) = &self.protocol_type {
+            let mut evidence = vec![ClassificationEvidence::ProtocolType(protocol_type.clone())];
+            if !self.protocol.is_empty() {
+                evidence.push(ClassificationEvidence::Protocol(self.protocol.clone()));
+            }
+
+            let kind = match protocol_type.as_str() {
+                "consumer" if self.protocol == "stream" => GroupKind::Streams,
+                "consumer" => match &self.repartition_topic {
+                    Some(topic) => {
+                        evidence.push(ClassificationEvidence::RepartitionTopic(topic.clone()));
+                        GroupKind::Streams
+                    },
+                    None => GroupKind::ClassicConsumer,
+                },
+                "connect" => GroupKind::Connect,
+                "sr" => GroupKind::SchemaRegistry,
+                // Group created by the Broker to hold the offsets committed outside of a rebalance protocol.
+                "" => GroupKind::Simple,
+                _ => GroupKind::Unknown,
+            };
+
+            (kind, evidence)
+        } else if self.offset_commits > 0 {
+            (GroupKind::Simple, vec![ClassificationEvidence::OffsetCommitsWithoutGroupMetadata(self.offset_commits)])
+        } else {
+            (GroupKind::Unknown, vec![])
+        };
+
+        GroupClassification {
+            group: group.to_string(),
+            kind,
+            evidence,
+        }
+    }
+}
+
+/// Classifies groups by the [`GroupKind`] of their clients, accumulating records of `__consumer_offsets`.
+///
+/// Groups are classified from their [`GroupMetadata::protocol_type`](crate::GroupMetadata::protocol_type) and
+/// [`GroupMetadata::protocol`](crate::GroupMetadata::protocol); groups that only ever commit offsets are
+/// [`GroupKind::Simple`]. [KIP-848] and share groups are instead recognised by the type of the records the new
+/// Group Coordinator writes for them: [`KonsumerOffsetsData`] doesn't parse those (returning
+/// [`KonsumerOffsetsError::UnsupportedMessageVersion`](crate::KonsumerOffsetsError::UnsupportedMessageVersion)),
+/// so their key has to be passed to [`GroupClassifier::update_from_key`].
+///
+/// [KIP-848]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-848%3A+The+Next+Generation+of+the+Consumer+Rebalance+Protocol
+#[derive(Debug, Default, Clone)]
+pub struct GroupClassifier {
+    groups: BTreeMap<String, GroupObservations>,
+}
+
+impl GroupClassifier {
+    /// Create a new [`GroupClassifier`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// A [`KonsumerOffsetsData::GroupMetadata`] tombstone forgets the group metadata seen so far:
+    /// the group was deleted, and if still committing offsets, it's now used differently.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        match data {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => {
+                if offset_commit.is_tombstone {
+                    return;
+                }
+
+                let observations = self.groups.entry(offset_commit.group.clone()).or_default();
+                observations.offset_commits += 1;
+                if offset_commit.topic.ends_with(STREAMS_REPARTITION_TOPIC_SUFFIX) {
+                    observations.repartition_topic = Some(offset_commit.topic.clone());
+                }
+            },
+            KonsumerOffsetsData::GroupMetadata(group_metadata) => {
+                let observations = self.groups.entry(group_metadata.group.clone()).or_default();
+                if group_metadata.is_tombstone {
+                    observations.protocol_type = None;
+                    observations.protocol.clear();
+                    return;
+                }
+
+                observations.protocol_type = Some(group_metadata.protocol_type.clone());
+                // An empty group has no protocol: keep the one seen last.
+                if !group_metadata.protocol.is_empty() {
+                    observations.protocol = group_metadata.protocol.clone();
+                }
+            },
+        }
+    }
+
+    /// Update [`Self`] with the key of a record that [`KonsumerOffsetsData`] doesn't parse.
+    ///
+    /// The key of the records written by the new Group Coordinator starts, like the others,
+    /// with the record type and the group: this is enough to recognise [KIP-848] and share groups.
+    ///
+    /// Returns `true` if the key was of a record type relevant to the classification.
+    ///
+    /// [KIP-848]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-848%3A+The+Next+Generation+of+the+Consumer+Rebalance+Protocol
+    pub fn update_from_key(&mut self, key: &[u8]) -> bool {
+        let mut parser = BytesParser::from(key);
+        let Ok(record_type) = parse_i16(&mut parser) else {
+            return false;
+        };
+
+        if coordinator_record_kind(record_type).is_none() {
+            return false;
+        }
+
+        match parse_str(&mut parser) {
+            Ok(group) => {
+                self.groups.entry(group).or_default().record_types.insert(record_type);
+                true
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Classifies the given group.
+    ///
+    /// Returns `None` if nothing was seen about the group.
+    pub fn classify(&self, group: &str) -> Option<GroupClassification> {
+        self.groups.get(group).map(|observations| observations.classify(group))
+    }
+
+    /// Classifies all the groups seen so far, sorted by group.
+    pub fn classifications(&self) -> Vec<GroupClassification> {
+        self.groups.iter().map(|(group, observations)| observations.classify(group)).collect()
+    }
+
+    /// Number of groups of each [`GroupKind`].
+    pub fn breakdown(&self) -> BTreeMap<GroupKind, usize> {
+        let mut breakdown = BTreeMap::new();
+        for classification in self.classifications() {
+            *breakdown.entry(classification.kind).or_insert(0) += 1;
+        }
+        breakdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::is_thread_safe;
+    use crate::{GroupMetadata, OffsetCommit};
+
+    fn group_metadata(group: &str, protocol_type: &str, protocol: &str) -> KonsumerOffsetsData {
+        KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: group.to_string(),
+            protocol_type: protocol_type.to_string(),
+            protocol: protocol.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn offset_commit(group: &str, topic: &str) -> KonsumerOffsetsData {
+        KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn key(record_type: i16, group: &str) -> Vec<u8> {
+        let mut key = record_type.to_be_bytes().to_vec();
+        key.extend((group.len() as i16).to_be_bytes());
+        key.extend(group.as_bytes());
+        key
+    }
+
+    #[test]
+    fn test_classify_from_group_metadata() {
+        let mut classifier = GroupClassifier::new();
+        classifier.update_from(&group_metadata("orders", "consumer", "range"));
+        classifier.update_from(&group_metadata("connect-cluster", "connect", "sessioned"));
+        classifier.update_from(&group_metadata("wordcount", "consumer", "stream"));
+        classifier.update_from(&group_metadata("schema-registry", "sr", "v0"));
+        classifier.update_from(&group_metadata("mystery", "custom", ""));
+
+        assert_eq!(
+            classifier.classify("orders"),
+            Some(GroupClassification {
+                group: "orders".to_string(),
+                kind: GroupKind::ClassicConsumer,
+                evidence: vec![
+                    ClassificationEvidence::ProtocolType("consumer".to_string()),
+                    ClassificationEvidence::Protocol("range".to_string()),
+                ],
+            })
+        );
+        assert_eq!(classifier.classify("connect-cluster").unwrap().kind, GroupKind::Connect);
+        assert_eq!(classifier.classify("wordcount").unwrap().kind, GroupKind::Streams);
+        assert_eq!(classifier.classify("schema-registry").unwrap().kind, GroupKind::SchemaRegistry);
+        assert_eq!(classifier.classify("mystery").unwrap().kind, GroupKind::Unknown);
+        assert_eq!(classifier.classify("missing"), None);
+
+        // Once empty, the group keeps the protocol seen last.
+        classifier.update_from(&group_metadata("wordcount", "consumer", ""));
+        assert_eq!(classifier.classify("wordcount").unwrap().kind, GroupKind::Streams);
+    }
+
+    #[test]
+    fn test_classify_from_offset_commits() {
+        let mut classifier = GroupClassifier::new();
+        classifier.update_from(&offset_commit("flink-job", "events"));
+        classifier.update_from(&offset_commit("flink-job", "events"));
+        classifier.update_from(&group_metadata("streams-app", "consumer", ""));
+        classifier.update_from(&offset_commit("streams-app", "streams-app-KSTREAM-AGGREGATE-0000000003-repartition"));
+
+        assert_eq!(
+            classifier.classify("flink-job"),
+            Some(GroupClassification {
+                group: "flink-job".to_string(),
+                kind: GroupKind::Simple,
+                evidence: vec![ClassificationEvidence::OffsetCommitsWithoutGroupMetadata(2)],
+            })
+        );
+        assert_eq!(
+            classifier.classify("streams-app").unwrap().evidence,
+            vec![
+                ClassificationEvidence::ProtocolType("consumer".to_string()),
+                ClassificationEvidence::RepartitionTopic(
+                    "streams-app-KSTREAM-AGGREGATE-0000000003-repartition".to_string()
+                ),
+            ]
+        );
+        assert_eq!(classifier.classify("streams-app").unwrap().kind, GroupKind::Streams);
+    }
+
+    #[test]
+    fn test_classify_from_key() {
+        let mut classifier = GroupClassifier::new();
+        assert!(classifier.update_from_key(&key(3, "new-consumer")));
+        assert!(classifier.update_from_key(&key(5, "new-consumer")));
+        assert!(classifier.update_from_key(&key(11, "queue")));
+        assert!(classifier.update_from_key(&key(16, "regex-consumer")));
+        assert!(classifier.update_from_key(&key(15, "queue-state")));
+        assert!(!classifier.update_from_key(&key(42, "unrelated")));
+        assert!(!classifier.update_from_key(&[0]));
+
+        // The new Group Coordinator keeps writing offset commits as before.
+        classifier.update_from(&offset_commit("new-consumer", "events"));
+
+        assert_eq!(
+            classifier.classify("new-consumer"),
+            Some(GroupClassification {
+                group: "new-consumer".to_string(),
+                kind: GroupKind::Kip848Consumer,
+                evidence: vec![ClassificationEvidence::RecordType(3), ClassificationEvidence::RecordType(5)],
+            })
+        );
+        assert_eq!(classifier.classify("queue").unwrap().kind, GroupKind::Share);
+        assert_eq!(
+            classifier.classify("regex-consumer").unwrap().evidence,
+            vec![ClassificationEvidence::RecordType(16)]
+        );
+        assert_eq!(classifier.classify("regex-consumer").unwrap().kind, GroupKind::Kip848Consumer);
+        assert_eq!(classifier.classify("queue-state").unwrap().kind, GroupKind::Share);
+        assert_eq!(classifier.classify("unrelated"), None);
+        assert_eq!(classifier.breakdown(), BTreeMap::from([(GroupKind::Kip848Consumer, 2), (GroupKind::Share, 2)]));
+    }
+
+    #[test]
+    fn test_group_deletion() {
+        let mut classifier = GroupClassifier::new();
+        classifier.update_from(&group_metadata("g1", "consumer", "range"));
+        classifier.update_from(&KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: "g1".to_string(),
+            is_tombstone: true,
+            ..Default::default()
+        }));
+        classifier.update_from(&offset_commit("g1", "events"));
+
+        assert_eq!(classifier.classify("g1").unwrap().kind, GroupKind::Simple);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<GroupKind>();
+        is_thread_safe::<ClassificationEvidence>();
+        is_thread_safe::<GroupClassification>();
+        is_thread_safe::<GroupClassifier>();
+    }
+}
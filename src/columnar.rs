@@ -0,0 +1,570 @@
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Int16Builder, Int32Builder, Int64Builder, ListBuilder, StringBuilder, TimestampMillisecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+
+use crate::group_metadata::GroupMetadata;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::record::KonsumerOffsetsRecord;
+use crate::utils::timestamp_millis;
+
+/// Timezone of all timestamp columns.
+const UTC: &str = "UTC";
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, Some(UTC.into()))
+}
+
+/// Columns describing the coordinates of the source record: they are null
+/// for data appended without coordinates (see [`ArrowBatchBuilder::append`]).
+fn record_fields() -> [Field; 3] {
+    [
+        Field::new("record_partition", DataType::Int32, true),
+        Field::new("record_offset", DataType::Int64, true),
+        Field::new("record_timestamp", timestamp_type(), true),
+    ]
+}
+
+/// Schema of the [`ArrowTables::offset_commits`] table: one row per [`OffsetCommit`] (including tombstones).
+///
+/// Columns that come from the payload are null for tombstones, as are `leader_epoch` when not known (`-1`)
+/// and `expire_timestamp` when not set (i.e. for schema versions other than `1`).
+pub fn offset_commits_schema() -> SchemaRef {
+    let mut fields = record_fields().to_vec();
+    fields.extend([
+        Field::new("group", DataType::Utf8, false),
+        Field::new("topic", DataType::Utf8, false),
+        Field::new("partition", DataType::Int32, false),
+        Field::new("is_tombstone", DataType::Boolean, false),
+        Field::new("message_version", DataType::Int16, false),
+        Field::new("schema_version", DataType::Int16, true),
+        Field::new("offset", DataType::Int64, true),
+        Field::new("leader_epoch", DataType::Int32, true),
+        Field::new("metadata", DataType::Utf8, true),
+        Field::new("commit_timestamp", timestamp_type(), true),
+        Field::new("expire_timestamp", timestamp_type(), true),
+    ]);
+    Arc::new(Schema::new(fields))
+}
+
+/// Schema of the [`ArrowTables::group_members`] table: one row per member of each [`GroupMetadata`].
+///
+/// `current_state_timestamp` is null for schema versions before `2`; `group_instance_id` and `rack_id`
+/// are null when not set.
+pub fn group_members_schema() -> SchemaRef {
+    let mut fields = record_fields().to_vec();
+    fields.extend([
+        Field::new("group", DataType::Utf8, false),
+        Field::new("generation", DataType::Int32, false),
+        Field::new("protocol_type", DataType::Utf8, false),
+        Field::new("protocol", DataType::Utf8, false),
+        Field::new("leader", DataType::Utf8, false),
+        Field::new("current_state_timestamp", timestamp_type(), true),
+        Field::new("member_id", DataType::Utf8, false),
+        Field::new("group_instance_id", DataType::Utf8, true),
+        Field::new("client_id", DataType::Utf8, false),
+        Field::new("client_host", DataType::Utf8, false),
+        Field::new("rebalance_timeout_ms", DataType::Int32, false),
+        Field::new("session_timeout_ms", DataType::Int32, false),
+        Field::new("subscribed_topics", DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))), false),
+        Field::new("rack_id", DataType::Utf8, true),
+    ]);
+    Arc::new(Schema::new(fields))
+}
+
+/// Schema of the [`ArrowTables::group_assignments`] table: one row per partition assigned to each member
+/// of each [`GroupMetadata`].
+pub fn group_assignments_schema() -> SchemaRef {
+    let mut fields = record_fields().to_vec();
+    fields.extend([
+        Field::new("group", DataType::Utf8, false),
+        Field::new("generation", DataType::Int32, false),
+        Field::new("member_id", DataType::Utf8, false),
+        Field::new("client_id", DataType::Utf8, false),
+        Field::new("client_host", DataType::Utf8, false),
+        Field::new("topic", DataType::Utf8, false),
+        Field::new("partition", DataType::Int32, false),
+    ]);
+    Arc::new(Schema::new(fields))
+}
+
+/// Tables produced by [`ArrowBatchBuilder::finish`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowTables {
+    /// [`OffsetCommit`]s, with schema [`offset_commits_schema`].
+    pub offset_commits: RecordBatch,
+
+    /// Members of [`GroupMetadata`]s, with schema [`group_members_schema`].
+    pub group_members: RecordBatch,
+
+    /// Partition assignments of [`GroupMetadata`]s members, with schema [`group_assignments_schema`].
+    pub group_assignments: RecordBatch,
+}
+
+/// Builder of the columns describing the coordinates of the source record.
+#[derive(Debug, Default)]
+struct RecordColumns {
+    partition: Int32Builder,
+    offset: Int64Builder,
+    timestamp: TimestampMillisecondBuilder,
+}
+
+impl RecordColumns {
+    fn append(&mut self, record: Option<&KonsumerOffsetsRecord>) {
+        self.partition.append_option(record.map(|r| r.partition));
+        self.offset.append_option(record.map(|r| r.offset));
+        self.timestamp.append_option(record.map(|r| r.timestamp));
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        vec![
+            Arc::new(self.partition.finish()),
+            Arc::new(self.offset.finish()),
+            Arc::new(self.timestamp.finish().with_timezone(UTC)),
+        ]
+    }
+}
+
+#[derive(Debug, Default)]
+struct OffsetCommitColumns {
+    record: RecordColumns,
+    group: StringBuilder,
+    topic: StringBuilder,
+    partition: Int32Builder,
+    is_tombstone: BooleanBuilder,
+    message_version: Int16Builder,
+    schema_version: Int16Builder,
+    offset: Int64Builder,
+    leader_epoch: Int32Builder,
+    metadata: StringBuilder,
+    commit_timestamp: TimestampMillisecondBuilder,
+    expire_timestamp: TimestampMillisecondBuilder,
+}
+
+impl OffsetCommitColumns {
+    fn append(&mut self, record: Option<&KonsumerOffsetsRecord>, oc: &OffsetCommit) {
+        self.record.append(record);
+        self.group.append_value(&oc.group);
+        self.topic.append_value(&oc.topic);
+        self.partition.append_value(oc.partition);
+        self.is_tombstone.append_value(oc.is_tombstone);
+        self.message_version.append_value(oc.message_version);
+
+        let payload = (!oc.is_tombstone).then_some(oc);
+        self.schema_version.append_option(payload.map(|oc| oc.schema_version));
+        self.offset.append_option(payload.map(|oc| oc.offset));
+        self.leader_epoch.append_option(payload.map(|oc| oc.leader_epoch).filter(|le| *le >= 0));
+        self.metadata.append_option(payload.map(|oc| &oc.metadata));
+        self.commit_timestamp.append_option(payload.map(|oc| timestamp_millis(&oc.commit_timestamp)));
+        self.expire_timestamp.append_option(
+            payload
+                .filter(|oc| oc.schema_version == 1)
+                .map(|oc| timestamp_millis(&oc.expire_timestamp))
+                .filter(|ts| *ts >= 0),
+        );
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let mut columns = self.record.finish();
+        columns.extend::<[ArrayRef; 11]>([
+            Arc::new(self.group.finish()),
+            Arc::new(self.topic.finish()),
+            Arc::new(self.partition.finish()),
+            Arc::new(self.is_tombstone.finish()),
+            Arc::new(self.message_version.finish()),
+            Arc::new(self.schema_version.finish()),
+            Arc::new(self.offset.finish()),
+            Arc::new(self.leader_epoch.finish()),
+            Arc::new(self.metadata.finish()),
+            Arc::new(self.commit_timestamp.finish().with_timezone(UTC)),
+            Arc::new(self.expire_timestamp.finish().with_timezone(UTC)),
+        ]);
+        RecordBatch::try_new(offset_commits_schema(), columns)
+    }
+}
+
+#[derive(Debug, Default)]
+struct GroupMemberColumns {
+    record: RecordColumns,
+    group: StringBuilder,
+    generation: Int32Builder,
+    protocol_type: StringBuilder,
+    protocol: StringBuilder,
+    leader: StringBuilder,
+    current_state_timestamp: TimestampMillisecondBuilder,
+    member_id: StringBuilder,
+    group_instance_id: StringBuilder,
+    client_id: StringBuilder,
+    client_host: StringBuilder,
+    rebalance_timeout_ms: Int32Builder,
+    session_timeout_ms: Int32Builder,
+    subscribed_topics: ListBuilder<StringBuilder>,
+    rack_id: StringBuilder,
+}
+
+impl GroupMemberColumns {
+    fn append(&mut self, record: Option<&KonsumerOffsetsRecord>, gm: &GroupMetadata) {
+        let current_state_timestamp = (gm.schema_version >= 2).then(|| timestamp_millis(&gm.current_state_timestamp));
+
+        for member in &gm.members {
+            self.record.append(record);
+            self.group.append_value(&gm.group);
+            self.generation.append_value(gm.generation);
+            self.protocol_type.append_value(&gm.protocol_type);
+            self.protocol.append_value(&gm.protocol);
+            self.leader.append_value(&gm.leader);
+            self.current_state_timestamp.append_option(current_state_timestamp);
+            self.member_id.append_value(&member.id);
+            self.group_instance_id.append_option(Some(&member.group_instance_id).filter(|id| !id.is_empty()));
+            self.client_id.append_value(&member.client_id);
+            self.client_host.append_value(&member.client_host);
+            self.rebalance_timeout_ms.append_value(member.rebalance_timeout);
+            self.session_timeout_ms.append_value(member.session_timeout);
+            for topic in &member.subscription.subscribed_topics {
+                self.subscribed_topics.values().append_value(topic);
+            }
+            self.subscribed_topics.append(true);
+            self.rack_id.append_option(Some(&member.subscription.rack_id).filter(|id| !id.is_empty()));
+        }
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let mut columns = self.record.finish();
+        columns.extend::<[ArrayRef; 14]>([
+            Arc::new(self.group.finish()),
+            Arc::new(self.generation.finish()),
+            Arc::new(self.protocol_type.finish()),
+            Arc::new(self.protocol.finish()),
+            Arc::new(self.leader.finish()),
+            Arc::new(self.current_state_timestamp.finish().with_timezone(UTC)),
+            Arc::new(self.member_id.finish()),
+            Arc::new(self.group_instance_id.finish()),
+            Arc::new(self.client_id.finish()),
+            Arc::new(self.client_host.finish()),
+            Arc::new(self.rebalance_timeout_ms.finish()),
+            Arc::new(self.session_timeout_ms.finish()),
+            Arc::new(self.subscribed_topics.finish()),
+            Arc::new(self.rack_id.finish()),
+        ]);
+        RecordBatch::try_new(group_members_schema(), columns)
+    }
+}
+
+#[derive(Debug, Default)]
+struct GroupAssignmentColumns {
+    record: RecordColumns,
+    group: StringBuilder,
+    generation: Int32Builder,
+    member_id: StringBuilder,
+    client_id: StringBuilder,
+    client_host: StringBuilder,
+    topic: StringBuilder,
+    partition: Int32Builder,
+}
+
+impl GroupAssignmentColumns {
+    fn append(&mut self, record: Option<&KonsumerOffsetsRecord>, gm: &GroupMetadata) {
+        for member in &gm.members {
+            for tp in &member.assignment.assigned_topic_partitions {
+                for partition in &tp.partitions {
+                    self.record.append(record);
+                    self.group.append_value(&gm.group);
+                    self.generation.append_value(gm.generation);
+                    self.member_id.append_value(&member.id);
+                    self.client_id.append_value(&member.client_id);
+                    self.client_host.append_value(&member.client_host);
+                    self.topic.append_value(&tp.topic);
+                    self.partition.append_value(*partition);
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let mut columns = self.record.finish();
+        columns.extend::<[ArrayRef; 7]>([
+            Arc::new(self.group.finish()),
+            Arc::new(self.generation.finish()),
+            Arc::new(self.member_id.finish()),
+            Arc::new(self.client_id.finish()),
+            Arc::new(self.client_host.finish()),
+            Arc::new(self.topic.finish()),
+            Arc::new(self.partition.finish()),
+        ]);
+        RecordBatch::try_new(group_assignments_schema(), columns)
+    }
+}
+
+/// Accumulates parsed records into columnar [Apache Arrow](https://arrow.apache.org/) tables.
+///
+/// [`OffsetCommit`]s go into one table, while each [`GroupMetadata`] is flattened into two:
+/// one row per member, and one row per partition assigned to each member.
+/// See [`offset_commits_schema`], [`group_members_schema`] and [`group_assignments_schema`] for the
+/// (stable) schemas of the produced [`RecordBatch`]es.
+///
+/// **NOTE:** [`GroupMetadata`] tombstones, and groups without members, produce no rows.
+#[derive(Debug, Default)]
+pub struct ArrowBatchBuilder {
+    offset_commits: OffsetCommitColumns,
+    group_members: GroupMemberColumns,
+    group_assignments: GroupAssignmentColumns,
+    appended: usize,
+}
+
+impl ArrowBatchBuilder {
+    /// Create an empty [`Self`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a [`KonsumerOffsetsRecord`], filling the `record_*` columns with its coordinates.
+    pub fn append_record(&mut self, record: &KonsumerOffsetsRecord) {
+        self.append_inner(Some(record), &record.data);
+    }
+
+    /// Appends a [`KonsumerOffsetsData`]: the `record_*` columns are left null.
+    pub fn append(&mut self, data: &KonsumerOffsetsData) {
+        self.append_inner(None, data);
+    }
+
+    fn append_inner(&mut self, record: Option<&KonsumerOffsetsRecord>, data: &KonsumerOffsetsData) {
+        match data {
+            KonsumerOffsetsData::OffsetCommit(oc) => self.offset_commits.append(record, oc),
+            KonsumerOffsetsData::GroupMetadata(gm) => {
+                self.group_members.append(record, gm);
+                self.group_assignments.append(record, gm);
+            },
+        }
+        self.appended += 1;
+    }
+
+    /// Number of [`KonsumerOffsetsData`] appended since the last [`Self::finish`].
+    pub fn len(&self) -> usize {
+        self.appended
+    }
+
+    /// Whether nothing was appended since the last [`Self::finish`].
+    pub fn is_empty(&self) -> bool {
+        self.appended == 0
+    }
+
+    /// Builds the [`ArrowTables`] out of all the data appended so far, and resets [`Self`].
+    pub fn finish(&mut self) -> Result<ArrowTables, ArrowError> {
+        self.appended = 0;
+        Ok(ArrowTables {
+            offset_commits: self.offset_commits.finish()?,
+            group_members: self.group_members.finish()?,
+            group_assignments: self.group_assignments.finish()?,
+        })
+    }
+}
+
+/// Streams parsed records into [Apache Parquet](https://parquet.apache.org/) files.
+///
+/// Records are accumulated with an [`ArrowBatchBuilder`], and every `batch_size` records
+/// the resulting [`ArrowTables`] are written to 3 files: one per table (see [`ArrowBatchBuilder`]).
+/// Call [`Self::close`] once done, to write the last batch and the Parquet footers.
+///
+/// **NOTE:** Unlike the other types of this crate, this is `Send` but not `Sync`,
+/// as the underlying Parquet writers are not.
+#[cfg(feature = "parquet")]
+pub struct ParquetExporter<W: std::io::Write + Send> {
+    builder: ArrowBatchBuilder,
+    batch_size: usize,
+    offset_commits: parquet::arrow::ArrowWriter<W>,
+    group_members: parquet::arrow::ArrowWriter<W>,
+    group_assignments: parquet::arrow::ArrowWriter<W>,
+}
+
+/// Default number of records per batch written by [`ParquetExporter`].
+#[cfg(feature = "parquet")]
+pub const DEFAULT_PARQUET_BATCH_SIZE: usize = 8192;
+
+#[cfg(feature = "parquet")]
+impl<W: std::io::Write + Send> ParquetExporter<W> {
+    /// Create [`Self`], writing each table to the corresponding writer.
+    ///
+    /// Files are compressed with Zstandard.
+    pub fn new(
+        offset_commits: W,
+        group_members: W,
+        group_assignments: W,
+    ) -> Result<Self, parquet::errors::ParquetError> {
+        use parquet::arrow::ArrowWriter;
+        use parquet::basic::{Compression, ZstdLevel};
+        use parquet::file::properties::WriterProperties;
+
+        let props =
+            || Some(WriterProperties::builder().set_compression(Compression::ZSTD(ZstdLevel::default())).build());
+
+        Ok(Self {
+            builder: ArrowBatchBuilder::new(),
+            batch_size: DEFAULT_PARQUET_BATCH_SIZE,
+            offset_commits: ArrowWriter::try_new(offset_commits, offset_commits_schema(), props())?,
+            group_members: ArrowWriter::try_new(group_members, group_members_schema(), props())?,
+            group_assignments: ArrowWriter::try_new(group_assignments, group_assignments_schema(), props())?,
+        })
+    }
+
+    /// Set the number of records accumulated before writing a batch.
+    ///
+    /// Default is [`DEFAULT_PARQUET_BATCH_SIZE`].
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Appends a [`KonsumerOffsetsRecord`] (see [`ArrowBatchBuilder::append_record`]).
+    pub fn append_record(&mut self, record: &KonsumerOffsetsRecord) -> Result<(), parquet::errors::ParquetError> {
+        self.builder.append_record(record);
+        self.write_if_full()
+    }
+
+    /// Appends a [`KonsumerOffsetsData`] (see [`ArrowBatchBuilder::append`]).
+    pub fn append(&mut self, data: &KonsumerOffsetsData) -> Result<(), parquet::errors::ParquetError> {
+        self.builder.append(data);
+        self.write_if_full()
+    }
+
+    /// Writes the last batch and the Parquet footers, returning the underlying writers
+    /// (for offset commits, group members and group assignments, respectively).
+    pub fn close(mut self) -> Result<(W, W, W), parquet::errors::ParquetError> {
+        self.write()?;
+        Ok((self.offset_commits.into_inner()?, self.group_members.into_inner()?, self.group_assignments.into_inner()?))
+    }
+
+    fn write_if_full(&mut self) -> Result<(), parquet::errors::ParquetError> {
+        if self.builder.len() >= self.batch_size {
+            self.write()?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self) -> Result<(), parquet::errors::ParquetError> {
+        if self.builder.is_empty() {
+            return Ok(());
+        }
+
+        let tables = self.builder.finish()?;
+        self.offset_commits.write(&tables.offset_commits)?;
+        self.group_members.write(&tables.group_members)?;
+        self.group_assignments.write(&tables.group_assignments)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int32Type, Int64Type, TimestampMillisecondType};
+    use arrow_array::Array;
+
+    use crate::utils::{is_thread_safe, read_fixture};
+    use crate::{
+        group_assignments_schema, group_members_schema, offset_commits_schema, ArrowBatchBuilder, ArrowTables,
+        KonsumerOffsetsData, KonsumerOffsetsRecord,
+    };
+
+    fn build_tables() -> ArrowTables {
+        let mut builder = ArrowBatchBuilder::new();
+        builder.append_record(&KonsumerOffsetsRecord {
+            partition: 46,
+            offset: 10,
+            timestamp: 1672871009300,
+            data: read_fixture("offset_commit", 1),
+        });
+        builder.append(&read_fixture("group_metadata", 4));
+
+        let KonsumerOffsetsData::OffsetCommit(mut tombstone) = read_fixture("offset_commit", 1) else {
+            panic!("Returned wrong enum value!");
+        };
+        tombstone.is_tombstone = true;
+        builder.append(&KonsumerOffsetsData::OffsetCommit(tombstone));
+
+        assert_eq!(builder.len(), 3);
+        let tables = builder.finish().unwrap();
+        assert!(builder.is_empty());
+        tables
+    }
+
+    #[test]
+    fn test_offset_commits_table() {
+        let batch = build_tables().offset_commits;
+        assert_eq!(batch.schema(), offset_commits_schema());
+        assert_eq!(batch.num_rows(), 2);
+
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        assert_eq!(column("record_partition").as_primitive::<Int32Type>().value(0), 46);
+        assert!(column("record_partition").is_null(1));
+        assert_eq!(column("group").as_string::<i32>().value(0), "ivan-experimental-consumer");
+        assert_eq!(column("offset").as_primitive::<Int64Type>().value(0), 97507);
+        assert!(column("offset").is_null(1));
+        assert!(column("leader_epoch").is_null(0));
+        assert_eq!(column("commit_timestamp").as_primitive::<TimestampMillisecondType>().value(0), 1672871009232);
+        assert!(column("expire_timestamp").is_null(0));
+        assert!(column("is_tombstone").as_boolean().value(1));
+    }
+
+    #[test]
+    fn test_group_tables() {
+        let tables = build_tables();
+
+        let members = tables.group_members;
+        assert_eq!(members.schema(), group_members_schema());
+        assert_eq!(members.num_rows(), 2);
+        let column = |name: &str| members.column_by_name(name).unwrap().clone();
+        assert_eq!(column("generation").as_primitive::<Int32Type>().value(1), 6);
+        assert_eq!(column("client_host").as_string::<i32>().value(0), "/172.18.0.1");
+        assert!(column("group_instance_id").is_null(0));
+        assert_eq!(column("subscribed_topics").as_list::<i32>().value(0).as_string::<i32>().value(0), "t01");
+
+        let assignments = tables.group_assignments;
+        assert_eq!(assignments.schema(), group_assignments_schema());
+        let KonsumerOffsetsData::GroupMetadata(gm) = read_fixture("group_metadata", 4) else {
+            panic!("Returned wrong enum value!");
+        };
+        let expected_rows: usize =
+            gm.members.iter().flat_map(|m| &m.assignment.assigned_topic_partitions).map(|tp| tp.partitions.len()).sum();
+        assert_eq!(assignments.num_rows(), expected_rows);
+        assert_eq!(
+            assignments.column_by_name("topic").unwrap().as_string::<i32>().value(0),
+            gm.members[0].assignment.assigned_topic_partitions[0].topic
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_export() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        use crate::ParquetExporter;
+
+        let mut exporter = ParquetExporter::new(Vec::new(), Vec::new(), Vec::new()).unwrap().with_batch_size(2);
+        for _ in 0..3 {
+            exporter.append(&read_fixture("offset_commit", 1)).unwrap();
+            exporter.append(&read_fixture("group_metadata", 1)).unwrap();
+        }
+        let (offset_commits, group_members, _) = exporter.close().unwrap();
+
+        let rows = |bytes: Vec<u8>| {
+            ParquetRecordBatchReaderBuilder::try_new(bytes::Bytes::from(bytes))
+                .unwrap()
+                .build()
+                .unwrap()
+                .map(|batch| batch.unwrap().num_rows())
+                .sum::<usize>()
+        };
+        assert_eq!(rows(offset_commits), 3);
+        assert_eq!(rows(group_members), 3);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ArrowBatchBuilder>();
+        is_thread_safe::<ArrowTables>();
+    }
+}
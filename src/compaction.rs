@@ -0,0 +1,433 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::record::KonsumerOffsetsRecord;
+
+/// Key that [Log Compaction] uses to determine which records of `__consumer_offsets` supersede each other.
+///
+/// This is a typed version of the record key: for each [`CompactionKey`], only the latest record is
+/// retained by compaction.
+///
+/// [Log Compaction]: https://kafka.apache.org/documentation/#compaction
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompactionKey {
+    /// Key of an [`crate::OffsetCommit`].
+    OffsetCommit {
+        /// Group.
+        group: String,
+        /// Topic.
+        topic: String,
+        /// Partition.
+        partition: i32,
+    },
+
+    /// Key of a [`crate::GroupMetadata`].
+    GroupMetadata {
+        /// Group.
+        group: String,
+    },
+}
+
+impl From<&KonsumerOffsetsData> for CompactionKey {
+    fn from(data: &KonsumerOffsetsData) -> Self {
+        match data {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => CompactionKey::OffsetCommit {
+                group: offset_commit.group.clone(),
+                topic: offset_commit.topic.clone(),
+                partition: offset_commit.partition,
+            },
+            KonsumerOffsetsData::GroupMetadata(group_metadata) => CompactionKey::GroupMetadata {
+                group: group_metadata.group.clone(),
+            },
+        }
+    }
+}
+
+/// Topic configuration that controls the [Log Compaction] of `__consumer_offsets`.
+///
+/// The real log cleaner works in _bytes_: as this crate handles parsed records, sizes are
+/// approximated by _number of records_.
+///
+/// [Log Compaction]: https://kafka.apache.org/documentation/#compaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogCompactionConfig {
+    /// Topic configuration `min.cleanable.dirty.ratio`: a partition is cleaned only when the
+    /// ratio of _dirty_ (i.e. not yet cleaned) records over all cleanable records reaches this.
+    ///
+    /// Default is `0.5`.
+    pub min_cleanable_dirty_ratio: f64,
+
+    /// Topic configuration `delete.retention.ms`: how long tombstones are retained
+    /// after the partition section containing them was cleaned.
+    ///
+    /// Default is 1 day.
+    pub delete_retention_ms: i64,
+
+    /// Topic configuration `min.compaction.lag.ms`: records younger than this are not cleaned.
+    ///
+    /// Default is `0`.
+    pub min_compaction_lag_ms: i64,
+
+    /// Approximation of the topic configuration `segment.bytes`, as number of records per segment.
+    ///
+    /// The log cleaner never cleans the _active_ (i.e. latest) segment of a partition.
+    /// If `None`, the entire partition is considered cleanable. Default is `None`.
+    pub segment_records: Option<usize>,
+}
+
+impl Default for LogCompactionConfig {
+    fn default() -> Self {
+        Self {
+            min_cleanable_dirty_ratio: 0.5,
+            delete_retention_ms: 24 * 60 * 60 * 1000,
+            min_compaction_lag_ms: 0,
+            segment_records: None,
+        }
+    }
+}
+
+/// Outcome of a cleaning pass over a partition, performed by [`LogCompactionSimulator::clean`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompactionPass {
+    /// Partition of `__consumer_offsets`.
+    pub partition: i32,
+
+    /// Ratio of dirty records over all the cleanable records, before the pass.
+    pub dirty_ratio: f64,
+
+    /// `true` if the partition was cleaned, `false` if [`Self::dirty_ratio`] was below
+    /// [`LogCompactionConfig::min_cleanable_dirty_ratio`] (or there was nothing to clean).
+    pub cleaned: bool,
+
+    /// Number of records in the partition before the pass.
+    pub records_before: usize,
+
+    /// Number of records in the partition after the pass.
+    pub records_after: usize,
+
+    /// Number of tombstones removed by the pass.
+    pub tombstones_removed: usize,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+struct PartitionLog {
+    records: Vec<KonsumerOffsetsRecord>,
+    appended: usize,
+    active_segment_base_offset: Option<i64>,
+    cleaner_checkpoint: i64,
+    // Time of the pass that first cleaned each (retained) tombstone, by offset.
+    tombstones_cleaned_at: BTreeMap<i64, i64>,
+}
+
+/// Simulates what the Kafka log cleaner retains of `__consumer_offsets`.
+///
+/// `__consumer_offsets` uses [Log Compaction]: the log cleaner periodically removes from each partition
+/// the records superseded by a later record with the same [`CompactionKey`], and tombstones
+/// `delete.retention.ms` after they were first cleaned. What survives is what a [Group Coordinator] loads, after a failover.
+///
+/// Records are [appended](Self::append) in the order they were read, and then each call to
+/// [`Self::clean`] simulates a pass of the log cleaner on every partition. For each partition:
+///
+/// 1. The _cleanable_ section ends at the base offset of the _active_ segment, or at the first record
+///    younger than `min.compaction.lag.ms`, whichever comes first.
+/// 2. The records between the _cleaner checkpoint_ (i.e. where the previous pass ended) and the end of
+///    the cleanable section are _dirty_: the partition is cleaned only if the ratio of dirty records
+///    over all the cleanable records is at least `min.cleanable.dirty.ratio`.
+/// 3. In the cleanable section, a record is retained only if no later dirty record has the same
+///    [`CompactionKey`]. Tombstones are removed once `delete.retention.ms` have passed since
+///    the pass that first cleaned them (see [KIP-534]).
+/// 4. The cleaner checkpoint moves to the end of the cleanable section.
+///
+/// [Log Compaction]: https://kafka.apache.org/documentation/#compaction
+/// [KIP-534]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-534%3A+Retain+tombstones+and+transaction+markers+for+approximately+delete.retention.ms+milliseconds
+/// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogCompactionSimulator {
+    config: LogCompactionConfig,
+    partitions: BTreeMap<i32, PartitionLog>,
+}
+
+impl LogCompactionSimulator {
+    /// Create a [`Self`], that simulates a topic configured with the given [`LogCompactionConfig`].
+    pub fn new(config: LogCompactionConfig) -> Self {
+        Self {
+            config,
+            partitions: BTreeMap::new(),
+        }
+    }
+
+    /// Append a record to its partition.
+    ///
+    /// Records are expected in offset order, within each partition.
+    pub fn append(&mut self, record: KonsumerOffsetsRecord) {
+        let log = self.partitions.entry(record.partition).or_default();
+
+        if let Some(segment_records) = self.config.segment_records {
+            if segment_records > 0 && log.appended % segment_records == 0 {
+                log.active_segment_base_offset = Some(record.offset);
+            }
+        }
+
+        log.appended += 1;
+        log.records.push(record);
+    }
+
+    /// Simulates a pass of the log cleaner, at the time `now_ms`, on every partition.
+    ///
+    /// `now_ms` is compared with [`KonsumerOffsetsRecord::timestamp`] of the appended records.
+    pub fn clean(&mut self, now_ms: i64) -> Vec<CompactionPass> {
+        let config = self.config;
+        self.partitions
+            .iter_mut()
+            .map(|(partition, log)| Self::clean_partition(&config, *partition, log, now_ms))
+            .collect()
+    }
+
+    /// Records of a partition that survived compaction so far, in offset order.
+    pub fn records(&self, partition: i32) -> &[KonsumerOffsetsRecord] {
+        self.partitions.get(&partition).map(|log| log.records.as_slice()).unwrap_or_default()
+    }
+
+    /// Partitions that have at least 1 record appended.
+    pub fn partitions(&self) -> impl Iterator<Item = i32> + '_ {
+        self.partitions.keys().copied()
+    }
+
+    /// What a [Group Coordinator] would load from a partition, if it became its leader now.
+    ///
+    /// This is the latest record for each [`CompactionKey`], amongst the surviving ones,
+    /// excluding keys whose latest record is a tombstone.
+    ///
+    /// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+    pub fn coordinator_view(&self, partition: i32) -> BTreeMap<CompactionKey, &KonsumerOffsetsRecord> {
+        let mut view = BTreeMap::new();
+
+        for record in self.records(partition) {
+            let key = CompactionKey::from(&record.data);
+            if record.data.is_tombstone() {
+                view.remove(&key);
+            } else {
+                view.insert(key, record);
+            }
+        }
+
+        view
+    }
+
+    fn clean_partition(
+        config: &LogCompactionConfig,
+        partition: i32,
+        log: &mut PartitionLog,
+        now_ms: i64,
+    ) -> CompactionPass {
+        let records_before = log.records.len();
+
+        // The cleanable section ends at the active segment, or at the first record too young to be compacted.
+        let lag_horizon = now_ms.saturating_sub(config.min_compaction_lag_ms);
+        let cleanable_end = log
+            .records
+            .iter()
+            .find(|r| r.timestamp > lag_horizon || log.active_segment_base_offset.is_some_and(|base| r.offset >= base))
+            .map(|r| r.offset)
+            .unwrap_or(i64::MAX);
+
+        let cleanable = log.records.iter().filter(|r| r.offset < cleanable_end).count();
+        let dirty =
+            log.records.iter().filter(|r| r.offset >= log.cleaner_checkpoint && r.offset < cleanable_end).count();
+        let dirty_ratio = if cleanable == 0 {
+            0.0
+        } else {
+            dirty as f64 / cleanable as f64
+        };
+
+        if dirty == 0 || dirty_ratio < config.min_cleanable_dirty_ratio {
+            return CompactionPass {
+                partition,
+                dirty_ratio,
+                cleaned: false,
+                records_before,
+                records_after: records_before,
+                tombstones_removed: 0,
+            };
+        }
+
+        // Build the "offset map": latest offset of each key, in the dirty section.
+        let mut offset_map: HashMap<CompactionKey, i64> = HashMap::new();
+        for r in log.records.iter().filter(|r| r.offset >= log.cleaner_checkpoint && r.offset < cleanable_end) {
+            offset_map.insert(CompactionKey::from(&r.data), r.offset);
+        }
+
+        let tombstones_cleaned_at = &mut log.tombstones_cleaned_at;
+        let mut tombstones_removed = 0;
+        log.records.retain(|r| {
+            if r.offset >= cleanable_end {
+                return true;
+            }
+
+            if offset_map.get(&CompactionKey::from(&r.data)).is_some_and(|&latest| r.offset < latest) {
+                tombstones_cleaned_at.remove(&r.offset);
+                return false;
+            }
+
+            // Tombstones survive the pass that first cleans them, and then `delete.retention.ms` from it.
+            if r.data.is_tombstone() {
+                let cleaned_at = *tombstones_cleaned_at.entry(r.offset).or_insert(now_ms);
+                if cleaned_at < now_ms && now_ms >= cleaned_at.saturating_add(config.delete_retention_ms) {
+                    tombstones_cleaned_at.remove(&r.offset);
+                    tombstones_removed += 1;
+                    return false;
+                }
+            }
+
+            true
+        });
+
+        if cleanable_end != i64::MAX {
+            log.cleaner_checkpoint = cleanable_end;
+        } else if let Some(last) = log.records.last() {
+            log.cleaner_checkpoint = last.offset + 1;
+        }
+
+        CompactionPass {
+            partition,
+            dirty_ratio,
+            cleaned: true,
+            records_before,
+            records_after: log.records.len(),
+            tombstones_removed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::is_thread_safe;
+    use crate::{GroupMetadata, OffsetCommit};
+
+    fn offset_commit(offset: i64, timestamp: i64, partition: i32, is_tombstone: bool) -> KonsumerOffsetsRecord {
+        KonsumerOffsetsRecord {
+            partition: 0,
+            offset,
+            timestamp,
+            data: KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+                group: "g".to_string(),
+                topic: "t".to_string(),
+                partition,
+                is_tombstone,
+                offset: offset * 10,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn group_metadata(offset: i64, timestamp: i64) -> KonsumerOffsetsRecord {
+        KonsumerOffsetsRecord {
+            partition: 0,
+            offset,
+            timestamp,
+            data: KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                group: "g".to_string(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn offsets(sim: &LogCompactionSimulator) -> Vec<i64> {
+        sim.records(0).iter().map(|r| r.offset).collect()
+    }
+
+    #[test]
+    fn test_latest_value_per_key() {
+        let mut sim = LogCompactionSimulator::new(LogCompactionConfig::default());
+        sim.append(group_metadata(0, 0));
+        sim.append(offset_commit(1, 0, 0, false));
+        sim.append(offset_commit(2, 0, 1, false));
+        sim.append(offset_commit(3, 0, 0, false));
+        sim.append(group_metadata(4, 0));
+
+        let passes = sim.clean(1_000);
+        assert_eq!(passes.len(), 1);
+        assert!(passes[0].cleaned);
+        assert_eq!(passes[0].records_after, 3);
+        assert_eq!(offsets(&sim), vec![2, 3, 4]);
+
+        let view = sim.coordinator_view(0);
+        assert_eq!(view.len(), 3);
+        assert_eq!(
+            view.get(&CompactionKey::OffsetCommit {
+                group: "g".to_string(),
+                topic: "t".to_string(),
+                partition: 0
+            })
+            .map(|r| r.offset),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_tombstones_retention() {
+        let config = LogCompactionConfig {
+            delete_retention_ms: 100,
+            ..Default::default()
+        };
+        let mut sim = LogCompactionSimulator::new(config);
+        sim.append(offset_commit(0, 0, 0, false));
+        sim.append(offset_commit(1, 0, 0, true));
+
+        // First pass: the tombstone supersedes the commit, but is retained.
+        sim.clean(1_000);
+        assert_eq!(offsets(&sim), vec![1]);
+        assert!(sim.coordinator_view(0).is_empty());
+
+        // Nothing dirty: no pass.
+        assert!(!sim.clean(1_000)[0].cleaned);
+
+        // The tombstone is retained until `delete.retention.ms` after the pass that first cleaned it...
+        sim.append(offset_commit(2, 1_000, 1, false));
+        sim.append(offset_commit(3, 1_000, 1, false));
+        let passes = sim.clean(1_099);
+        assert!(passes[0].cleaned);
+        assert_eq!(passes[0].tombstones_removed, 0);
+        assert_eq!(offsets(&sim), vec![1, 3]);
+
+        // ... and removed by the first pass after that.
+        sim.append(offset_commit(4, 1_000, 1, false));
+        sim.append(offset_commit(5, 1_000, 1, false));
+        let passes = sim.clean(1_100);
+        assert_eq!(passes[0].tombstones_removed, 1);
+        assert_eq!(offsets(&sim), vec![5]);
+    }
+
+    #[test]
+    fn test_active_segment_and_dirty_ratio() {
+        let config = LogCompactionConfig {
+            segment_records: Some(2),
+            ..Default::default()
+        };
+        let mut sim = LogCompactionSimulator::new(config);
+        for offset in 0..5 {
+            sim.append(offset_commit(offset, 0, 0, false));
+        }
+
+        // Active segment starts at offset 4: it's retained, even if it supersedes all.
+        sim.clean(1_000);
+        assert_eq!(offsets(&sim), vec![3, 4]);
+
+        // Offset 3 is now clean, offset 4 is in the active segment: dirty ratio is 0.
+        let passes = sim.clean(1_000);
+        assert!(!passes[0].cleaned);
+        assert_eq!(passes[0].dirty_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<CompactionKey>();
+        is_thread_safe::<LogCompactionConfig>();
+        is_thread_safe::<CompactionPass>();
+        is_thread_safe::<LogCompactionSimulator>();
+    }
+}
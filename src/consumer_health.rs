@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::lag::LogOffsets;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// Thresholds used by [`ConsumerHealthEvaluator`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsumerHealthConfig {
+    /// Number of runs of commits for the same offset retained, per `(group, topic, partition)`, to evaluate.
+    ///
+    /// Consecutive commits for the same offset are retained as a single run, however many they are:
+    /// this way a stall is visible for as long as it lasts, independently of how frequently the consumer commits.
+    pub window_size: usize,
+
+    /// For how long commits must keep arriving for the same offset, to consider a consumer stalled.
+    pub stall_threshold_ms: i64,
+
+    /// Minimum number of commits for the same offset, to consider a consumer stalled.
+    pub stall_min_commits: usize,
+
+    /// For how long commits must have stopped arriving, to consider a consumer stopped.
+    pub stop_threshold_ms: i64,
+}
+
+impl Default for ConsumerHealthConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 10,
+            stall_threshold_ms: 5 * 60 * 1000,
+            stall_min_commits: 3,
+            stop_threshold_ms: 10 * 60 * 1000,
+        }
+    }
+}
+
+/// An [`OffsetCommit`], reduced to what [`ConsumerHealthEvaluator`] needs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitSample {
+    /// [`OffsetCommit::offset`].
+    pub offset: i64,
+
+    /// [`OffsetCommit::commit_timestamp`], in milliseconds since Unix Epoch.
+    pub commit_timestamp: i64,
+}
+
+/// Kind of problem detected by [`ConsumerHealthEvaluator`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsumerIssueKind {
+    /// Commits keep arriving, but the offset does not move while there are records to consume.
+    ///
+    /// **NOTE:** That there are records to consume is approximated by the log-end offset being ahead
+    /// of the committed offset at evaluation time: whether the log-end offset grew during the stall
+    /// is not verified.
+    Stalled,
+
+    /// Commits stopped arriving, while there are records to consume.
+    Stopped,
+
+    /// The committed offset moved backwards (ex. after an offset reset).
+    Regressing,
+}
+
+/// A problem detected by [`ConsumerHealthEvaluator`] for a `(group, topic, partition)`, with its evidence.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsumerIssue {
+    /// Group.
+    pub group: String,
+
+    /// Topic.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Kind of problem.
+    pub kind: ConsumerIssueKind,
+
+    /// First commit of the evidence:
+    ///
+    /// * [`ConsumerIssueKind::Stalled`]: the first commit of the stalled offset
+    /// * [`ConsumerIssueKind::Stopped`]: the first commit of the last committed offset
+    /// * [`ConsumerIssueKind::Regressing`]: the commit before the offset moved backwards
+    pub first_commit: CommitSample,
+
+    /// Last commit of the evidence:
+    ///
+    /// * [`ConsumerIssueKind::Stalled`] and [`ConsumerIssueKind::Stopped`]: the latest commit
+    /// * [`ConsumerIssueKind::Regressing`]: the commit that moved the offset backwards
+    pub last_commit: CommitSample,
+
+    /// How long the problem has lasted, in milliseconds:
+    ///
+    /// * [`ConsumerIssueKind::Stalled`]: between [`Self::first_commit`] and [`Self::last_commit`]
+    /// * [`ConsumerIssueKind::Stopped`]: since [`Self::last_commit`]
+    /// * [`ConsumerIssueKind::Regressing`]: between [`Self::first_commit`] and [`Self::last_commit`]
+    pub duration_ms: i64,
+
+    /// Log-end offset of the partition at evaluation time, if known.
+    pub log_end_offset: Option<i64>,
+}
+
+/// Consecutive commits for the same offset.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct CommitRun {
+    first: CommitSample,
+    last: CommitSample,
+    commits: usize,
+}
+
+/// Detects stalled, stopped and regressing consumers, from the sequence of their [`OffsetCommit`]s.
+///
+/// This is the core of a [Burrow]-style evaluator: it retains a sliding window of the latest runs
+/// of commits for the same offset, for each `(group, topic, partition)` and, when asked to evaluate,
+/// looks for the patterns described by [`ConsumerIssueKind`]. Thresholds are set via [`ConsumerHealthConfig`].
+///
+/// Telling an idle consumer from a stuck one requires the log-end offsets of the partitions,
+/// that are **not** in `__consumer_offsets` and are supplied by the caller (see [`LogOffsets`]):
+/// a consumer that has consumed everything is never stalled nor stopped. When the log-end offset
+/// is not known, a consumer can be reported as stopped, but not as stalled.
+///
+/// [Burrow]: https://github.com/linkedin/Burrow/wiki/Consumer-Lag-Evaluation-Rules
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ConsumerHealthEvaluator {
+    config: ConsumerHealthConfig,
+    commits: BTreeMap<(String, String, i32), VecDeque<CommitRun>>,
+}
+
+impl ConsumerHealthEvaluator {
+    /// Create a [`Self`] with the given [`ConsumerHealthConfig`].
+    ///
+    /// A [`ConsumerHealthConfig::window_size`] lower than `2` is raised to `2`:
+    /// that is the minimum to see the offset move.
+    pub fn new(config: ConsumerHealthConfig) -> Self {
+        Self {
+            config: ConsumerHealthConfig {
+                window_size: config.window_size.max(2),
+                ..config
+            },
+            commits: BTreeMap::new(),
+        }
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    ///
+    /// If the [`OffsetCommit`] is a tombstone, the commits for its `(group, topic, partition)`
+    /// are forgotten.
+    pub fn update(&mut self, offset_commit: &OffsetCommit) {
+        let key = (offset_commit.group.clone(), offset_commit.topic.clone(), offset_commit.partition);
+
+        if offset_commit.is_tombstone {
+            self.commits.remove(&key);
+            return;
+        }
+
+        let sample = CommitSample {
+            offset: offset_commit.offset,
+            commit_timestamp: timestamp_millis(&offset_commit.commit_timestamp),
+        };
+
+        let window = self.commits.entry(key).or_default();
+        match window.back_mut() {
+            Some(run) if run.last.offset == sample.offset => {
+                run.last = sample;
+                run.commits += 1;
+            },
+            _ => {
+                if window.len() == self.config.window_size {
+                    window.pop_front();
+                }
+                window.push_back(CommitRun {
+                    first: sample,
+                    last: sample,
+                    commits: 1,
+                });
+            },
+        }
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::OffsetCommit`] is relevant: other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        if let KonsumerOffsetsData::OffsetCommit(offset_commit) = data {
+            self.update(offset_commit);
+        }
+    }
+
+    /// Evaluates a `(group, topic, partition)` at time `now_ms`.
+    ///
+    /// Returns the problems detected, if any: [`ConsumerIssueKind::Stopped`] takes precedence
+    /// over [`ConsumerIssueKind::Stalled`], while [`ConsumerIssueKind::Regressing`] can be reported
+    /// together with either.
+    pub fn evaluate(
+        &self,
+        group: &str,
+        topic: &str,
+        partition: i32,
+        now_ms: i64,
+        log_offsets: &impl LogOffsets,
+    ) -> Vec<ConsumerIssue> {
+        let Some(window) = self.commits.get(&(group.to_string(), topic.to_string(), partition)) else {
+            return Vec::new();
+        };
+        let Some(&latest) = window.back() else {
+            return Vec::new();
+        };
+
+        let log_end_offset = log_offsets.log_end_offset(topic, partition);
+        let issue = |kind, first_commit: CommitSample, last_commit: CommitSample, duration_ms| ConsumerIssue {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            partition,
+            kind,
+            first_commit,
+            last_commit,
+            duration_ms,
+            log_end_offset,
+        };
+        let mut issues = Vec::new();
+
+        // Latest commit that moved the offset backwards
+        if let Some((before, after)) =
+            window.iter().zip(window.iter().skip(1)).rev().find(|(b, a)| a.first.offset < b.last.offset)
+        {
+            issues.push(issue(
+                ConsumerIssueKind::Regressing,
+                before.last,
+                after.first,
+                after.first.commit_timestamp - before.last.commit_timestamp,
+            ));
+        }
+
+        // Run of trailing commits for the latest offset
+        let (first, last) = (latest.first, latest.last);
+        let behind = log_end_offset.map(|end| end > last.offset);
+
+        if behind != Some(false) && now_ms - last.commit_timestamp >= self.config.stop_threshold_ms {
+            issues.push(issue(ConsumerIssueKind::Stopped, first, last, now_ms - last.commit_timestamp));
+        } else if behind == Some(true)
+            && latest.commits >= self.config.stall_min_commits
+            && last.commit_timestamp - first.commit_timestamp >= self.config.stall_threshold_ms
+        {
+            issues.push(issue(ConsumerIssueKind::Stalled, first, last, last.commit_timestamp - first.commit_timestamp));
+        }
+
+        issues
+    }
+
+    /// Evaluates every `(group, topic, partition)` seen so far at time `now_ms`,
+    /// returning all the problems detected, sorted by group, topic and partition.
+    pub fn evaluate_all(&self, now_ms: i64, log_offsets: &impl LogOffsets) -> Vec<ConsumerIssue> {
+        self.commits
+            .keys()
+            .flat_map(|(group, topic, partition)| self.evaluate(group, topic, *partition, now_ms, log_offsets))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::utils::{commit, is_thread_safe};
+
+    const MINUTE: i64 = 60 * 1000;
+
+    fn evaluator(commits: &[(i64, i64)]) -> ConsumerHealthEvaluator {
+        let mut evaluator = ConsumerHealthEvaluator::default();
+        for (offset, ts) in commits {
+            evaluator.update(&commit("g1", "t1", 0, *offset, *ts));
+        }
+        evaluator
+    }
+
+    fn log_end(offset: i64) -> HashMap<(String, i32), i64> {
+        HashMap::from([(("t1".to_string(), 0), offset)])
+    }
+
+    fn kinds(issues: &[ConsumerIssue]) -> Vec<ConsumerIssueKind> {
+        issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_healthy() {
+        let e = evaluator(&[(10, 0), (20, MINUTE), (30, 2 * MINUTE)]);
+        assert_eq!(e.evaluate("g1", "t1", 0, 3 * MINUTE, &log_end(40)), vec![]);
+        assert_eq!(e.evaluate("g1", "t1", 1, 3 * MINUTE, &log_end(40)), vec![]);
+    }
+
+    #[test]
+    fn test_stalled() {
+        let e = evaluator(&[(10, 0), (20, MINUTE), (20, 3 * MINUTE), (20, 6 * MINUTE)]);
+
+        let issues = e.evaluate("g1", "t1", 0, 7 * MINUTE, &log_end(100));
+        assert_eq!(kinds(&issues), vec![ConsumerIssueKind::Stalled]);
+        assert_eq!(
+            issues[0].first_commit,
+            CommitSample {
+                offset: 20,
+                commit_timestamp: MINUTE
+            }
+        );
+        assert_eq!(issues[0].last_commit.commit_timestamp, 6 * MINUTE);
+        assert_eq!(issues[0].duration_ms, 5 * MINUTE);
+        assert_eq!(issues[0].log_end_offset, Some(100));
+
+        // Nothing to consume: idle, not stalled
+        assert_eq!(e.evaluate("g1", "t1", 0, 7 * MINUTE, &log_end(20)), vec![]);
+        // Unknown log-end offset
+        assert_eq!(e.evaluate("g1", "t1", 0, 7 * MINUTE, &HashMap::<(String, i32), i64>::new()), vec![]);
+    }
+
+    #[test]
+    fn test_stalled_with_frequent_commits() {
+        // Auto-commit every 5 seconds, stuck on the same offset for 6 minutes
+        let mut commits = vec![(10, 0)];
+        commits.extend((1..=72).map(|i| (20, i * 5 * 1000)));
+        let e = evaluator(&commits);
+
+        let issues = e.evaluate("g1", "t1", 0, 6 * MINUTE, &log_end(100));
+        assert_eq!(kinds(&issues), vec![ConsumerIssueKind::Stalled]);
+        assert_eq!(issues[0].first_commit.commit_timestamp, 5 * 1000);
+        assert_eq!(issues[0].last_commit.commit_timestamp, 6 * MINUTE);
+        assert_eq!(issues[0].duration_ms, 6 * MINUTE - 5 * 1000);
+    }
+
+    #[test]
+    fn test_stopped() {
+        let e = evaluator(&[(10, 0), (20, MINUTE)]);
+
+        let issues = e.evaluate("g1", "t1", 0, 12 * MINUTE, &log_end(100));
+        assert_eq!(kinds(&issues), vec![ConsumerIssueKind::Stopped]);
+        assert_eq!(issues[0].duration_ms, 11 * MINUTE);
+
+        assert_eq!(e.evaluate("g1", "t1", 0, 12 * MINUTE, &log_end(20)), vec![]);
+        assert_eq!(e.evaluate("g1", "t1", 0, 5 * MINUTE, &log_end(100)), vec![]);
+        assert_eq!(e.evaluate_all(12 * MINUTE, &HashMap::<(String, i32), i64>::new()).len(), 1);
+    }
+
+    #[test]
+    fn test_regressing() {
+        let e = evaluator(&[(10, 0), (50, MINUTE), (5, 2 * MINUTE), (15, 3 * MINUTE)]);
+
+        let issues = e.evaluate_all(4 * MINUTE, &log_end(100));
+        assert_eq!(kinds(&issues), vec![ConsumerIssueKind::Regressing]);
+        assert_eq!(issues[0].first_commit.offset, 50);
+        assert_eq!(issues[0].last_commit.offset, 5);
+        assert_eq!(issues[0].duration_ms, MINUTE);
+    }
+
+    #[test]
+    fn test_window_and_tombstone() {
+        let mut e = ConsumerHealthEvaluator::new(ConsumerHealthConfig {
+            window_size: 2,
+            ..Default::default()
+        });
+        for (offset, ts) in [(50, 0), (5, MINUTE), (6, 2 * MINUTE), (7, 3 * MINUTE)] {
+            e.update(&commit("g1", "t1", 0, offset, ts));
+        }
+        // The regression slid out of the window
+        assert_eq!(e.evaluate_all(4 * MINUTE, &log_end(100)), vec![]);
+
+        e.update_from(&KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+            is_tombstone: true,
+            ..commit("g1", "t1", 0, 0, 0)
+        }));
+        assert_eq!(e.evaluate_all(60 * MINUTE, &log_end(100)), vec![]);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ConsumerHealthConfig>();
+        is_thread_safe::<CommitSample>();
+        is_thread_safe::<ConsumerIssueKind>();
+        is_thread_safe::<ConsumerIssue>();
+        is_thread_safe::<ConsumerHealthEvaluator>();
+    }
+}
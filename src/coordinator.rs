@@ -0,0 +1,179 @@
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::record::KonsumerOffsetsRecord;
+
+/// Default value of the Broker configuration `offsets.topic.num.partitions`.
+pub const DEFAULT_OFFSETS_TOPIC_NUM_PARTITIONS: i32 = 50;
+
+/// Computes the hash code of a string, exactly like Java [`String.hashCode()`] does.
+///
+/// Java strings are sequences of UTF-16 code units, and the hash is computed over those
+/// (_not_ over the UTF-8 bytes, nor the Unicode code points), as:
+///
+/// ```text
+/// s[0]*31^(n-1) + s[1]*31^(n-2) + ... + s[n-1]
+/// ```
+///
+/// using 32-bit signed integer arithmetic, that wraps around on overflow.
+///
+/// [`String.hashCode()`]: https://docs.oracle.com/en/java/javase/17/docs/api/java.base/java/lang/String.html#hashCode()
+pub fn java_string_hash_code(s: &str) -> i32 {
+    s.encode_utf16().fold(0i32, |hash, code_unit| hash.wrapping_mul(31).wrapping_add(code_unit as i32))
+}
+
+/// Computes which partition of `__consumer_offsets` stores the data of a consumer group.
+///
+/// The Broker leader of that partition is the [Group Coordinator] of the group:
+/// all [`crate::OffsetCommit`] and [`crate::GroupMetadata`] of the group are produced to it.
+///
+/// This is based on `kafka.coordinator.group.GroupMetadataManager#partitionFor`, that does
+/// `Utils.abs(groupId.hashCode) % groupMetadataTopicPartitionCount`. Note that
+/// [`Utils.abs`] maps `Integer.MIN_VALUE` to `0`, instead of overflowing.
+///
+/// # Arguments
+///
+/// * `group` - Consumer group identifier.
+/// * `num_partitions` - Number of partitions of `__consumer_offsets`,
+///   i.e. the Broker configuration `offsets.topic.num.partitions`
+///   (see [`DEFAULT_OFFSETS_TOPIC_NUM_PARTITIONS`]). Must be greater than `0`.
+///
+/// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+/// [`Utils.abs`]: https://github.com/apache/kafka/blob/trunk/clients/src/main/java/org/apache/kafka/common/utils/Utils.java
+pub fn group_partition(group: &str, num_partitions: i32) -> i32 {
+    let hash_code = java_string_hash_code(group);
+    let abs = if hash_code == i32::MIN {
+        0
+    } else {
+        hash_code.abs()
+    };
+
+    abs % num_partitions
+}
+
+/// A record found in a partition of `__consumer_offsets` that doesn't match its group.
+///
+/// See [`validate_partitioning`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitioningViolation {
+    /// Group of the record.
+    pub group: String,
+
+    /// Partition of `__consumer_offsets` the record was read from.
+    pub partition: i32,
+
+    /// Offset of the record in [`Self::partition`].
+    pub offset: i64,
+
+    /// Partition of `__consumer_offsets` the record should have been read from,
+    /// as per [`group_partition`].
+    pub expected_partition: i32,
+
+    /// `true` if the record contains an [`crate::OffsetCommit`],
+    /// `false` if it contains a [`crate::GroupMetadata`].
+    pub is_offset_commit: bool,
+}
+
+/// Finds the records that are stored in a partition of `__consumer_offsets` that does not match their group.
+///
+/// A Broker always stores the data of a group in the partition given by [`group_partition`]:
+/// records that violate that rule likely come from a different cluster (ex. mirrored
+/// with a different number of partitions) or are corrupted.
+///
+/// # Arguments
+///
+/// * `records` - The records to validate.
+/// * `num_partitions` - Number of partitions of `__consumer_offsets`
+///   (i.e. the Broker configuration `offsets.topic.num.partitions`).
+pub fn validate_partitioning<'a>(
+    records: impl IntoIterator<Item = &'a KonsumerOffsetsRecord>,
+    num_partitions: i32,
+) -> Vec<PartitioningViolation> {
+    records
+        .into_iter()
+        .filter_map(|record| {
+            let group = record.data.group();
+            let expected_partition = group_partition(group, num_partitions);
+
+            if expected_partition == record.partition {
+                return None;
+            }
+
+            Some(PartitioningViolation {
+                group: group.to_string(),
+                partition: record.partition,
+                offset: record.offset,
+                expected_partition,
+                is_offset_commit: matches!(record.data, KonsumerOffsetsData::OffsetCommit(_)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::utils::is_thread_safe;
+    use crate::{GroupMetadata, OffsetCommit};
+
+    #[rstest]
+    #[case("", 0)]
+    #[case("hello", 99162322)]
+    #[case("Hello World", -862545276)]
+    #[case("\u{20AC}", 8364)]
+    #[case("\u{1F600}", 1772899)]
+    fn test_java_string_hash_code(#[case] s: &str, #[case] expected: i32) {
+        assert_eq!(java_string_hash_code(s), expected);
+    }
+
+    #[rstest]
+    #[case("Hello World", 50, 26)]
+    #[case("hello", 50, 22)]
+    #[case("polygenelubricants", 50, 0)]
+    #[case("kafkesc-devcluster-group-id", 1, 0)]
+    fn test_group_partition(#[case] group: &str, #[case] num_partitions: i32, #[case] expected: i32) {
+        assert_eq!(group_partition(group, num_partitions), expected);
+    }
+
+    #[test]
+    fn test_validate_partitioning() {
+        let records = [
+            KonsumerOffsetsRecord {
+                partition: 22,
+                offset: 0,
+                timestamp: 0,
+                data: KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+                    group: "hello".to_string(),
+                    ..Default::default()
+                }),
+            },
+            KonsumerOffsetsRecord {
+                partition: 3,
+                offset: 1,
+                timestamp: 0,
+                data: KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                    group: "Hello World".to_string(),
+                    ..Default::default()
+                }),
+            },
+        ];
+
+        let violations = validate_partitioning(&records, DEFAULT_OFFSETS_TOPIC_NUM_PARTITIONS);
+        assert_eq!(
+            violations,
+            vec![PartitioningViolation {
+                group: "Hello World".to_string(),
+                partition: 3,
+                offset: 1,
+                expected_partition: 26,
+                is_offset_commit: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<PartitioningViolation>();
+    }
+}
@@ -0,0 +1,502 @@
+use std::io::BufRead;
+
+use base64::Engine;
+use serde_json::{Map, Value};
+
+use crate::errors::DumpLogError;
+use crate::record::KonsumerOffsetsRecord;
+
+/// Text printed by `kafka-dump-log.sh --offsets-decoder` in place of the payload of tombstones.
+const DUMP_LOG_TOMBSTONE: &str = "<DELETE>";
+
+/// A record of the dump, re-encoded into its original binary format.
+struct EncodedRecord {
+    offset: i64,
+    timestamp: i64,
+    key: Vec<u8>,
+    payload: Option<Vec<u8>>,
+}
+
+/// Reads the output of `kafka-dump-log.sh --offsets-decoder` back into [`KonsumerOffsetsRecord`]s.
+///
+/// `kafka-dump-log.sh` prints, for each record of a `__consumer_offsets` log segment, a line like:
+///
+/// ```text
+/// | offset: 42 CreateTime: 1672871009232 keySize: 54 valueSize: 28 sequence: -1 headerKeys: [] key: {"type":"1","data":{"group":"g","topic":"t","partition":0}} payload: {"version":"3","data":{"offset":97507,"leaderEpoch":-1,"metadata":"","commitTimestamp":1672871009232}}
+/// ```
+///
+/// The `key:` and `payload:` sections are the JSON rendering of the record, produced by the Broker
+/// with the same schemas this crate parses. This reader turns them back into the original binary
+/// format, and parses that with [`KonsumerOffsetsRecord::try_from_bytes`]: the resulting data is the
+/// same that parsing the raw record would have produced.
+///
+/// The coordinates of each record are taken from the dump itself:
+///
+/// * partition: from the `Dumping <path>` header, where the directory of the segment is
+///   named `__consumer_offsets-<partition>` (or from [`Self::with_partition`]; `-1` if unknown)
+/// * offset: from the `offset:` of the record line
+/// * timestamp: from the `CreateTime:` (or `LogAppendTime:`) of the record line
+///
+/// Batch headers (`baseOffset: ...`) are used to skip the records of control batches
+/// (i.e. transaction markers); other lines, like `Starting offset: ...`, are ignored.
+///
+/// **NOTE:** Only the JSON output of Kafka 3.0+ is supported. Also, for the `consumer` protocol type,
+/// the dump replaces subscription and assignment bytes with their decoded form, dropping their version:
+/// the subscription version is inferred from the fields present, while the assignment version
+/// (whose fields are the same across versions) is always set to `0`.
+pub struct DumpLogReader<R: BufRead> {
+    lines: std::io::Lines<R>,
+    line_number: usize,
+    partition: i32,
+    in_control_batch: bool,
+}
+
+impl<R: BufRead> DumpLogReader<R> {
+    /// Create [`Self`], reading the dump from the given [`BufRead`].
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line_number: 0,
+            partition: -1,
+            in_control_batch: false,
+        }
+    }
+
+    /// Set the partition of `__consumer_offsets` the dump was produced from.
+    ///
+    /// Use this when the `Dumping <path>` header is missing or the segment was renamed:
+    /// a header found later in the dump overrides it.
+    pub fn with_partition(mut self, partition: i32) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    /// Parses a single line of the dump, returning the re-encoded record if the line contained one.
+    fn parse_line(&mut self, line: &str) -> Result<Option<EncodedRecord>, String> {
+        let line = line.trim();
+
+        if let Some(path) = line.strip_prefix("Dumping ") {
+            if let Some(partition) = partition_from_path(path) {
+                self.partition = partition;
+            }
+            self.in_control_batch = false;
+            return Ok(None);
+        }
+
+        if line.starts_with("baseOffset:") {
+            self.in_control_batch = field(line, "isControl:") == Some("true");
+            return Ok(None);
+        }
+
+        let line = line.strip_prefix('|').map(str::trim_start).unwrap_or(line);
+        if !line.starts_with("offset:") || self.in_control_batch {
+            return Ok(None);
+        }
+
+        let offset =
+            field(line, "offset:").and_then(|o| o.parse::<i64>().ok()).ok_or("missing or invalid 'offset:'")?;
+        let timestamp = field(line, "CreateTime:")
+            .or_else(|| field(line, "LogAppendTime:"))
+            .and_then(|t| t.parse::<i64>().ok())
+            .ok_or("missing or invalid 'CreateTime:' / 'LogAppendTime:'")?;
+
+        let key_start = line.find(" key: ").ok_or("missing 'key:'")? + " key: ".len();
+        let (key, rest) = parse_json_prefix(&line[key_start..]).map_err(|e| format!("invalid 'key:' JSON: {e}"))?;
+        let payload = rest.trim_start().strip_prefix("payload:").ok_or("missing 'payload:'")?.trim();
+
+        let (key_type, key_bytes) = encode_key(&key)?;
+        let payload_bytes = if payload == DUMP_LOG_TOMBSTONE {
+            None
+        } else {
+            let (payload, _) = parse_json_prefix(payload).map_err(|e| format!("invalid 'payload:' JSON: {e}"))?;
+            Some(encode_payload(&payload, key_type)?)
+        };
+
+        Ok(Some(EncodedRecord {
+            offset,
+            timestamp,
+            key: key_bytes,
+            payload: payload_bytes,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for DumpLogReader<R> {
+    type Item = Result<KonsumerOffsetsRecord, DumpLogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+
+            let parsed = match line {
+                Ok(line) => self.parse_line(&line),
+                Err(e) => return Some(Err(DumpLogError::Io(self.line_number, e))),
+            };
+
+            match parsed {
+                Ok(Some(record)) => {
+                    return Some(
+                        KonsumerOffsetsRecord::try_from_bytes(
+                            self.partition,
+                            record.offset,
+                            record.timestamp,
+                            Some(&record.key),
+                            record.payload.as_deref(),
+                        )
+                        .map_err(|e| DumpLogError::Parsing(self.line_number, e)),
+                    )
+                },
+                Ok(None) => continue,
+                Err(reason) => return Some(Err(DumpLogError::InvalidLine(self.line_number, reason))),
+            }
+        }
+    }
+}
+
+/// Extracts the partition out of the path of a `__consumer_offsets-<partition>/<segment>.log` file.
+fn partition_from_path(path: &str) -> Option<i32> {
+    let dir = std::path::Path::new(path.trim()).parent()?.file_name()?.to_str()?;
+    dir.rsplit_once('-')?.1.parse().ok()
+}
+
+/// Returns the whitespace-delimited value following `name` in a dump line.
+fn field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    line.split_whitespace().skip_while(|token| *token != name).nth(1)
+}
+
+/// Parses the JSON value at the beginning of `text`, returning it together with the text that follows.
+fn parse_json_prefix(text: &str) -> Result<(Value, &str), serde_json::Error> {
+    let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+    let value = values.next().unwrap_or_else(|| serde_json::from_str::<Value>(""))?;
+    Ok((value, &text[values.byte_offset()..]))
+}
+
+/// Re-encodes the `{"type":...,"data":...}` key section into the binary key of the record.
+///
+/// Returns the key type (i.e. message version), together with the binary key.
+fn encode_key(key: &Value) -> Result<(i16, Vec<u8>), String> {
+    let message_version = version_of(key, "type")?;
+    let data = object(key, "data")?;
+
+    let mut bytes = Vec::new();
+    write_i16(&mut bytes, message_version);
+    match message_version {
+        0..=1 => {
+            write_str(&mut bytes, str_or_null(data, "group")?)?;
+            write_str(&mut bytes, str_or_null(data, "topic")?)?;
+            write_i32(&mut bytes, int32(data, "partition")?);
+        },
+        2 => {
+            write_str(&mut bytes, str_or_null(data, "group")?)?;
+        },
+        v => return Err(format!("unsupported key type {v}")),
+    }
+
+    Ok((message_version, bytes))
+}
+
+/// Re-encodes the `{"version":...,"data":...}` payload section into the binary payload of the record.
+///
+/// The shape of `data` is determined by the key type, as returned by [`encode_key`].
+fn encode_payload(payload: &Value, key_type: i16) -> Result<Vec<u8>, String> {
+    let schema_version = version_of(payload, "version")?;
+    let data = object(payload, "data")?;
+
+    let mut bytes = Vec::new();
+    write_i16(&mut bytes, schema_version);
+
+    match key_type {
+        0..=1 => encode_offset_commit_value(&mut bytes, data, schema_version)?,
+        2 => encode_group_metadata_value(&mut bytes, data, schema_version)?,
+        v => return Err(format!("unsupported key type {v}")),
+    }
+
+    Ok(bytes)
+}
+
+/// Mirrors the generated `kafka.internals.generated.OffsetCommitValue#write` method.
+fn encode_offset_commit_value(bytes: &mut Vec<u8>, data: &Map<String, Value>, version: i16) -> Result<(), String> {
+    write_i64(bytes, int(data, "offset")?);
+    if version >= 3 {
+        write_i32(bytes, int32(data, "leaderEpoch")?);
+    }
+    write_str(bytes, str_or_null(data, "metadata")?)?;
+    write_i64(bytes, int(data, "commitTimestamp")?);
+    if version == 1 {
+        write_i64(bytes, int(data, "expireTimestamp")?);
+    }
+
+    Ok(())
+}
+
+/// Mirrors the generated `kafka.internals.generated.GroupMetadataValue#write` method.
+fn encode_group_metadata_value(bytes: &mut Vec<u8>, data: &Map<String, Value>, version: i16) -> Result<(), String> {
+    write_str(bytes, str_or_null(data, "protocolType")?)?;
+    write_i32(bytes, int32(data, "generation")?);
+    write_str(bytes, str_or_null(data, "protocol")?)?;
+    write_str(bytes, str_or_null(data, "leader")?)?;
+    if version >= 2 {
+        write_i64(bytes, int(data, "currentStateTimestamp")?);
+    }
+
+    let members = array(data, "members")?;
+    write_len(bytes, members.len())?;
+    for member in members {
+        let member = member.as_object().ok_or("'members' item is not an object")?;
+
+        write_str(bytes, str_or_null(member, "memberId")?)?;
+        if version >= 3 {
+            write_str(bytes, str_or_null(member, "groupInstanceId")?)?;
+        }
+        write_str(bytes, str_or_null(member, "clientId")?)?;
+        write_str(bytes, str_or_null(member, "clientHost")?)?;
+        if version >= 1 {
+            write_i32(bytes, int32(member, "rebalanceTimeout")?);
+        }
+        write_i32(bytes, int32(member, "sessionTimeout")?);
+
+        let subscription = match member.get("subscription") {
+            Some(Value::Object(subscription)) => encode_subscription(subscription)?,
+            _ => bytes_or_null(member, "subscription")?,
+        };
+        write_bytes(bytes, &subscription)?;
+
+        let assignment = match member.get("assignment") {
+            Some(Value::Object(assignment)) => encode_assignment(assignment)?,
+            _ => bytes_or_null(member, "assignment")?,
+        };
+        write_bytes(bytes, &assignment)?;
+    }
+
+    Ok(())
+}
+
+/// Mirrors `org.apache.kafka.common.message.ConsumerProtocolSubscription#write`,
+/// inferring the version from the fields present.
+fn encode_subscription(data: &Map<String, Value>) -> Result<Vec<u8>, String> {
+    let version = if data.contains_key("rackId") {
+        3
+    } else if data.contains_key("generationId") {
+        2
+    } else if data.contains_key("ownedPartitions") {
+        1
+    } else {
+        0
+    };
+
+    let mut bytes = Vec::new();
+    write_i16(&mut bytes, version);
+
+    let topics = array(data, "topics")?;
+    write_len(&mut bytes, topics.len())?;
+    for topic in topics {
+        write_str(&mut bytes, topic.as_str().ok_or("'topics' item is not a string")?)?;
+    }
+
+    write_bytes(&mut bytes, &bytes_or_null(data, "userData")?)?;
+
+    if version >= 1 {
+        encode_topic_partitions(&mut bytes, array(data, "ownedPartitions")?)?;
+    }
+    if version >= 2 {
+        write_i32(&mut bytes, int32(data, "generationId")?);
+    }
+    if version >= 3 {
+        write_str(&mut bytes, str_or_null(data, "rackId")?)?;
+    }
+
+    Ok(bytes)
+}
+
+/// Mirrors `org.apache.kafka.common.message.ConsumerProtocolAssignment#write`, at version `0`.
+fn encode_assignment(data: &Map<String, Value>) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    write_i16(&mut bytes, 0);
+    encode_topic_partitions(&mut bytes, array(data, "assignedPartitions")?)?;
+    write_bytes(&mut bytes, &bytes_or_null(data, "userData")?)?;
+
+    Ok(bytes)
+}
+
+fn encode_topic_partitions(bytes: &mut Vec<u8>, topic_partitions: &[Value]) -> Result<(), String> {
+    write_len(bytes, topic_partitions.len())?;
+    for tp in topic_partitions {
+        let tp = tp.as_object().ok_or("topic partitions item is not an object")?;
+        write_str(bytes, str_or_null(tp, "topic")?)?;
+
+        let partitions = array(tp, "partitions")?;
+        write_len(bytes, partitions.len())?;
+        for partition in partitions {
+            let partition = partition.as_i64().ok_or("partition is not an integer")?;
+            write_i32(bytes, i32::try_from(partition).map_err(|_| format!("partition out of range: {partition}"))?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a version, that Kafka renders as a JSON string (ex. `"type":"1"`).
+fn version_of(value: &Value, name: &str) -> Result<i16, String> {
+    let version = match value.get(name) {
+        Some(Value::String(s)) => s.parse::<i16>().ok(),
+        Some(Value::Number(n)) => n.as_i64().and_then(|n| i16::try_from(n).ok()),
+        _ => None,
+    };
+    version.ok_or_else(|| format!("missing or invalid '{name}'"))
+}
+
+fn object<'a>(value: &'a Value, name: &str) -> Result<&'a Map<String, Value>, String> {
+    value.get(name).and_then(Value::as_object).ok_or_else(|| format!("missing or invalid '{name}' object"))
+}
+
+fn array<'a>(data: &'a Map<String, Value>, name: &str) -> Result<&'a [Value], String> {
+    match data.get(name) {
+        Some(Value::Array(items)) => Ok(items),
+        Some(Value::Null) => Ok(&[]),
+        _ => Err(format!("missing or invalid '{name}' array")),
+    }
+}
+
+fn int(data: &Map<String, Value>, name: &str) -> Result<i64, String> {
+    data.get(name).and_then(Value::as_i64).ok_or_else(|| format!("missing or invalid '{name}' integer"))
+}
+
+fn int32(data: &Map<String, Value>, name: &str) -> Result<i32, String> {
+    let value = int(data, name)?;
+    i32::try_from(value).map_err(|_| format!("'{name}' out of range: {value}"))
+}
+
+/// Reads a (nullable) string: `null` is read as an empty string, like the binary parser does.
+fn str_or_null<'a>(data: &'a Map<String, Value>, name: &str) -> Result<&'a str, String> {
+    match data.get(name) {
+        Some(Value::String(s)) => Ok(s),
+        Some(Value::Null) => Ok(""),
+        _ => Err(format!("missing or invalid '{name}' string")),
+    }
+}
+
+/// Reads (nullable) bytes, that Kafka renders as a Base64 JSON string.
+fn bytes_or_null(data: &Map<String, Value>, name: &str) -> Result<Vec<u8>, String> {
+    match data.get(name) {
+        Some(Value::String(s)) => {
+            base64::engine::general_purpose::STANDARD.decode(s).map_err(|e| format!("invalid '{name}' base64: {e}"))
+        },
+        Some(Value::Null) => Ok(Vec::new()),
+        _ => Err(format!("missing or invalid '{name}' bytes")),
+    }
+}
+
+fn write_i16(bytes: &mut Vec<u8>, value: i16) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_i32(bytes: &mut Vec<u8>, value: i32) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_i64(bytes: &mut Vec<u8>, value: i64) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Writes the length of an array, failing if it doesn't fit the `i32` Kafka uses for it.
+fn write_len(bytes: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    write_i32(bytes, i32::try_from(len).map_err(|_| format!("array too long: {len} items"))?);
+    Ok(())
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) -> Result<(), String> {
+    let len = i16::try_from(value.len()).map_err(|_| format!("string too long: {} bytes", value.len()))?;
+    write_i16(bytes, len);
+    bytes.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, value: &[u8]) -> Result<(), String> {
+    let len = i32::try_from(value.len()).map_err(|_| format!("bytes too long: {} bytes", value.len()))?;
+    write_i32(bytes, len);
+    bytes.extend_from_slice(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{is_thread_safe, read_fixture};
+    use crate::{DumpLogError, DumpLogReader, KonsumerOffsetsError};
+
+    const DUMP: &str = r#"Dumping /var/lib/kafka/data/__consumer_offsets-46/00000000000000000000.log
+Log starting offset: 0
+baseOffset: 0 lastOffset: 0 count: 1 baseSequence: 0 lastSequence: 0 producerId: -1 producerEpoch: -1 partitionLeaderEpoch: 0 isTransactional: false isControl: false deleteHorizonMs: OptionalLong.empty position: 0 CreateTime: 1672871009232 size: 150 magic: 2 compresscodec: none crc: 1 isvalid: true
+| offset: 0 CreateTime: 1672871009232 keySize: 54 valueSize: 28 sequence: 0 headerKeys: [] key: {"type":"1","data":{"group":"ivan-experimental-consumer","topic":"__consumer_offsets","partition":46}} payload: {"version":"3","data":{"offset":97507,"leaderEpoch":-1,"metadata":"","commitTimestamp":1672871009232}}
+baseOffset: 1 lastOffset: 1 count: 1 baseSequence: 0 lastSequence: 0 producerId: 7 producerEpoch: 0 partitionLeaderEpoch: 0 isTransactional: true isControl: true deleteHorizonMs: OptionalLong.empty position: 150 CreateTime: 1672871009300 size: 78 magic: 2 compresscodec: none crc: 2 isvalid: true
+| offset: 1 CreateTime: 1672871009300 keySize: 4 valueSize: 6 sequence: 0 headerKeys: [] endTxnMarker: COMMIT coordinatorEpoch: 0
+baseOffset: 2 lastOffset: 3 count: 2 baseSequence: 0 lastSequence: 1 producerId: -1 producerEpoch: -1 partitionLeaderEpoch: 0 isTransactional: false isControl: false deleteHorizonMs: OptionalLong.empty position: 228 CreateTime: 1672871010000 size: 600 magic: 2 compresscodec: none crc: 3 isvalid: true
+| offset: 2 CreateTime: 1672870956437 keySize: 31 valueSize: 300 sequence: 0 headerKeys: [] key: {"type":"2","data":{"group":"kafkesc-devcluster-group-id"}} payload: {"version":"3","data":{"protocolType":"consumer","generation":7,"protocol":"range","leader":"rdkafka-9f4fc1b0-1d7d-4471-90e8-e0f64f3c9d9f","currentStateTimestamp":1672870956437,"members":[{"memberId":"rdkafka-9f4fc1b0-1d7d-4471-90e8-e0f64f3c9d9f","groupInstanceId":null,"clientId":"rdkafka","clientHost":"/172.18.0.1","rebalanceTimeout":300000,"sessionTimeout":45000,"subscription":{"topics":["t01"],"userData":null,"ownedPartitions":[]},"assignment":{"assignedPartitions":[{"topic":"t01","partitions":[0,1,2]}],"userData":null}}]}}
+| offset: 3 CreateTime: 1672871010000 keySize: 54 valueSize: -1 sequence: 1 headerKeys: [] key: {"type":"1","data":{"group":"ivan-experimental-consumer","topic":"__consumer_offsets","partition":46}} payload: <DELETE>
+"#;
+
+    #[test]
+    fn test_read_dump() {
+        let records = DumpLogReader::new(DUMP.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!((records[0].partition, records[0].offset, records[0].timestamp), (46, 0, 1672871009232));
+        assert_eq!(records[0].data, read_fixture("offset_commit", 1));
+
+        assert_eq!((records[1].partition, records[1].offset, records[1].timestamp), (46, 2, 1672870956437));
+        assert_eq!(records[1].data, read_fixture("group_metadata", 1));
+
+        assert_eq!(records[2].offset, 3);
+        assert!(records[2].data.is_tombstone());
+        assert_eq!(records[2].data.group(), "ivan-experimental-consumer");
+    }
+
+    #[test]
+    fn test_read_dump_with_partition() {
+        let dump = DUMP.lines().skip(1).collect::<Vec<_>>().join("\n");
+        let records = DumpLogReader::new(dump.as_bytes()).with_partition(7).collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(records.iter().all(|r| r.partition == 7));
+    }
+
+    #[test]
+    fn test_read_dump_errors() {
+        let dump = "| offset: 5 CreateTime: 1 key: offset_commit::group=g,partition=t-0 payload: offset=1\n\
+                    | offset: 6 CreateTime: 1 key: {\"type\":\"2\",\"data\":{\"group\":\"g\"}} payload: <DELETE>\n\
+                    | offset: 7 CreateTime: 1 key: {\"type\":\"1\",\"data\":{\"group\":\"g\",\"topic\":\"t\",\"partition\":0}} payload: {\"version\":\"9\",\"data\":{\"offset\":1,\"leaderEpoch\":-1,\"metadata\":\"\",\"commitTimestamp\":1}}\n";
+        let mut records = DumpLogReader::new(dump.as_bytes());
+
+        assert!(matches!(records.next(), Some(Err(DumpLogError::InvalidLine(1, _)))));
+        assert_eq!(records.next().unwrap().unwrap().data.group(), "g");
+        assert!(matches!(
+            records.next(),
+            Some(Err(DumpLogError::Parsing(3, KonsumerOffsetsError::UnsupportedOffsetCommitSchema(9))))
+        ));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_read_dump_invalid_values() {
+        let too_long = "g".repeat(i16::MAX as usize + 1);
+        let dump = format!(
+            "| offset: 1 CreateTime: 1 key: {{\"type\":\"1\",\"data\":{{\"group\":\"g\",\"topic\":\"t\",\"partition\":0}}}} payload: {{\"version\":\"3\",\"data\":{{\"members\":[]}}}}\n\
+             | offset: 2 CreateTime: 1 key: {{\"type\":\"1\",\"data\":{{\"group\":\"g\",\"topic\":\"t\",\"partition\":3000000000}}}} payload: <DELETE>\n\
+             | offset: 3 CreateTime: 1 key: {{\"type\":\"2\",\"data\":{{\"group\":\"{too_long}\"}}}} payload: <DELETE>\n"
+        );
+        let errors = DumpLogReader::new(dump.as_bytes())
+            .map(|r| match r {
+                Err(DumpLogError::InvalidLine(line, reason)) => (line, reason),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        // Payload shaped like group metadata, for an offset commit key
+        assert_eq!(errors[0], (1, "missing or invalid 'offset' integer".to_string()));
+        assert_eq!(errors[1], (2, "'partition' out of range: 3000000000".to_string()));
+        assert_eq!(errors[2], (3, "string too long: 32768 bytes".to_string()));
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<DumpLogReader<&[u8]>>();
+    }
+}
@@ -50,13 +50,113 @@ pub enum KonsumerOffsetsError {
     UnableToParseForVersion(String, i16, String),
 }
 
+/// Errors variants that can be encountered when reading the output of `kafka-dump-log.sh --offsets-decoder`.
+///
+/// See [`crate::DumpLogReader`] for details about the supported output.
+#[cfg(feature = "dump_log")]
+#[derive(Error, Debug)]
+pub enum DumpLogError {
+    /// An I/O error occurred on the underlying reader (line number, error).
+    #[error("I/O failure reading kafka-dump-log output at line {0}: {1}")]
+    Io(usize, #[source] std::io::Error),
+
+    /// A line of the output could not be parsed (line number, reason).
+    #[error("Failed to parse kafka-dump-log output at line {0}: {1}")]
+    InvalidLine(usize, String),
+
+    /// The record re-encoded from a line failed to be parsed (line number, error).
+    #[error("Failed to parse record of kafka-dump-log output at line {0}: {1}")]
+    Parsing(usize, #[source] KonsumerOffsetsError),
+}
+
+/// Errors variants that can be encountered when writing or reading captures.
+///
+/// See [`crate::CaptureWriter`] for details about the capture format.
+#[derive(Error, Debug)]
+pub enum CaptureError {
+    /// An I/O error occurred on the underlying writer or reader.
+    #[error("Capture I/O failure: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The data does not start with the capture magic bytes.
+    #[error("Not a capture: invalid magic bytes")]
+    InvalidMagic,
+
+    /// The capture was written with a version of the format which this crate doesn't support.
+    #[error("Encountered a not supported capture version: {0}")]
+    UnsupportedVersion(u16),
+
+    /// The capture uses a compression which is unknown, or not enabled via feature flags.
+    #[error("Encountered a not supported (or not enabled) capture compression: {0}")]
+    UnsupportedCompression(u8),
+
+    /// The capture content is inconsistent (ex. checksum mismatch).
+    #[error("Capture is corrupted: {0}")]
+    Corrupted(String),
+
+    /// A record is too large to fit in a block (length of the record, in bytes).
+    ///
+    /// See [`crate::MAX_CAPTURE_BLOCK_LEN`].
+    #[error("Record too large for a capture block: {0} bytes")]
+    RecordTooLarge(usize),
+
+    /// A record of the capture failed to be parsed.
+    #[error("Failed to parse captured record: {0}")]
+    Parsing(#[from] KonsumerOffsetsError),
+}
+
+/// Errors variants that can be encountered when exporting to SQLite.
+///
+/// See [`crate::SqliteExporter`] for details about the database schema.
+#[cfg(feature = "sqlite")]
+#[derive(Error, Debug)]
+pub enum SqliteExportError {
+    /// An error occurred on the underlying SQLite database.
+    #[error("SQLite failure: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// The database was created with a version of the schema which this crate doesn't support.
+    #[error("Encountered a not supported SQLite schema version: {0}")]
+    UnsupportedSchemaVersion(i32),
+}
+
+/// Errors variants that can be encountered when converting from Protobuf or Avro.
+///
+/// See [`crate::proto`] and [`crate::avro`] for details about the conversions.
+#[cfg(any(feature = "protobuf", feature = "avro"))]
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum ConversionError {
+    /// The encoded bytes are malformed.
+    #[error("Failed to decode: {0}")]
+    Decoding(String),
+
+    /// A field, required by the types of this crate, is missing.
+    #[error("Missing required field: {0}")]
+    MissingField(&'static str),
+
+    /// A field has a value that doesn't fit in the type used for it by this crate (field, value).
+    #[error("Value of {0} is out of range: {1}")]
+    OutOfRange(&'static str, i64),
+
+    /// A timestamp cannot be represented by the `ts_*` feature in use.
+    #[error("Invalid timestamp: {0}")]
+    Timestamp(#[from] KonsumerOffsetsError),
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::is_thread_safe;
-    use crate::KonsumerOffsetsError;
+    use crate::{CaptureError, KonsumerOffsetsError};
 
     #[test]
     fn test_types_thread_safety() {
         is_thread_safe::<KonsumerOffsetsError>();
+        is_thread_safe::<CaptureError>();
+        #[cfg(feature = "dump_log")]
+        is_thread_safe::<crate::DumpLogError>();
+        #[cfg(feature = "sqlite")]
+        is_thread_safe::<crate::SqliteExportError>();
+        #[cfg(any(feature = "protobuf", feature = "avro"))]
+        is_thread_safe::<crate::ConversionError>();
     }
 }
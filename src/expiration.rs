@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::group_metadata::GroupMetadata;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// Value of `GroupMetadata::protocol_type` used by groups of Kafka Consumers.
+const CONSUMER_PROTOCOL_TYPE: &str = "consumer";
+
+/// Broker configuration that controls the expiration of committed offsets.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OffsetRetentionConfig {
+    /// Broker configuration `offsets.retention.minutes`, converted to milliseconds.
+    ///
+    /// Default is 7 days.
+    pub retention_ms: i64,
+
+    /// Broker configuration `offsets.retention.check.interval.ms`.
+    ///
+    /// The Broker checks for expired offsets with this frequency: an offset is actually
+    /// removed up to this long after it has expired. Default is 10 minutes.
+    pub check_interval_ms: i64,
+}
+
+impl Default for OffsetRetentionConfig {
+    fn default() -> Self {
+        Self {
+            retention_ms: 7 * 24 * 60 * 60 * 1000,
+            check_interval_ms: 10 * 60 * 1000,
+        }
+    }
+}
+
+/// What the expiration of a committed offset is based upon.
+///
+/// See [`OffsetExpirationSimulator`] for details of the rules applied by the Broker.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OffsetExpirationBasis {
+    /// The offset was committed with an explicit expiration timestamp
+    /// (only in [`OffsetCommit::schema_version`] `1`).
+    ExpireTimestamp,
+
+    /// The group has a protocol type, but no members: the offset expires when the retention period has passed
+    /// since the group became empty ([`GroupMetadata::current_state_timestamp`]).
+    GroupEmpty,
+
+    /// The offset expires when the retention period has passed since it was committed.
+    ///
+    /// This applies to groups without [`GroupMetadata`] or [`GroupMetadata::protocol_type`]
+    /// (i.e. _standalone_ consumers), to topics no longer subscribed by a consumer group with members,
+    /// and to empty groups with no known [`GroupMetadata::current_state_timestamp`].
+    CommitTimestamp,
+
+    /// The group has members that are subscribed to the topic (or don't use the `consumer`
+    /// protocol type): the offset does not expire for as long as that is the case.
+    GroupActive,
+}
+
+/// Predicted expiration of a committed offset, produced by [`OffsetExpirationSimulator`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OffsetExpiration {
+    /// Group.
+    pub group: String,
+
+    /// Topic.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Committed offset.
+    pub offset: i64,
+
+    /// Timestamp of when [`Self::offset`] was committed.
+    pub commit_timestamp: i64,
+
+    /// What the expiration is based upon.
+    pub basis: OffsetExpirationBasis,
+
+    /// Timestamp from which the Broker will consider the offset expired.
+    ///
+    /// `None` if [`Self::basis`] is [`OffsetExpirationBasis::GroupActive`].
+    pub expires_at: Option<i64>,
+
+    /// Timestamp by which the Broker will have removed the offset: this is [`Self::expires_at`]
+    /// plus [`OffsetRetentionConfig::check_interval_ms`].
+    pub removed_by: Option<i64>,
+
+    /// `true` if, at the `now_ms` timestamp used for the prediction, the offset is already expired.
+    pub expired: bool,
+}
+
+impl OffsetExpiration {
+    /// `true` if the offset will expire within `horizon_ms` from `now_ms` (or is already expired).
+    pub fn expires_within(&self, now_ms: i64, horizon_ms: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now_ms.saturating_add(horizon_ms))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct CommittedOffset {
+    offset: i64,
+    commit_timestamp: i64,
+    expire_timestamp: Option<i64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct GroupState {
+    protocol_type: Option<String>,
+    is_empty: bool,
+    current_state_timestamp: Option<i64>,
+    subscribed_topics: BTreeSet<String>,
+}
+
+/// Predicts which committed offsets the Broker will expire, and when.
+///
+/// Committed offsets don't live forever in `__consumer_offsets`: the [Group Coordinator]
+/// periodically (every `offsets.retention.check.interval.ms`) removes the expired ones, by
+/// producing a tombstone for them. Since [KIP-211], the rules are those of
+/// `kafka.coordinator.group.GroupMetadata#removeExpiredOffsets`:
+///
+/// 1. Offsets committed with an explicit expiration timestamp (only possible with
+///    [`OffsetCommit::schema_version`] `1`) expire at that timestamp.
+/// 2. If the group has [`GroupMetadata`] with a protocol type but no members (i.e. it's _Empty_), all its
+///    offsets expire when `offsets.retention.minutes` have passed since the group became empty
+///    ([`GroupMetadata::current_state_timestamp`]), or since they were committed if that's not known.
+/// 3. If the group has no [`GroupMetadata`], or no protocol type (i.e. _standalone_ consumers that
+///    manually assign partitions, and use Kafka for offset storage only), offsets expire when
+///    `offsets.retention.minutes` have passed since they were committed.
+/// 4. If the group has members, uses the `consumer` protocol type and is aware of their
+///    subscriptions, offsets of topics that are **not** subscribed anymore expire when
+///    `offsets.retention.minutes` have passed since they were committed.
+/// 5. Otherwise, offsets don't expire.
+///
+/// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+/// [KIP-211]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-211%3A+Revise+Expiration+Semantics+of+Consumer+Group+Offsets
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OffsetExpirationSimulator {
+    config: OffsetRetentionConfig,
+    offsets: BTreeMap<String, BTreeMap<(String, i32), CommittedOffset>>,
+    groups: BTreeMap<String, GroupState>,
+}
+
+impl OffsetExpirationSimulator {
+    /// Create a [`Self`], that simulates Brokers configured with the given [`OffsetRetentionConfig`].
+    pub fn new(config: OffsetRetentionConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    pub fn update(&mut self, offset_commit: &OffsetCommit) {
+        let key = (offset_commit.topic.clone(), offset_commit.partition);
+
+        if offset_commit.is_tombstone {
+            if let Some(offsets) = self.offsets.get_mut(&offset_commit.group) {
+                offsets.remove(&key);
+                if offsets.is_empty() {
+                    self.offsets.remove(&offset_commit.group);
+                }
+            }
+            return;
+        }
+
+        let expire_timestamp = timestamp_millis(&offset_commit.expire_timestamp);
+        self.offsets.entry(offset_commit.group.clone()).or_default().insert(
+            key,
+            CommittedOffset {
+                offset: offset_commit.offset,
+                commit_timestamp: timestamp_millis(&offset_commit.commit_timestamp),
+                expire_timestamp: (offset_commit.schema_version == 1 && expire_timestamp >= 0)
+                    .then_some(expire_timestamp),
+            },
+        );
+    }
+
+    /// Update [`Self`] with a [`GroupMetadata`].
+    pub fn update_group_metadata(&mut self, group_metadata: &GroupMetadata) {
+        if group_metadata.is_tombstone {
+            self.groups.remove(&group_metadata.group);
+            return;
+        }
+
+        self.groups.insert(
+            group_metadata.group.clone(),
+            GroupState {
+                protocol_type: Some(group_metadata.protocol_type.clone()).filter(|pt| !pt.is_empty()),
+                is_empty: group_metadata.members.is_empty(),
+                current_state_timestamp: (group_metadata.schema_version >= 2)
+                    .then(|| timestamp_millis(&group_metadata.current_state_timestamp)),
+                subscribed_topics: group_metadata
+                    .members
+                    .iter()
+                    .flat_map(|m| m.subscription.subscribed_topics.iter().cloned())
+                    .collect(),
+            },
+        );
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        match data {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => self.update(offset_commit),
+            KonsumerOffsetsData::GroupMetadata(group_metadata) => self.update_group_metadata(group_metadata),
+        }
+    }
+
+    /// Predicts the expiration of every committed offset known to [`Self`], as of `now_ms`.
+    ///
+    /// Results are sorted by `(group, topic, partition)`.
+    pub fn predict(&self, now_ms: i64) -> Vec<OffsetExpiration> {
+        self.offsets
+            .iter()
+            .flat_map(|(group, offsets)| {
+                let group_state = self.groups.get(group);
+                offsets.iter().map(move |((topic, partition), committed)| {
+                    self.predict_offset(group, group_state, topic, *partition, committed, now_ms)
+                })
+            })
+            .collect()
+    }
+
+    /// Predicts which committed offsets known to [`Self`] are already expired as of `now_ms`,
+    /// or will be within `horizon_ms` from it.
+    pub fn expiring_within(&self, now_ms: i64, horizon_ms: i64) -> Vec<OffsetExpiration> {
+        self.predict(now_ms).into_iter().filter(|e| e.expires_within(now_ms, horizon_ms)).collect()
+    }
+
+    fn predict_offset(
+        &self,
+        group: &str,
+        group_state: Option<&GroupState>,
+        topic: &str,
+        partition: i32,
+        committed: &CommittedOffset,
+        now_ms: i64,
+    ) -> OffsetExpiration {
+        let retention = self.config.retention_ms;
+
+        let (basis, base_timestamp) = match group_state {
+            // Like Kafka, only groups with a protocol type expire offsets from when they became empty.
+            Some(state) if state.is_empty && state.protocol_type.is_some() => match state.current_state_timestamp {
+                Some(ts) => (OffsetExpirationBasis::GroupEmpty, Some(ts)),
+                None => (OffsetExpirationBasis::CommitTimestamp, Some(committed.commit_timestamp)),
+            },
+            Some(state)
+                if state.protocol_type.as_deref() == Some(CONSUMER_PROTOCOL_TYPE)
+                    && !state.subscribed_topics.contains(topic) =>
+            {
+                (OffsetExpirationBasis::CommitTimestamp, Some(committed.commit_timestamp))
+            },
+            Some(state) if state.protocol_type.is_some() => (OffsetExpirationBasis::GroupActive, None),
+            _ => (OffsetExpirationBasis::CommitTimestamp, Some(committed.commit_timestamp)),
+        };
+
+        let (basis, expires_at) = match (basis, committed.expire_timestamp) {
+            (OffsetExpirationBasis::GroupActive, _) => (basis, None),
+            (_, Some(expire_timestamp)) => (OffsetExpirationBasis::ExpireTimestamp, Some(expire_timestamp)),
+            (_, None) => (basis, base_timestamp.map(|ts| ts.saturating_add(retention))),
+        };
+
+        OffsetExpiration {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            partition,
+            offset: committed.offset,
+            commit_timestamp: committed.commit_timestamp,
+            basis,
+            expires_at,
+            removed_by: expires_at.map(|ts| ts.saturating_add(self.config.check_interval_ms)),
+            expired: expires_at.is_some_and(|ts| now_ms >= ts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{commit, group, is_thread_safe, member, timestamp_from_millis};
+
+    const RETENTION: i64 = 1_000;
+
+    fn subscribed_group(name: &str, subscribed_topics: &[&str], current_state_timestamp: i64) -> GroupMetadata {
+        let members = if subscribed_topics.is_empty() {
+            vec![]
+        } else {
+            vec![member("a", subscribed_topics, &[])]
+        };
+        GroupMetadata {
+            schema_version: 3,
+            current_state_timestamp: timestamp_from_millis(current_state_timestamp),
+            ..group(name, members)
+        }
+    }
+
+    fn simulator() -> OffsetExpirationSimulator {
+        OffsetExpirationSimulator::new(OffsetRetentionConfig {
+            retention_ms: RETENTION,
+            check_interval_ms: 100,
+        })
+    }
+
+    #[test]
+    fn test_standalone_group() {
+        let mut sim = simulator();
+        sim.update(&commit("g", "t", 0, 42, 500));
+
+        let predictions = sim.predict(1_000);
+        assert_eq!(predictions.len(), 1);
+        assert_eq!(predictions[0].basis, OffsetExpirationBasis::CommitTimestamp);
+        assert_eq!(predictions[0].expires_at, Some(1_500));
+        assert_eq!(predictions[0].removed_by, Some(1_600));
+        assert!(!predictions[0].expired);
+        assert!(sim.predict(1_500)[0].expired);
+    }
+
+    #[test]
+    fn test_active_and_empty_group() {
+        let mut sim = simulator();
+        sim.update(&commit("g", "subscribed", 0, 42, 500));
+        sim.update(&commit("g", "unsubscribed", 0, 42, 500));
+        sim.update_group_metadata(&subscribed_group("g", &["subscribed"], 200));
+
+        let predictions = sim.predict(10_000);
+        assert_eq!(predictions[0].topic, "subscribed");
+        assert_eq!(predictions[0].basis, OffsetExpirationBasis::GroupActive);
+        assert_eq!(predictions[0].expires_at, None);
+        assert!(!predictions[0].expired);
+        assert_eq!(predictions[1].topic, "unsubscribed");
+        assert_eq!(predictions[1].basis, OffsetExpirationBasis::CommitTimestamp);
+        assert!(predictions[1].expired);
+        assert_eq!(sim.expiring_within(0, 2_000).len(), 1);
+
+        // Group becomes empty: all offsets expire, based on when that happened.
+        sim.update_group_metadata(&subscribed_group("g", &[], 3_000));
+        let predictions = sim.predict(3_500);
+        assert!(predictions.iter().all(|p| p.basis == OffsetExpirationBasis::GroupEmpty));
+        assert!(predictions.iter().all(|p| p.expires_at == Some(4_000) && !p.expired));
+    }
+
+    #[test]
+    fn test_empty_group_without_protocol_type() {
+        let mut sim = simulator();
+        sim.update(&commit("g", "t", 0, 42, 500));
+        sim.update_group_metadata(&GroupMetadata {
+            protocol_type: String::new(),
+            ..subscribed_group("g", &[], 3_000)
+        });
+
+        let predictions = sim.predict(1_500);
+        assert_eq!(predictions[0].basis, OffsetExpirationBasis::CommitTimestamp);
+        assert_eq!(predictions[0].expires_at, Some(1_500));
+        assert!(predictions[0].expired);
+    }
+
+    #[test]
+    fn test_explicit_expire_timestamp() {
+        let mut sim = simulator();
+        let mut offset_commit = commit("g", "t", 0, 42, 500);
+        offset_commit.schema_version = 1;
+        offset_commit.expire_timestamp = timestamp_from_millis(700);
+        sim.update(&offset_commit);
+
+        let predictions = sim.predict(800);
+        assert_eq!(predictions[0].basis, OffsetExpirationBasis::ExpireTimestamp);
+        assert!(predictions[0].expired);
+    }
+
+    #[test]
+    fn test_tombstone_removes_offset() {
+        let mut sim = simulator();
+        sim.update(&commit("g", "t", 0, 42, 500));
+        let mut tombstone = commit("g", "t", 0, 42, 0);
+        tombstone.is_tombstone = true;
+        sim.update_from(&KonsumerOffsetsData::OffsetCommit(tombstone));
+        assert!(sim.predict(0).is_empty());
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<OffsetRetentionConfig>();
+        is_thread_safe::<OffsetExpirationBasis>();
+        is_thread_safe::<OffsetExpiration>();
+        is_thread_safe::<OffsetExpirationSimulator>();
+    }
+}
@@ -0,0 +1,494 @@
+use std::fmt;
+use std::ops::Range;
+
+use bytes_parser::BytesParser;
+
+use crate::errors::KonsumerOffsetsError;
+use crate::errors::KonsumerOffsetsError::{
+    ByteParsingError, MessageKeyMissing, UnsupportedConsumerProtocolAssignmentVersion,
+    UnsupportedConsumerProtocolSubscriptionVersion, UnsupportedGroupMetadataSchema, UnsupportedMessageVersion,
+    UnsupportedOffsetCommitSchema,
+};
+
+/// Maximum number of bytes shown in the raw hex column, when displaying a [`RecordExplanation`].
+const DISPLAY_MAX_HEX_BYTES: usize = 16;
+
+/// Part of a `__consumer_offsets` record that an [`ExplainedField`] was parsed from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordSection {
+    /// The record key.
+    Key,
+    /// The record payload.
+    Payload,
+}
+
+/// A field parsed out of the bytes of a `__consumer_offsets` record, annotated with where it came from.
+///
+/// See [`explain`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExplainedField {
+    /// Part of the record the field was parsed from.
+    pub section: RecordSection,
+
+    /// Path of the field within the parsed data (ex. `members[0].subscription.rack_id`).
+    ///
+    /// Fields that are the _length_ of a string or a collection have the `.length` suffix.
+    pub path: String,
+
+    /// Range of bytes of [`Self::section`] the field was parsed from.
+    ///
+    /// This is empty if the field is not present in the bytes (see [`Self::branch`]).
+    pub range: Range<usize>,
+
+    /// Hexadecimal representation of the bytes in [`Self::range`].
+    pub raw_hex: String,
+
+    /// Decoded value of the field.
+    pub value: String,
+
+    /// Schema version branch that determined how the field was parsed, if any
+    /// (ex. `schema_version >= 3`).
+    pub branch: Option<String>,
+}
+
+/// Annotated byte map of a `__consumer_offsets` record, produced by [`explain`].
+#[derive(Debug, Eq, PartialEq)]
+pub struct RecordExplanation {
+    /// Fields parsed out of the record, in the order they were parsed.
+    pub fields: Vec<ExplainedField>,
+
+    /// Error that stopped the parsing, if any: [`Self::fields`] contains what was parsed up to that point.
+    pub error: Option<KonsumerOffsetsError>,
+}
+
+impl RecordExplanation {
+    /// `true` if the whole record was parsed without errors.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl fmt::Display for RecordExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut section = None;
+
+        for field in &self.fields {
+            if section != Some(field.section) {
+                section = Some(field.section);
+                writeln!(
+                    f,
+                    "{}",
+                    if field.section == RecordSection::Key {
+                        "KEY"
+                    } else {
+                        "PAYLOAD"
+                    }
+                )?;
+            }
+
+            let hex = if field.raw_hex.len() > DISPLAY_MAX_HEX_BYTES * 2 {
+                format!("{}...", &field.raw_hex[..DISPLAY_MAX_HEX_BYTES * 2])
+            } else {
+                field.raw_hex.clone()
+            };
+
+            write!(
+                f,
+                "  [{:>5}..{:<5}] {:<35} {} = {}",
+                field.range.start, field.range.end, hex, field.path, field.value
+            )?;
+            if let Some(branch) = &field.branch {
+                write!(f, "  ({branch})")?;
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(error) = &self.error {
+            writeln!(f, "ERROR: {error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Explains how the bytes of a `__consumer_offsets` record map to the fields of [`crate::KonsumerOffsetsData`].
+///
+/// This follows the same logic of [`crate::KonsumerOffsetsData::try_from_bytes`], but instead of
+/// producing the parsed data, it produces an annotated map of each field parsed: its byte range,
+/// raw bytes, decoded value and the schema version branch that applied. This goes all the way down to
+/// the nested [`crate::MemberMetadata`], [`crate::ConsumerProtocolSubscription`],
+/// [`crate::ConsumerProtocolAssignment`] and [`crate::TopicPartitions`].
+///
+/// If the record fails to parse, the explanation contains all the fields up to the failure point,
+/// and the error that occurred.
+///
+/// # Arguments
+///
+/// * `key` - Key of the record.
+/// * `payload` - Payload of the record: `None` if the record is a tombstone.
+pub fn explain(key: Option<&[u8]>, payload: Option<&[u8]>) -> RecordExplanation {
+    let mut fields = Vec::new();
+    let error = explain_record(&mut fields, key, payload).err();
+
+    RecordExplanation {
+        fields,
+        error,
+    }
+}
+
+fn explain_record(
+    fields: &mut Vec<ExplainedField>,
+    key: Option<&[u8]>,
+    payload: Option<&[u8]>,
+) -> Result<(), KonsumerOffsetsError> {
+    let key = key.ok_or(MessageKeyMissing)?;
+
+    let mut k = Walker::new(fields, RecordSection::Key, key);
+    let message_version = k.i16("message_version", None)?;
+    match message_version {
+        // Key of an `OffsetCommit`: mirrors `OffsetCommit::try_from`.
+        0..=1 => {
+            k.str("group", None)?;
+            k.str("topic", None)?;
+            k.i32("partition", None)?;
+        },
+        // Key of a `GroupMetadata`: mirrors `GroupMetadata::try_from`.
+        2 => {
+            k.str("group", None)?;
+        },
+        _ => return Err(UnsupportedMessageVersion(message_version)),
+    }
+
+    let mut p = Walker::new(fields, RecordSection::Payload, payload.unwrap_or_default());
+    match (payload, message_version) {
+        (None, _) => {
+            p.absent("is_tombstone", "true", "no payload");
+            Ok(())
+        },
+        (Some(_), 0..=1) => explain_offset_commit_payload(&mut p),
+        (Some(_), _) => explain_group_metadata_payload(&mut p),
+    }
+}
+
+/// Mirrors `OffsetCommit::parse_payload`.
+fn explain_offset_commit_payload(p: &mut Walker) -> Result<(), KonsumerOffsetsError> {
+    let schema_version = p.i16("schema_version", None)?;
+    if !(0..=3).contains(&schema_version) {
+        return Err(UnsupportedOffsetCommitSchema(schema_version));
+    }
+
+    p.i64("offset", None)?;
+
+    if schema_version >= 3 {
+        p.i32("leader_epoch", Some("schema_version >= 3"))?;
+    } else {
+        p.absent("leader_epoch", "-1", "schema_version < 3");
+    }
+
+    p.str("metadata", None)?;
+    p.i64("commit_timestamp", None)?;
+
+    if schema_version == 1 {
+        p.i64("expire_timestamp", Some("schema_version == 1"))?;
+    } else {
+        p.absent("expire_timestamp", "-1", "schema_version != 1");
+    }
+
+    Ok(())
+}
+
+/// Mirrors `GroupMetadata::parse_payload`.
+fn explain_group_metadata_payload(p: &mut Walker) -> Result<(), KonsumerOffsetsError> {
+    let schema_version = p.i16("schema_version", None)?;
+    if !(0..=3).contains(&schema_version) {
+        return Err(UnsupportedGroupMetadataSchema(schema_version));
+    }
+
+    p.str("protocol_type", None)?;
+    p.i32("generation", None)?;
+    p.str("protocol", None)?;
+    p.str("leader", None)?;
+
+    if schema_version >= 2 {
+        p.i64("current_state_timestamp", Some("schema_version >= 2"))?;
+    } else {
+        p.absent("current_state_timestamp", "-1", "schema_version < 2");
+    }
+
+    let members_len = p.i32("members.length", None)?;
+    for i in 0..members_len {
+        explain_member_metadata(p, &format!("members[{i}]"), schema_version)?;
+    }
+
+    Ok(())
+}
+
+/// Mirrors `MemberMetadata::try_from`.
+fn explain_member_metadata(p: &mut Walker, path: &str, schema_version: i16) -> Result<(), KonsumerOffsetsError> {
+    p.str(&format!("{path}.id"), None)?;
+
+    if schema_version >= 3 {
+        p.str(&format!("{path}.group_instance_id"), Some("schema_version >= 3"))?;
+    } else {
+        p.absent(&format!("{path}.group_instance_id"), "\"\"", "schema_version < 3");
+    }
+
+    p.str(&format!("{path}.client_id"), None)?;
+    p.str(&format!("{path}.client_host"), None)?;
+
+    if schema_version >= 1 {
+        p.i32(&format!("{path}.rebalance_timeout"), Some("schema_version >= 1"))?;
+    } else {
+        p.absent(&format!("{path}.rebalance_timeout"), "0", "schema_version < 1");
+    }
+
+    p.i32(&format!("{path}.session_timeout"), None)?;
+
+    let subscription_len = p.i32(&format!("{path}.subscription.length"), None)?;
+    explain_subscription(&mut p.sub(subscription_len)?, &format!("{path}.subscription"))?;
+
+    let assignment_len = p.i32(&format!("{path}.assignment.length"), None)?;
+    explain_assignment(&mut p.sub(assignment_len)?, &format!("{path}.assignment"))?;
+
+    Ok(())
+}
+
+/// Mirrors `ConsumerProtocolSubscription::try_from`.
+fn explain_subscription(p: &mut Walker, path: &str) -> Result<(), KonsumerOffsetsError> {
+    let schema_version = p.i16(&format!("{path}.schema_version"), None)?;
+    if !(0..=3).contains(&schema_version) {
+        return Err(UnsupportedConsumerProtocolSubscriptionVersion(schema_version));
+    }
+
+    let topics_len = p.i32(&format!("{path}.subscribed_topics.length"), None)?;
+    for i in 0..topics_len {
+        p.str(&format!("{path}.subscribed_topics[{i}]"), None)?;
+    }
+
+    p.bytes(&format!("{path}.user_data"), None)?;
+
+    if schema_version >= 1 {
+        let branch = Some("schema_version >= 1");
+        let owned_len = p.i32(&format!("{path}.owned_topic_partitions.length"), branch)?;
+        for i in 0..owned_len {
+            explain_topic_partitions(p, &format!("{path}.owned_topic_partitions[{i}]"))?;
+        }
+    } else {
+        p.absent(&format!("{path}.owned_topic_partitions"), "[]", "schema_version < 1");
+    }
+
+    if schema_version >= 2 {
+        p.i32(&format!("{path}.generation_id"), Some("schema_version >= 2"))?;
+    } else {
+        p.absent(&format!("{path}.generation_id"), "-1", "schema_version < 2");
+    }
+
+    if schema_version >= 3 {
+        p.str(&format!("{path}.rack_id"), Some("schema_version >= 3"))?;
+    } else {
+        p.absent(&format!("{path}.rack_id"), "\"\"", "schema_version < 3");
+    }
+
+    Ok(())
+}
+
+/// Mirrors `ConsumerProtocolAssignment::try_from`.
+fn explain_assignment(p: &mut Walker, path: &str) -> Result<(), KonsumerOffsetsError> {
+    let schema_version = p.i16(&format!("{path}.schema_version"), None)?;
+    if !(0..=3).contains(&schema_version) {
+        return Err(UnsupportedConsumerProtocolAssignmentVersion(schema_version));
+    }
+
+    let assigned_len = p.i32(&format!("{path}.assigned_topic_partitions.length"), None)?;
+    for i in 0..assigned_len {
+        explain_topic_partitions(p, &format!("{path}.assigned_topic_partitions[{i}]"))?;
+    }
+
+    p.bytes(&format!("{path}.user_data"), None)?;
+
+    Ok(())
+}
+
+/// Mirrors `TopicPartitions::try_from`.
+fn explain_topic_partitions(p: &mut Walker, path: &str) -> Result<(), KonsumerOffsetsError> {
+    p.str(&format!("{path}.topic"), None)?;
+
+    let partitions_len = p.i32(&format!("{path}.partitions.length"), None)?;
+    for i in 0..partitions_len {
+        p.i32(&format!("{path}.partitions[{i}]"), None)?;
+    }
+
+    Ok(())
+}
+
+/// Walks the bytes of a section of a record (or a slice of it), recording an [`ExplainedField`] for each parse.
+struct Walker<'a, 'f> {
+    fields: &'f mut Vec<ExplainedField>,
+    section: RecordSection,
+    bytes: &'a [u8],
+    parser: BytesParser<'a>,
+    base: usize,
+}
+
+impl<'a, 'f> Walker<'a, 'f> {
+    fn new(fields: &'f mut Vec<ExplainedField>, section: RecordSection, bytes: &'a [u8]) -> Self {
+        Self {
+            fields,
+            section,
+            bytes,
+            parser: BytesParser::from(bytes),
+            base: 0,
+        }
+    }
+
+    /// A [`Walker`] over the next `len` bytes, that reports byte ranges relative to the whole section.
+    fn sub(&mut self, len: i32) -> Result<Walker<'a, '_>, KonsumerOffsetsError> {
+        let start = self.parser.position();
+        self.parser.parse_slice(len as usize).map_err(ByteParsingError)?;
+        let bytes = &self.bytes[start..start + len as usize];
+
+        Ok(Walker {
+            fields: self.fields,
+            section: self.section,
+            bytes,
+            parser: BytesParser::from(bytes),
+            base: self.base + start,
+        })
+    }
+
+    fn record(&mut self, path: &str, start: usize, value: String, branch: Option<&str>) {
+        let end = self.parser.position();
+        self.fields.push(ExplainedField {
+            section: self.section,
+            path: path.to_string(),
+            range: self.base + start..self.base + end,
+            raw_hex: self.bytes[start..end].iter().map(|b| format!("{b:02x}")).collect(),
+            value,
+            branch: branch.map(String::from),
+        });
+    }
+
+    /// Records a field that is not present in the bytes, and takes a default value.
+    fn absent(&mut self, path: &str, default: &str, branch: &str) {
+        let start = self.parser.position();
+        self.record(path, start, default.to_string(), Some(branch));
+    }
+
+    fn i16(&mut self, path: &str, branch: Option<&str>) -> Result<i16, KonsumerOffsetsError> {
+        let start = self.parser.position();
+        let value = self.parser.parse_i16().map_err(ByteParsingError)?;
+        self.record(path, start, value.to_string(), branch);
+        Ok(value)
+    }
+
+    fn i32(&mut self, path: &str, branch: Option<&str>) -> Result<i32, KonsumerOffsetsError> {
+        let start = self.parser.position();
+        let value = self.parser.parse_i32().map_err(ByteParsingError)?;
+        self.record(path, start, value.to_string(), branch);
+        Ok(value)
+    }
+
+    fn i64(&mut self, path: &str, branch: Option<&str>) -> Result<i64, KonsumerOffsetsError> {
+        let start = self.parser.position();
+        let value = self.parser.parse_i64().map_err(ByteParsingError)?;
+        self.record(path, start, value.to_string(), branch);
+        Ok(value)
+    }
+
+    /// Mirrors `utils::parse_str`.
+    fn str(&mut self, path: &str, branch: Option<&str>) -> Result<(), KonsumerOffsetsError> {
+        let len = self.i16(&format!("{path}.length"), branch)?;
+        let start = self.parser.position();
+        let value = if len < 0 {
+            String::default()
+        } else {
+            self.parser.parse_str_utf8(len as usize).map_err(ByteParsingError)?.to_string()
+        };
+        self.record(path, start, format!("{value:?}"), branch);
+        Ok(())
+    }
+
+    /// Mirrors `utils::parse_vec_bytes`.
+    fn bytes(&mut self, path: &str, branch: Option<&str>) -> Result<(), KonsumerOffsetsError> {
+        let len = self.i32(&format!("{path}.length"), branch)?;
+        let start = self.parser.position();
+        let value = self.parser.parse_slice(len as usize).map_err(ByteParsingError)?;
+        let value = format!("{} bytes", value.len());
+        self.record(path, start, value, branch);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{is_thread_safe, read_fixture_bytes};
+
+    fn field<'a>(explanation: &'a RecordExplanation, path: &str) -> &'a ExplainedField {
+        explanation.fields.iter().find(|f| f.path == path).unwrap()
+    }
+
+    #[test]
+    fn test_explain_offset_commit() {
+        let (key, payload) = read_fixture_bytes("offset_commit", 1);
+        let explanation = explain(Some(&key), Some(&payload));
+        assert!(explanation.is_ok());
+
+        let message_version = field(&explanation, "message_version");
+        assert_eq!(message_version.section, RecordSection::Key);
+        assert_eq!(message_version.range, 0..2);
+        assert_eq!(message_version.raw_hex, "0001");
+        assert_eq!(message_version.value, "1");
+
+        assert_eq!(field(&explanation, "group").value, "\"ivan-experimental-consumer\"");
+        assert_eq!(field(&explanation, "partition").value, "46");
+        assert_eq!(field(&explanation, "offset").value, "97507");
+        assert_eq!(field(&explanation, "leader_epoch").branch.as_deref(), Some("schema_version >= 3"));
+
+        let expire_timestamp = field(&explanation, "expire_timestamp");
+        assert!(expire_timestamp.range.is_empty());
+        assert_eq!(expire_timestamp.range.end, payload.len());
+    }
+
+    #[test]
+    fn test_explain_group_metadata() {
+        let (key, payload) = read_fixture_bytes("group_metadata", 1);
+        let explanation = explain(Some(&key), Some(&payload));
+        assert!(explanation.is_ok());
+
+        assert_eq!(field(&explanation, "members[0].subscription.subscribed_topics[0]").value, "\"t01\"");
+        let partition = field(&explanation, "members[0].assignment.assigned_topic_partitions[0].partitions[2]");
+        assert_eq!(partition.value, "2");
+        assert_eq!(partition.raw_hex, "00000002");
+        assert_eq!(&payload[partition.range.clone()], &[0, 0, 0, 2]);
+        assert_eq!(explanation.fields.last().unwrap().range.end, payload.len());
+    }
+
+    #[test]
+    fn test_explain_partial() {
+        let (key, payload) = read_fixture_bytes("group_metadata", 1);
+        let truncated = &payload[..payload.len() - 10];
+        let explanation = explain(Some(&key), Some(truncated));
+        assert!(!explanation.is_ok());
+        assert!(matches!(explanation.error, Some(ByteParsingError(_))));
+        assert!(explanation.fields.iter().any(|f| f.path == "members[0].subscription.rack_id"));
+
+        let explanation = explain(None, None);
+        assert_eq!(explanation.error, Some(MessageKeyMissing));
+        assert!(explanation.fields.is_empty());
+
+        let explanation = explain(Some(&[0, 9]), None);
+        assert_eq!(explanation.error, Some(UnsupportedMessageVersion(9)));
+        assert_eq!(explanation.fields.len(), 1);
+        assert!(explanation.to_string().contains("ERROR: "));
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<RecordSection>();
+        is_thread_safe::<ExplainedField>();
+        is_thread_safe::<RecordExplanation>();
+    }
+}
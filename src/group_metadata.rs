@@ -50,6 +50,7 @@ use crate::utils::{parse_i16, parse_i32, parse_str, parse_vec_bytes};
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(feature = "ts_int", feature = "ts_chrono"), derive(Default))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct GroupMetadata {
     /// **(KEY)** First 2-bytes integers in the original `__consumer_offsets`, identifying this data type.
     ///
@@ -128,8 +129,10 @@ pub struct GroupMetadata {
     #[cfg(feature = "ts_int")]
     pub current_state_timestamp: i64,
     #[cfg(feature = "ts_chrono")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub current_state_timestamp: chrono::DateTime<chrono::Utc>,
     #[cfg(feature = "ts_time")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub current_state_timestamp: time::OffsetDateTime,
 
     /// **(PAYLOAD)** Members that are part of this [`GroupMetadata::group`].
@@ -237,6 +240,7 @@ impl GroupMetadata {
 /// Note that the words "Member" and "Consumer" can be used interchangeably in this context.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct MemberMetadata {
     /// Consumer Group Member identifier.
     pub id: String,
@@ -325,6 +329,7 @@ impl MemberMetadata {
 /// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct ConsumerProtocolSubscription {
     /// Subscription (schema) version.
     ///
@@ -443,6 +448,7 @@ impl TryFrom<Vec<u8>> for ConsumerProtocolSubscription {
 /// Represents a collection of partitions belonging to a specific topic.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct TopicPartitions {
     /// Topic name.
     pub topic: String,
@@ -495,6 +501,7 @@ impl TopicPartitions {
 /// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct ConsumerProtocolAssignment {
     /// Assignment (schema) version.
     ///
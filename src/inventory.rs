@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::group_metadata::{GroupMetadata, MemberMetadata};
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+
+/// Host of a client, parsed from [`MemberMetadata::client_host`].
+///
+/// The Broker records the address of the client as Java `InetAddress::toString` does,
+/// i.e. `/<ip>` or `<hostname>/<ip>`: when the IP can be parsed, the hostname is dropped.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClientHost {
+    /// IP address of the client.
+    Ip(IpAddr),
+
+    /// Host as recorded, when it can't be parsed into an IP address.
+    Other(String),
+}
+
+impl ClientHost {
+    /// Parses a [`MemberMetadata::client_host`].
+    pub fn parse(client_host: &str) -> Self {
+        let address = client_host.rsplit_once('/').map_or(client_host, |(_, address)| address);
+        match address.trim().parse::<IpAddr>() {
+            Ok(ip) => ClientHost::Ip(ip),
+            Err(_) => ClientHost::Other(client_host.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ClientHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientHost::Ip(ip) => write!(f, "{ip}"),
+            ClientHost::Other(host) => write!(f, "{host}"),
+        }
+    }
+}
+
+/// Client library a member uses, inferred by [`ClientInventory`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClientLibrary {
+    /// Apache Kafka Java client.
+    Java,
+
+    /// Kafka Streams (Java).
+    KafkaStreams,
+
+    /// Kafka Connect worker (Java).
+    KafkaConnect,
+
+    /// [librdkafka](https://github.com/confluentinc/librdkafka), and the clients based on it
+    /// (ex. `confluent-kafka-python`, `confluent-kafka-go`).
+    Librdkafka,
+
+    /// [Sarama](https://github.com/IBM/sarama) (Go).
+    Sarama,
+
+    /// [franz-go](https://github.com/twmb/franz-go) (Go).
+    FranzGo,
+
+    /// [kafka-python](https://github.com/dpkp/kafka-python).
+    KafkaPython,
+
+    /// [KafkaJS](https://github.com/tulios/kafkajs) (Node.js).
+    KafkaJs,
+
+    /// Not enough evidence to tell.
+    Unknown,
+}
+
+impl ClientLibrary {
+    /// Infers the client library of a member of the given group.
+    ///
+    /// Libraries are recognised by the default `client.id` they set, so clients
+    /// configured with a custom one are [`ClientLibrary::Unknown`] (unless the group tells).
+    fn infer(group_metadata: &GroupMetadata, member: &MemberMetadata) -> Self {
+        let client_id = member.client_id.as_str();
+        if group_metadata.protocol_type == "connect" {
+            ClientLibrary::KafkaConnect
+        } else if group_metadata.protocol == "stream" || client_id.contains("-StreamThread-") {
+            ClientLibrary::KafkaStreams
+        } else if client_id == "rdkafka" {
+            ClientLibrary::Librdkafka
+        } else if client_id.starts_with("sarama") {
+            ClientLibrary::Sarama
+        } else if client_id == "kgo" {
+            ClientLibrary::FranzGo
+        } else if client_id.starts_with("kafka-python") {
+            ClientLibrary::KafkaPython
+        } else if client_id.starts_with("kafkajs") {
+            ClientLibrary::KafkaJs
+        } else if client_id.starts_with("consumer-") {
+            ClientLibrary::Java
+        } else {
+            ClientLibrary::Unknown
+        }
+    }
+
+    /// Infers the version of the client library of a member, if possible.
+    ///
+    /// For Java clients, the [`ConsumerProtocolSubscription::schema_version`](crate::ConsumerProtocolSubscription::schema_version)
+    /// gives the range of Apache Kafka versions the client belongs to; `kafka-python` instead
+    /// includes its version in the default `client.id`.
+    fn infer_version(&self, member: &MemberMetadata) -> Option<String> {
+        match self {
+            ClientLibrary::Java | ClientLibrary::KafkaStreams => {
+                let versions = match member.subscription.schema_version {
+                    0 => "< 2.4",
+                    // KIP-429: owned partitions.
+                    1 => ">= 2.4, < 3.4",
+                    // KIP-792: generation id.
+                    2 => ">= 3.4, < 3.5",
+                    // KIP-881: rack id.
+                    _ => ">= 3.5",
+                };
+                Some(versions.to_string())
+            },
+            ClientLibrary::KafkaPython => member
+                .client_id
+                .strip_prefix("kafka-python-")
+                .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+                .map(str::to_string),
+            _ => None,
+        }
+    }
+}
+
+/// A member of a group, as listed by [`ClientInventory`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientMember {
+    /// Group.
+    pub group: String,
+
+    /// [`MemberMetadata::id`].
+    pub member_id: String,
+
+    /// [`MemberMetadata::group_instance_id`], if a static member.
+    pub group_instance_id: Option<String>,
+
+    /// [`MemberMetadata::client_id`].
+    pub client_id: String,
+
+    /// Parsed [`MemberMetadata::client_host`].
+    pub host: ClientHost,
+
+    /// Inferred client library.
+    pub library: ClientLibrary,
+
+    /// Inferred version of the client library (ex. `>= 3.5`, `2.0.2`), if possible.
+    pub version: Option<String>,
+
+    /// Topics the member subscribes to, or is assigned partitions of, sorted.
+    pub topics: Vec<String>,
+}
+
+impl ClientMember {
+    fn new(group_metadata: &GroupMetadata, member: &MemberMetadata) -> Self {
+        let library = ClientLibrary::infer(group_metadata, member);
+        let topics: BTreeSet<&String> = member
+            .subscription
+            .subscribed_topics
+            .iter()
+            .chain(member.assignment.assigned_topic_partitions.iter().map(|tp| &tp.topic))
+            .collect();
+
+        Self {
+            group: group_metadata.group.clone(),
+            member_id: member.id.clone(),
+            group_instance_id: Some(member.group_instance_id.clone()).filter(|id| !id.is_empty()),
+            client_id: member.client_id.clone(),
+            host: ClientHost::parse(&member.client_host),
+            library,
+            version: library.infer_version(member),
+            topics: topics.into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Inventory of the clients that are members of groups, across all groups.
+///
+/// Built from the latest [`GroupMetadata`] of each group, it aggregates members by host, `client.id`
+/// and inferred [`ClientLibrary`]: useful to find outdated clients, or unexpected hosts consuming a topic.
+#[derive(Debug, Default, Clone)]
+pub struct ClientInventory {
+    groups: BTreeMap<String, Vec<ClientMember>>,
+}
+
+impl ClientInventory {
+    /// Create a new [`ClientInventory`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update [`Self`] with a [`GroupMetadata`], replacing the members known for its group.
+    ///
+    /// A tombstone forgets the group.
+    pub fn update(&mut self, group_metadata: &GroupMetadata) {
+        if group_metadata.is_tombstone {
+            self.groups.remove(&group_metadata.group);
+            return;
+        }
+
+        let members = group_metadata.members.iter().map(|member| ClientMember::new(group_metadata, member)).collect();
+        self.groups.insert(group_metadata.group.clone(), members);
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::GroupMetadata`] is relevant: other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        if let KonsumerOffsetsData::GroupMetadata(group_metadata) = data {
+            self.update(group_metadata);
+        }
+    }
+
+    /// All members, sorted by group.
+    pub fn members(&self) -> impl Iterator<Item = &ClientMember> {
+        self.groups.values().flatten()
+    }
+
+    /// Members, by [`ClientHost`].
+    pub fn by_host(&self) -> BTreeMap<&ClientHost, Vec<&ClientMember>> {
+        self.aggregate(|member| &member.host)
+    }
+
+    /// Members, by `client.id`.
+    pub fn by_client_id(&self) -> BTreeMap<&str, Vec<&ClientMember>> {
+        self.aggregate(|member| member.client_id.as_str())
+    }
+
+    /// Members, by inferred [`ClientLibrary`] and version.
+    pub fn by_library(&self) -> BTreeMap<(ClientLibrary, Option<&str>), Vec<&ClientMember>> {
+        self.aggregate(|member| (member.library, member.version.as_deref()))
+    }
+
+    /// Hosts of the members that subscribe to, or are assigned partitions of, the given topic.
+    pub fn hosts_consuming(&self, topic: &str) -> BTreeSet<&ClientHost> {
+        self.members().filter(|member| member.topics.iter().any(|t| t == topic)).map(|member| &member.host).collect()
+    }
+
+    fn aggregate<'a, K: Ord>(&'a self, key: impl Fn(&'a ClientMember) -> K) -> BTreeMap<K, Vec<&'a ClientMember>> {
+        let mut aggregated: BTreeMap<K, Vec<&ClientMember>> = BTreeMap::new();
+        for member in self.members() {
+            aggregated.entry(key(member)).or_default().push(member);
+        }
+        aggregated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+    use crate::utils::{group, is_thread_safe, member};
+    use crate::{ConsumerProtocolAssignment, TopicPartitions};
+
+    fn client_member(id: &str, client_id: &str, host: &str, topic: &str, version: i16) -> MemberMetadata {
+        let mut m = member(id, &[topic], &[]);
+        m.client_id = client_id.to_string();
+        m.client_host = host.to_string();
+        m.subscription.schema_version = version;
+        m
+    }
+
+    fn group_with_protocol(name: &str, protocol: &str, members: Vec<MemberMetadata>) -> GroupMetadata {
+        GroupMetadata {
+            protocol: protocol.to_string(),
+            ..group(name, members)
+        }
+    }
+
+    #[test]
+    fn test_parse_host() {
+        assert_eq!(ClientHost::parse("/10.0.0.1"), ClientHost::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+        assert_eq!(ClientHost::parse("worker-1/10.0.0.1"), ClientHost::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+        assert_eq!(ClientHost::parse("/0:0:0:0:0:0:0:1"), ClientHost::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert_eq!(ClientHost::parse("worker-1"), ClientHost::Other("worker-1".to_string()));
+        assert_eq!(ClientHost::parse("/10.0.0.1").to_string(), "10.0.0.1");
+    }
+
+    #[test]
+    fn test_infer_library() {
+        let mut inventory = ClientInventory::new();
+        inventory.update(&group_with_protocol(
+            "g1",
+            "range",
+            vec![
+                client_member("a", "consumer-g1-1", "/10.0.0.1", "t1", 3),
+                client_member("b", "consumer-g1-2", "/10.0.0.2", "t1", 0),
+                client_member("c", "rdkafka", "/10.0.0.3", "t1", 0),
+                client_member("d", "kafka-python-2.0.2", "/10.0.0.4", "t1", 0),
+                client_member("e", "billing", "/10.0.0.5", "t1", 1),
+            ],
+        ));
+        inventory.update(&group_with_protocol(
+            "wordcount",
+            "stream",
+            vec![client_member("f", "wordcount-0a1b-StreamThread-1-consumer", "/10.0.0.6", "t2", 2)],
+        ));
+
+        let libraries: Vec<(ClientLibrary, Option<&str>)> =
+            inventory.members().map(|m| (m.library, m.version.as_deref())).collect();
+        assert_eq!(
+            libraries,
+            vec![
+                (ClientLibrary::Java, Some(">= 3.5")),
+                (ClientLibrary::Java, Some("< 2.4")),
+                (ClientLibrary::Librdkafka, None),
+                (ClientLibrary::KafkaPython, Some("2.0.2")),
+                (ClientLibrary::Unknown, None),
+                (ClientLibrary::KafkaStreams, Some(">= 3.4, < 3.5")),
+            ]
+        );
+        assert_eq!(inventory.by_library()[&(ClientLibrary::Java, Some("< 2.4"))][0].member_id, "b");
+    }
+
+    #[test]
+    fn test_aggregations() {
+        let mut static_member = client_member("c", "consumer-g2-1", "worker-1/10.0.0.1", "payments", 3);
+        static_member.group_instance_id = "worker-1".to_string();
+        static_member.assignment = ConsumerProtocolAssignment {
+            assigned_topic_partitions: vec![TopicPartitions {
+                topic: "audit".to_string(),
+                partitions: vec![0],
+            }],
+            ..Default::default()
+        };
+
+        let mut inventory = ClientInventory::new();
+        inventory.update_from(&KonsumerOffsetsData::GroupMetadata(group_with_protocol(
+            "g1",
+            "range",
+            vec![
+                client_member("a", "orders", "/10.0.0.1", "orders", 3),
+                client_member("b", "orders", "/10.0.0.2", "orders", 3),
+            ],
+        )));
+        inventory.update(&group_with_protocol("g2", "range", vec![static_member]));
+
+        let ip1 = ClientHost::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        let ip2 = ClientHost::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+        let by_host = inventory.by_host();
+        assert_eq!(by_host.keys().copied().collect::<Vec<_>>(), vec![&ip1, &ip2]);
+        assert_eq!(by_host[&ip1].iter().map(|m| m.member_id.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+        assert_eq!(inventory.by_client_id()["orders"].len(), 2);
+        assert_eq!(inventory.hosts_consuming("audit"), BTreeSet::from([&ip1]));
+        assert_eq!(inventory.hosts_consuming("orders"), BTreeSet::from([&ip1, &ip2]));
+
+        let c = inventory.members().find(|m| m.member_id == "c").unwrap();
+        assert_eq!(c.group_instance_id.as_deref(), Some("worker-1"));
+        assert_eq!(c.topics, vec!["audit".to_string(), "payments".to_string()]);
+
+        // Tombstone forgets the group.
+        inventory.update(&GroupMetadata {
+            group: "g2".to_string(),
+            is_tombstone: true,
+            ..Default::default()
+        });
+        assert_eq!(inventory.hosts_consuming("audit"), BTreeSet::new());
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ClientHost>();
+        is_thread_safe::<ClientLibrary>();
+        is_thread_safe::<ClientMember>();
+        is_thread_safe::<ClientInventory>();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::record::KonsumerOffsetsRecord;
+
+/// Version of the [`JsonEnvelope`] format produced by this crate.
+///
+/// It is bumped every time the shape of the envelope, or of the data it wraps, changes
+/// in a way that is not backward compatible.
+pub const JSON_ENVELOPE_VERSION: u16 = 1;
+
+/// Representation of the timestamps found in [`JsonEnvelope::data`].
+///
+/// It mirrors the `ts_*` feature flag the emitting crate was built with, so that consumers
+/// of the JSON know how to interpret timestamp fields (see [`json_schema`](crate::json_schema())).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+pub enum JsonTimestampFormat {
+    /// Integer, milliseconds since Unix Epoch (`ts_int` feature).
+    TsInt,
+
+    /// RFC 3339 string (`ts_chrono` feature).
+    TsChrono,
+
+    /// Array of 9 integers: year, ordinal day, hour, minute, second, nanosecond,
+    /// and UTC offset hours, minutes and seconds (`ts_time` feature).
+    TsTime,
+}
+
+impl JsonTimestampFormat {
+    /// The [`JsonTimestampFormat`] matching the `ts_*` feature in use.
+    pub fn current() -> Self {
+        #[cfg(feature = "ts_int")]
+        return Self::TsInt;
+        #[cfg(feature = "ts_chrono")]
+        return Self::TsChrono;
+        #[cfg(feature = "ts_time")]
+        return Self::TsTime;
+    }
+}
+
+/// Stable, versioned envelope to emit [`KonsumerOffsetsData`] as JSON.
+///
+/// Serializing [`KonsumerOffsetsData`] directly produces JSON whose shape depends on the
+/// `ts_*` feature in use, and carries no information about it. This envelope adds a version
+/// ([`JSON_ENVELOPE_VERSION`]), the timestamp representation and, when known, the coordinates
+/// of the record the data was parsed from:
+///
+/// ```json
+/// {
+///   "envelope_version": 1,
+///   "timestamp_format": "ts_int",
+///   "partition": 46,
+///   "offset": 10,
+///   "timestamp": 1672871009300,
+///   "data": { "OffsetCommit": { ... } }
+/// }
+/// ```
+///
+/// Consumers should check [`Self::envelope_version`] before interpreting the rest.
+/// The JSON Schema of the envelope is available via
+/// [`json_envelope_schema`](crate::json_envelope_schema()) (`json_schema` feature).
+///
+/// **NOTE:** [`Self::timestamp`] is milliseconds since Unix Epoch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+pub struct JsonEnvelope {
+    /// Version of the envelope format: [`JSON_ENVELOPE_VERSION`] when emitted by this crate.
+    pub envelope_version: u16,
+
+    /// Representation of the timestamps found in [`Self::data`].
+    pub timestamp_format: JsonTimestampFormat,
+
+    /// Partition of `__consumer_offsets` the record was read from, if known.
+    pub partition: Option<i32>,
+
+    /// Offset of the record in [`Self::partition`], if known.
+    pub offset: Option<i64>,
+
+    /// Timestamp of the record, if known.
+    pub timestamp: Option<i64>,
+
+    /// Data parsed from the key and payload of the record.
+    pub data: KonsumerOffsetsData,
+}
+
+impl From<KonsumerOffsetsData> for JsonEnvelope {
+    fn from(data: KonsumerOffsetsData) -> Self {
+        Self {
+            envelope_version: JSON_ENVELOPE_VERSION,
+            timestamp_format: JsonTimestampFormat::current(),
+            partition: None,
+            offset: None,
+            timestamp: None,
+            data,
+        }
+    }
+}
+
+impl From<KonsumerOffsetsRecord> for JsonEnvelope {
+    fn from(record: KonsumerOffsetsRecord) -> Self {
+        Self {
+            partition: Some(record.partition),
+            offset: Some(record.offset),
+            timestamp: Some(record.timestamp),
+            ..Self::from(record.data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::is_thread_safe;
+    use crate::{
+        GroupMetadata, JsonEnvelope, JsonTimestampFormat, KonsumerOffsetsData, KonsumerOffsetsRecord,
+        JSON_ENVELOPE_VERSION,
+    };
+
+    #[test]
+    fn test_from_record() {
+        let data = KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+            group: "g1".to_string(),
+            ..Default::default()
+        });
+
+        let envelope = JsonEnvelope::from(data.clone());
+        assert_eq!(envelope.envelope_version, JSON_ENVELOPE_VERSION);
+        assert_eq!(envelope.partition, None);
+
+        let envelope = JsonEnvelope::from(KonsumerOffsetsRecord {
+            partition: 3,
+            offset: 42,
+            timestamp: 1672871009300,
+            data: data.clone(),
+        });
+        assert_eq!(envelope.timestamp_format, JsonTimestampFormat::current());
+        assert_eq!((envelope.partition, envelope.offset, envelope.timestamp), (Some(3), Some(42), Some(1672871009300)));
+        assert_eq!(envelope.data, data);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&envelope).unwrap();
+            assert!(json.starts_with("{\"envelope_version\":1,\"timestamp_format\":\"ts_"));
+            assert_eq!(serde_json::from_str::<JsonEnvelope>(&json).unwrap(), envelope);
+        }
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<JsonEnvelope>();
+        is_thread_safe::<JsonTimestampFormat>();
+    }
+}
@@ -0,0 +1,264 @@
+use schemars::generate::SchemaSettings;
+#[cfg(not(feature = "ts_int"))]
+use schemars::{json_schema, SchemaGenerator};
+use schemars::{JsonSchema, Schema};
+use serde_json::Value;
+
+use crate::json_envelope::{JsonEnvelope, JSON_ENVELOPE_VERSION};
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+
+/// [JSON Schema] (draft 2020-12) of the `serde` representation of [`KonsumerOffsetsData`],
+/// and all the types nested in it.
+///
+/// The representation of timestamps depends on the `ts_*` feature in use
+/// (see [`JsonTimestampFormat`](crate::JsonTimestampFormat)): a copy of the schema
+/// for each of them is published in the `schemas/` directory of this crate.
+///
+/// Descriptions are taken from the documentation of each type, with links to Rust items
+/// replaced by plain names (ex. `GroupMetadata.protocol`), so that they read well in any language.
+///
+/// [JSON Schema]: https://json-schema.org/
+pub fn json_schema() -> Value {
+    root_schema_for::<KonsumerOffsetsData>()
+}
+
+/// [JSON Schema] (draft 2020-12) of [`JsonEnvelope`], including the [`KonsumerOffsetsData`] it wraps.
+///
+/// See [`json_schema`] for details.
+///
+/// [JSON Schema]: https://json-schema.org/
+pub fn json_envelope_schema() -> Value {
+    root_schema_for::<JsonEnvelope>()
+}
+
+fn root_schema_for<T: JsonSchema>() -> Value {
+    let schema = SchemaSettings::default()
+        .with_transform(|schema: &mut Schema| {
+            let title = schema.get("title").and_then(Value::as_str).map(str::to_string);
+            strip_doc_links(schema.as_object_mut(), title.as_deref());
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    serde_json::to_value(schema).expect("JSON Schema is always serializable")
+}
+
+/// Rewrites all the `description`s in `object` and its subschemas with [`plain_description`].
+///
+/// `self_name` is the name of the type being described: it is the title of the root schema,
+/// and the key of each definition in `$defs`.
+fn strip_doc_links(object: Option<&mut serde_json::Map<String, Value>>, self_name: Option<&str>) {
+    for (key, value) in object.into_iter().flatten() {
+        match (key.as_str(), value) {
+            ("description", Value::String(description)) => *description = plain_description(description, self_name),
+            ("$defs", Value::Object(defs)) => {
+                for (name, def) in defs {
+                    strip_doc_links(def.as_object_mut(), Some(name));
+                }
+            },
+            (_, Value::Object(subschema)) => strip_doc_links(Some(subschema), self_name),
+            (_, Value::Array(subschemas)) => {
+                for subschema in subschemas {
+                    strip_doc_links(subschema.as_object_mut(), self_name);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Turns a rustdoc description into plain Markdown.
+///
+/// Links to web pages are kept, while links to Rust items become code spans
+/// of their plain name: `` [`Self::members`] `` becomes `` `GroupMetadata.members` ``,
+/// `` [`json_schema`](crate::json_schema()) `` becomes `` `json_schema` ``, and constants become their value.
+fn plain_description(description: &str, self_name: Option<&str>) -> String {
+    let is_web_link = |target: &str| target.starts_with("http://") || target.starts_with("https://");
+
+    // Reference definitions (ex. `[Kafka]: https://...`) pointing to Rust items are dropped.
+    let mut web_references = Vec::new();
+    let lines: Vec<&str> = description
+        .lines()
+        .filter(|line| match line.strip_prefix('[').and_then(|l| l.split_once("]: ")) {
+            Some((label, target)) if !target.contains(char::is_whitespace) => {
+                if is_web_link(target) {
+                    web_references.push(label);
+                }
+                is_web_link(target)
+            },
+            _ => true,
+        })
+        .collect();
+    let text = lines.join("\n");
+
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("[`") {
+        plain.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find("`]") else {
+            break;
+        };
+        let label = &rest[2..end];
+        let after = &rest[end + 2..];
+
+        // Inline link: `[`label`](target)`, where the target can contain `()` too.
+        let inline_target = after.strip_prefix('(').and_then(|a| {
+            let mut depth = 0;
+            let close = a.find(|c| {
+                depth += match c {
+                    '(' => 1,
+                    ')' => -1,
+                    _ => 0,
+                };
+                depth < 0
+            })?;
+            Some((&a[..close], &a[close + 1..]))
+        });
+        match inline_target {
+            Some((target, after_link)) if !is_web_link(target) => {
+                plain.push_str(&format!("`{}`", plain_name(label, self_name)));
+                rest = after_link;
+            },
+            Some(_) => {
+                plain.push_str(&rest[..end + 2]);
+                rest = after;
+            },
+            None if web_references.contains(&&rest[1..end + 1]) => {
+                plain.push_str(&rest[..end + 2]);
+                rest = after;
+            },
+            None => {
+                plain.push_str(&format!("`{}`", plain_name(label, self_name)));
+                rest = after;
+            },
+        }
+    }
+    plain.push_str(rest);
+
+    plain
+}
+
+/// Plain name of the Rust item at `path` (ex. `Self::members` is `GroupMetadata.members`).
+fn plain_name(path: &str, self_name: Option<&str>) -> String {
+    if path == "JSON_ENVELOPE_VERSION" {
+        return JSON_ENVELOPE_VERSION.to_string();
+    }
+
+    let path = path.strip_prefix("crate::").unwrap_or(path);
+    let path = path.strip_suffix("()").unwrap_or(path);
+    let path = match (path.strip_prefix("Self"), self_name) {
+        (Some(rest), Some(self_name)) => format!("{self_name}{rest}"),
+        (Some(rest), None) => rest.trim_start_matches("::").to_string(),
+        (None, _) => path.to_string(),
+    };
+
+    path.replace("::", ".")
+}
+
+/// Schema of timestamp fields, whose type is controlled by the `ts_*` feature flags.
+#[cfg(feature = "ts_chrono")]
+pub(crate) fn timestamp_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "string",
+        "format": "date-time",
+        "description": "RFC 3339 date and time, in UTC"
+    })
+}
+
+/// Schema of timestamp fields, whose type is controlled by the `ts_*` feature flags.
+#[cfg(feature = "ts_time")]
+pub(crate) fn timestamp_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "array",
+        "description": "Year, ordinal day, hour, minute, second, nanosecond, and UTC offset hours, minutes and seconds",
+        "items": { "type": "integer" },
+        "minItems": 9,
+        "maxItems": 9
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::plain_description;
+    use crate::utils::read_fixtures;
+    use crate::{json_envelope_schema, json_schema, JsonEnvelope, KonsumerOffsetsData, KonsumerOffsetsRecord};
+
+    #[cfg(feature = "ts_int")]
+    const TS_FEATURE: &str = "ts_int";
+    #[cfg(feature = "ts_chrono")]
+    const TS_FEATURE: &str = "ts_chrono";
+    #[cfg(feature = "ts_time")]
+    const TS_FEATURE: &str = "ts_time";
+
+    /// Compares the generated schema with the published one.
+    ///
+    /// Set `UPDATE_SCHEMAS=1` to (re)generate the published schema instead.
+    fn assert_published(name: &str, schema: &serde_json::Value) {
+        let path = format!("schemas/{name}.{TS_FEATURE}.schema.json");
+        let generated = serde_json::to_string_pretty(schema).unwrap() + "\n";
+        if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+            fs::write(&path, &generated).unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            generated,
+            "{path} is outdated: run tests with UPDATE_SCHEMAS=1"
+        );
+    }
+
+    #[test]
+    fn test_published_schemas() {
+        assert_published("konsumer_offsets_data", &json_schema());
+        assert_published("json_envelope", &json_envelope_schema());
+    }
+
+    #[test]
+    fn test_plain_description() {
+        let description = "Members of [`Self`]: see [`Self::members`] and [`json_schema`](crate::json_schema()).\n\
+            Version [`JSON_ENVELOPE_VERSION`] of [`OffsetCommit`], by the [Group Coordinator].\n\
+            \n\
+            [Group Coordinator]: https://kafka.apache.org/\n\
+            [`OffsetCommit`]: crate::OffsetCommit";
+        assert_eq!(
+            plain_description(description, Some("GroupMetadata")),
+            "Members of `GroupMetadata`: see `GroupMetadata.members` and `json_schema`.\n\
+            Version `1` of `OffsetCommit`, by the [Group Coordinator].\n\
+            \n\
+            [Group Coordinator]: https://kafka.apache.org/"
+        );
+    }
+
+    #[test]
+    fn test_serde_output_matches_schema() {
+        let data_validator = jsonschema::validator_for(&json_schema()).unwrap();
+        let envelope_validator = jsonschema::validator_for(&json_envelope_schema()).unwrap();
+
+        for (i, data) in read_fixtures().into_iter().enumerate() {
+            let json = serde_json::to_value(&data).unwrap();
+            assert!(data_validator.is_valid(&json), "{json}");
+
+            let mut tombstone = data.clone();
+            match &mut tombstone {
+                KonsumerOffsetsData::OffsetCommit(oc) => oc.is_tombstone = true,
+                KonsumerOffsetsData::GroupMetadata(gm) => gm.is_tombstone = true,
+            }
+            assert!(data_validator.is_valid(&serde_json::to_value(&tombstone).unwrap()));
+
+            let envelope = JsonEnvelope::from(KonsumerOffsetsRecord {
+                partition: 1,
+                offset: i as i64,
+                timestamp: 1672871009300,
+                data,
+            });
+            assert!(envelope_validator.is_valid(&serde_json::to_value(&envelope).unwrap()));
+            assert!(envelope_validator.is_valid(&serde_json::to_value(JsonEnvelope::from(tombstone)).unwrap()));
+        }
+
+        assert!(!data_validator.is_valid(&serde_json::json!({ "OffsetCommit": { "group": 1 } })));
+        assert!(!envelope_validator.is_valid(&serde_json::json!({ "envelope_version": 1 })));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::coordinator::java_string_hash_code;
+use crate::group_metadata::{GroupMetadata, MemberMetadata};
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// Initial capacity of a Scala 2.13 `mutable.HashMap`.
+const SCALA_HASHMAP_INITIAL_CAPACITY: usize = 16;
+
+impl OffsetCommit {
+    /// Formats [`Self`] exactly like Kafka `kafka.coordinator.group.GroupMetadataManager.OffsetsMessageFormatter` does.
+    ///
+    /// This is the formatter used with `kafka-console-consumer.sh --formatter
+    /// kafka.coordinator.group.OffsetsMessageFormatter`, and produces lines like:
+    ///
+    /// ```text
+    /// [group,topic,0]::OffsetAndMetadata(offset=42, leaderEpoch=Optional[3], metadata=, commitTimestamp=1672871009232, expireTimestamp=None)
+    /// ```
+    ///
+    /// or, for tombstones:
+    ///
+    /// ```text
+    /// [group,topic,0]::NULL
+    /// ```
+    ///
+    /// The returned [`String`] includes the trailing new line.
+    pub fn to_offsets_message_format(&self) -> String {
+        let value = if self.is_tombstone {
+            "NULL".to_string()
+        } else {
+            let leader_epoch = if self.leader_epoch == -1 {
+                "Optional.empty".to_string()
+            } else {
+                format!("Optional[{}]", self.leader_epoch)
+            };
+
+            let expire_timestamp = timestamp_millis(&self.expire_timestamp);
+            let expire_timestamp = if self.schema_version == 1 && expire_timestamp != -1 {
+                format!("Some({expire_timestamp})")
+            } else {
+                "None".to_string()
+            };
+
+            format!(
+                "OffsetAndMetadata(offset={}, leaderEpoch={}, metadata={}, commitTimestamp={}, expireTimestamp={})",
+                self.offset,
+                leader_epoch,
+                self.metadata,
+                timestamp_millis(&self.commit_timestamp),
+                expire_timestamp
+            )
+        };
+
+        format!("[{},{},{}]::{}\n", self.group, self.topic, self.partition, value)
+    }
+}
+
+impl GroupMetadata {
+    /// Formats [`Self`] exactly like Kafka `kafka.coordinator.group.GroupMetadataManager.GroupMetadataMessageFormatter` does.
+    ///
+    /// This is the formatter used with `kafka-console-consumer.sh --formatter
+    /// kafka.coordinator.group.GroupMetadataMessageFormatter`, and produces lines like:
+    ///
+    /// ```text
+    /// group::GroupMetadata(groupId=group, generation=7, protocolType=Some(consumer), currentState=Stable, members=HashMap(...))
+    /// ```
+    ///
+    /// or, for tombstones:
+    ///
+    /// ```text
+    /// group::NULL
+    /// ```
+    ///
+    /// The Broker renders the `members` of the group from a Scala `mutable.HashMap`: members are listed
+    /// in the iteration order of that data structure, as implemented by Scala 2.13 (the default for Kafka 3).
+    ///
+    /// **NOTE:** This crate parses a `null` [`MemberMetadata::group_instance_id`] as an empty string:
+    /// both are rendered as `None`.
+    ///
+    /// The returned [`String`] includes the trailing new line.
+    pub fn to_group_metadata_message_format(&self) -> String {
+        let value = if self.is_tombstone {
+            "NULL".to_string()
+        } else {
+            let protocol_type = if self.protocol_type.is_empty() {
+                "None".to_string()
+            } else {
+                format!("Some({})", self.protocol_type)
+            };
+
+            let current_state = if self.members.is_empty() {
+                "Empty"
+            } else {
+                "Stable"
+            };
+
+            let members = scala_hashmap_order(&self.members)
+                .into_iter()
+                .map(|m| format!("{} -> {}", m.id, self.format_member(m)))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            format!(
+                "GroupMetadata(groupId={}, generation={}, protocolType={}, currentState={}, members=HashMap({}))",
+                self.group, self.generation, protocol_type, current_state, members
+            )
+        };
+
+        format!("{}::{}\n", self.group, value)
+    }
+
+    /// Formats a [`MemberMetadata`] like `kafka.coordinator.group.MemberMetadata#toString` does,
+    /// for a member loaded from `__consumer_offsets`.
+    fn format_member(&self, member: &MemberMetadata) -> String {
+        let group_instance_id = if member.group_instance_id.is_empty() {
+            "None".to_string()
+        } else {
+            format!("Some({})", member.group_instance_id)
+        };
+
+        // When loading schema version 0, the Broker uses the session timeout as rebalance timeout.
+        let rebalance_timeout = if self.schema_version == 0 {
+            member.session_timeout
+        } else {
+            member.rebalance_timeout
+        };
+
+        format!(
+            "MemberMetadata(memberId={}, groupInstanceId={}, clientId={}, clientHost={}, sessionTimeoutMs={}, rebalanceTimeoutMs={}, supportedProtocols=List({}))",
+            member.id,
+            group_instance_id,
+            member.client_id,
+            member.client_host,
+            member.session_timeout,
+            rebalance_timeout,
+            self.protocol
+        )
+    }
+}
+
+/// Orders members the way a Scala 2.13 `mutable.HashMap[String, _]`, keyed by [`MemberMetadata::id`], iterates them.
+///
+/// The map is a table of buckets, that doubles in size when it reaches 75% load. Each key is hashed
+/// with Java `String.hashCode`, that is then _improved_ as `h ^ (h >>> 16)`: the bucket index is
+/// the improved hash masked by the table size, and entries within a bucket are sorted by improved hash.
+/// Iteration goes bucket by bucket.
+fn scala_hashmap_order(members: &[MemberMetadata]) -> Vec<&MemberMetadata> {
+    // Later members with the same id replace earlier ones, keeping their position.
+    let mut unique: Vec<&MemberMetadata> = Vec::with_capacity(members.len());
+    for member in members {
+        match unique.iter_mut().find(|m| m.id == member.id) {
+            Some(existing) => *existing = member,
+            None => unique.push(member),
+        }
+    }
+
+    // The table grows whenever inserting would reach the threshold of 75% load.
+    let mut table_size = SCALA_HASHMAP_INITIAL_CAPACITY;
+    while unique.len() as f64 >= table_size as f64 * 0.75 {
+        table_size *= 2;
+    }
+
+    let improved_hash = |id: &str| {
+        let h = java_string_hash_code(id);
+        h ^ ((h as u32) >> 16) as i32
+    };
+
+    // Stable sort: entries with identical hash stay in insertion order.
+    unique.sort_by_key(|m| {
+        let hash = improved_hash(&m.id);
+        (hash as usize & (table_size - 1), hash)
+    });
+
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{read_fixture, timestamp_from_millis};
+    use crate::{GroupMetadata, KonsumerOffsetsData, MemberMetadata, OffsetCommit};
+
+    #[test]
+    fn test_offsets_message_format() {
+        let KonsumerOffsetsData::OffsetCommit(mut offset_commit) = read_fixture("offset_commit", 1) else {
+            panic!("Returned wrong enum value!");
+        };
+        assert_eq!(
+            offset_commit.to_offsets_message_format(),
+            "[ivan-experimental-consumer,__consumer_offsets,46]::OffsetAndMetadata(offset=97507, \
+            leaderEpoch=Optional.empty, metadata=, commitTimestamp=1672871009232, expireTimestamp=None)\n"
+        );
+
+        offset_commit.schema_version = 1;
+        offset_commit.leader_epoch = 3;
+        offset_commit.expire_timestamp = timestamp_from_millis(1672871009999);
+        assert!(offset_commit.to_offsets_message_format().ends_with(
+            "leaderEpoch=Optional[3], metadata=, commitTimestamp=1672871009232, expireTimestamp=Some(1672871009999))\n"
+        ));
+
+        let tombstone = OffsetCommit {
+            group: "g".to_string(),
+            topic: "t".to_string(),
+            partition: 1,
+            is_tombstone: true,
+            ..Default::default()
+        };
+        assert_eq!(tombstone.to_offsets_message_format(), "[g,t,1]::NULL\n");
+    }
+
+    #[test]
+    fn test_group_metadata_message_format() {
+        let KonsumerOffsetsData::GroupMetadata(mut group_metadata) = read_fixture("group_metadata", 4) else {
+            panic!("Returned wrong enum value!");
+        };
+
+        let member = |id: &str| {
+            format!(
+                "{id} -> MemberMetadata(memberId={id}, groupInstanceId=None, clientId=rdkafka, clientHost=/172.18.0.1, \
+                sessionTimeoutMs=45000, rebalanceTimeoutMs=300000, supportedProtocols=List(range))"
+            )
+        };
+        let expected = format!(
+            "kafkesc-devcluster-group-id::GroupMetadata(groupId=kafkesc-devcluster-group-id, generation=6, \
+            protocolType=Some(consumer), currentState=Stable, members=HashMap({}, {}))\n",
+            member("rdkafka-9f4fc1b0-1d7d-4471-90e8-e0f64f3c9d9f"),
+            member("rdkafka-6fdc40ae-296b-4ce4-8a8b-6b3fa4c9a932"),
+        );
+        assert_eq!(group_metadata.to_group_metadata_message_format(), expected);
+
+        // Order is determined by the Scala HashMap, not by the order of the members in the record.
+        group_metadata.members.reverse();
+        assert_eq!(group_metadata.to_group_metadata_message_format(), expected);
+    }
+
+    #[test]
+    fn test_group_metadata_message_format_empty_and_tombstone() {
+        let KonsumerOffsetsData::GroupMetadata(group_metadata) = read_fixture("group_metadata", 5) else {
+            panic!("Returned wrong enum value!");
+        };
+        assert_eq!(
+            group_metadata.to_group_metadata_message_format(),
+            "kafkesc-devcluster-group-id::GroupMetadata(groupId=kafkesc-devcluster-group-id, generation=8, \
+            protocolType=Some(consumer), currentState=Empty, members=HashMap())\n"
+        );
+
+        let group_metadata = GroupMetadata {
+            group: "g".to_string(),
+            is_tombstone: true,
+            ..Default::default()
+        };
+        assert_eq!(group_metadata.to_group_metadata_message_format(), "g::NULL\n");
+    }
+
+    #[test]
+    fn test_group_metadata_message_format_schema_v0() {
+        let group_metadata = GroupMetadata {
+            group: "g".to_string(),
+            schema_version: 0,
+            protocol: "range".to_string(),
+            members: vec![MemberMetadata {
+                id: "m".to_string(),
+                group_instance_id: "i".to_string(),
+                session_timeout: 10000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            group_metadata.to_group_metadata_message_format(),
+            "g::GroupMetadata(groupId=g, generation=0, protocolType=None, currentState=Stable, members=HashMap(\
+            m -> MemberMetadata(memberId=m, groupInstanceId=Some(i), clientId=, clientHost=, sessionTimeoutMs=10000, \
+            rebalanceTimeoutMs=10000, supportedProtocols=List(range))))\n"
+        );
+    }
+}
@@ -15,6 +15,7 @@ const MSG_V2_GROUP_METADATA: i16 = 2;
 /// topics, but it doesn't so... here we are.
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub enum KonsumerOffsetsData {
     /// Variant that wraps an [`OffsetCommit`] struct instance.
     OffsetCommit(OffsetCommit),
@@ -41,10 +42,10 @@ impl KonsumerOffsetsData {
     /// # Arguments
     ///
     /// * `key` - An [`Option`] of `&[u8]`: when set, it means the source Kafka message has a key.
-    ///     If `key` is `None`, this function will return an error: it's likely that the message
-    ///     it's not actually from the `__consumer_offsets` topic.
+    ///   If `key` is `None`, this function will return an error: it's likely that the message
+    ///   it's not actually from the `__consumer_offsets` topic.
     /// * `payload` - An [`Option`] of `&[u8]`: when set, it means the source Kafka message has
-    ///     a payload. If `payload` is `None`, the source Kafka message is a tombstone.
+    ///   a payload. If `payload` is `None`, the source Kafka message is a tombstone.
     pub fn try_from_bytes(
         key: Option<&[u8]>,
         payload: Option<&[u8]>,
@@ -95,6 +96,25 @@ impl KonsumerOffsetsData {
     ) -> Result<KonsumerOffsetsData, KonsumerOffsetsError> {
         Self::try_from_bytes(key.as_deref(), payload.as_deref())
     }
+
+    /// Group this data refers to.
+    ///
+    /// Both variants are keyed by group: this is also what determines which partition
+    /// of `__consumer_offsets` the data is stored in.
+    pub fn group(&self) -> &str {
+        match self {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => &offset_commit.group,
+            KonsumerOffsetsData::GroupMetadata(group_metadata) => &group_metadata.group,
+        }
+    }
+
+    /// Is this from a _tombstone_ message?
+    pub fn is_tombstone(&self) -> bool {
+        match self {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => offset_commit.is_tombstone,
+            KonsumerOffsetsData::GroupMetadata(group_metadata) => group_metadata.is_tombstone,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -108,11 +128,11 @@ mod tests {
     use crate::utils::is_thread_safe;
 
     #[rstest]
-    #[case(01)]
-    #[case(02)]
-    #[case(03)]
-    #[case(04)]
-    #[case(05)]
+    #[case(1)]
+    #[case(2)]
+    #[case(3)]
+    #[case(4)]
+    #[case(5)]
     fn from_offset_commit(#[case] fixture_id: u16) {
         #[cfg(feature = "ts_int")]
         let (key_bytes, payload_bytes, fmt_string) = read_offset_commit_fixture(fixture_id, "ts_int");
@@ -134,11 +154,11 @@ mod tests {
     }
 
     #[rstest]
-    #[case(01)]
-    #[case(02)]
-    #[case(03)]
-    #[case(04)]
-    #[case(05)]
+    #[case(1)]
+    #[case(2)]
+    #[case(3)]
+    #[case(4)]
+    #[case(5)]
     fn from_group_metadata(#[case] fixture_id: u16) {
         #[cfg(feature = "ts_int")]
         let (key_bytes, payload_bytes, fmt_string) = read_group_metadata_fixture(fixture_id, "ts_int");
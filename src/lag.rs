@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+
+/// Source of the _log offsets_ of topic partitions, used to compute consumer lag.
+///
+/// [`OffsetCommit`] tells us where a consumer group is at, but to compute how far behind it is,
+/// we also need to know where each partition ends (the _log-end offset_, a.k.a. _high watermark_)
+/// and, optionally, where it begins (the _log-start offset_, a.k.a. _low watermark_).
+///
+/// This information is **not** in `__consumer_offsets`: it has to be fetched from the Kafka
+/// cluster (ex. via a `ListOffsetsRequest`), and is supplied by the caller by implementing
+/// this trait.
+///
+/// Implementations are provided for:
+///
+/// * `HashMap<(String, i32), i64>`: `(topic, partition)` to log-end offset
+/// * `HashMap<(String, i32), Range<i64>>`: `(topic, partition)` to `log_start..log_end` offsets
+pub trait LogOffsets {
+    /// Log-end offset (i.e. offset of the _next_ record that will be produced) of a topic partition.
+    ///
+    /// Returns `None` if not known.
+    fn log_end_offset(&self, topic: &str, partition: i32) -> Option<i64>;
+
+    /// Log-start offset (i.e. offset of the _earliest_ record still retained) of a topic partition.
+    ///
+    /// Returns `None` if not known: this is the default.
+    fn log_start_offset(&self, _topic: &str, _partition: i32) -> Option<i64> {
+        None
+    }
+
+    /// All the partitions known for the given topic.
+    ///
+    /// This is used to report partitions for which a group has committed no offset at all.
+    /// Returns an empty [`Vec`] if not known: this is the default.
+    fn partitions(&self, _topic: &str) -> Vec<i32> {
+        Vec::new()
+    }
+}
+
+impl LogOffsets for HashMap<(String, i32), i64> {
+    fn log_end_offset(&self, topic: &str, partition: i32) -> Option<i64> {
+        self.get(&(topic.to_string(), partition)).copied()
+    }
+
+    fn partitions(&self, topic: &str) -> Vec<i32> {
+        known_partitions(self.keys(), topic)
+    }
+}
+
+impl LogOffsets for HashMap<(String, i32), Range<i64>> {
+    fn log_end_offset(&self, topic: &str, partition: i32) -> Option<i64> {
+        self.get(&(topic.to_string(), partition)).map(|r| r.end)
+    }
+
+    fn log_start_offset(&self, topic: &str, partition: i32) -> Option<i64> {
+        self.get(&(topic.to_string(), partition)).map(|r| r.start)
+    }
+
+    fn partitions(&self, topic: &str) -> Vec<i32> {
+        known_partitions(self.keys(), topic)
+    }
+}
+
+fn known_partitions<'a>(keys: impl Iterator<Item = &'a (String, i32)>, topic: &str) -> Vec<i32> {
+    let mut partitions: Vec<i32> = keys.filter(|(t, _)| t == topic).map(|(_, p)| *p).collect();
+    partitions.sort_unstable();
+    partitions
+}
+
+/// Latest committed offsets, for every `(group, topic, partition)` seen so far.
+///
+/// This is built by feeding it the [`OffsetCommit`]s read out of `__consumer_offsets`, in the
+/// order they were read: the latest commit for a given key replaces the previous one, and a
+/// _tombstone_ removes it, in the same way the [Group Coordinator] does when it loads the topic.
+///
+/// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommittedOffsets {
+    offsets: BTreeMap<String, BTreeMap<String, BTreeMap<i32, i64>>>,
+}
+
+impl CommittedOffsets {
+    /// Create an empty [`Self`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    ///
+    /// If the [`OffsetCommit`] is a tombstone, the committed offset for its
+    /// `(group, topic, partition)` is removed.
+    pub fn update(&mut self, offset_commit: &OffsetCommit) {
+        if offset_commit.is_tombstone {
+            if let Some(topics) = self.offsets.get_mut(&offset_commit.group) {
+                if let Some(partitions) = topics.get_mut(&offset_commit.topic) {
+                    partitions.remove(&offset_commit.partition);
+                    if partitions.is_empty() {
+                        topics.remove(&offset_commit.topic);
+                    }
+                }
+                if topics.is_empty() {
+                    self.offsets.remove(&offset_commit.group);
+                }
+            }
+        } else {
+            self.offsets
+                .entry(offset_commit.group.clone())
+                .or_default()
+                .entry(offset_commit.topic.clone())
+                .or_default()
+                .insert(offset_commit.partition, offset_commit.offset);
+        }
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::OffsetCommit`] carries committed offsets:
+    /// other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        if let KonsumerOffsetsData::OffsetCommit(offset_commit) = data {
+            self.update(offset_commit);
+        }
+    }
+
+    /// Committed offset of a `(group, topic, partition)`, if any.
+    pub fn get(&self, group: &str, topic: &str, partition: i32) -> Option<i64> {
+        self.offsets.get(group)?.get(topic)?.get(&partition).copied()
+    }
+
+    /// Groups that have at least 1 committed offset.
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.offsets.keys().map(String::as_str)
+    }
+
+    /// Topics for which the given group has at least 1 committed offset.
+    pub fn topics(&self, group: &str) -> impl Iterator<Item = &str> {
+        self.offsets.get(group).into_iter().flat_map(|topics| topics.keys().map(String::as_str))
+    }
+
+    /// Computes the [`GroupLag`] of the given group.
+    ///
+    /// Returns `None` if the group has no committed offset.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - Group to compute the lag of.
+    /// * `log_offsets` - Caller-supplied [`LogOffsets`] of the topic partitions the group consumes.
+    pub fn group_lag(&self, group: &str, log_offsets: &impl LogOffsets) -> Option<GroupLag> {
+        let topics = self.offsets.get(group)?;
+
+        let topics = topics
+            .iter()
+            .map(|(topic, committed)| {
+                let mut partitions: Vec<i32> = log_offsets.partitions(topic);
+                partitions.extend(committed.keys());
+                partitions.sort_unstable();
+                partitions.dedup();
+
+                TopicLag::new(
+                    topic,
+                    partitions
+                        .into_iter()
+                        .map(|p| PartitionLag::new(topic, p, committed.get(&p).copied(), log_offsets))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        Some(GroupLag::new(group, topics))
+    }
+
+    /// Computes the [`GroupLag`] of all the groups in [`Self`].
+    ///
+    /// # Arguments
+    ///
+    /// * `log_offsets` - Caller-supplied [`LogOffsets`] of the topic partitions the groups consume.
+    pub fn lag(&self, log_offsets: &impl LogOffsets) -> Vec<GroupLag> {
+        self.offsets.keys().filter_map(|group| self.group_lag(group, log_offsets)).collect()
+    }
+}
+
+/// Status of a [`PartitionLag`], describing how the committed offset relates to the partition log.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PartitionLagStatus {
+    /// Committed offset is within `[log_start, log_end]`: lag is `log_end - committed`.
+    Ok,
+
+    /// The group has not committed any offset for this partition.
+    ///
+    /// Where the consumer will start from depends on its `auto.offset.reset` configuration,
+    /// so lag can't be determined.
+    NoCommit,
+
+    /// The log-end offset of this partition is not known.
+    UnknownLogEndOffset,
+
+    /// Committed offset is past the log-end offset.
+    ///
+    /// This can happen, for example, if the log was truncated after the commit, or if the
+    /// log offsets were sampled _before_ the commit. Lag is reported as `0`.
+    AheadOfLogEnd,
+
+    /// Committed offset is below the log-start offset (i.e. out of range).
+    ///
+    /// The records between the committed offset and the log-start offset were deleted by
+    /// retention before the group could consume them. Lag is reported as
+    /// `log_end - log_start`: the records that are still there to consume.
+    BelowLogStart,
+}
+
+/// Lag of a consumer group on a single topic partition.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionLag {
+    /// Topic the partition belongs to.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Offset committed by the group, if any.
+    pub committed_offset: Option<i64>,
+
+    /// Log-start offset of the partition, if known.
+    pub log_start_offset: Option<i64>,
+
+    /// Log-end offset of the partition, if known.
+    pub log_end_offset: Option<i64>,
+
+    /// Number of records the group has still to consume, if it can be determined.
+    ///
+    /// How this is computed depends on [`Self::status`].
+    pub lag: Option<i64>,
+
+    /// How [`Self::committed_offset`] relates to the partition log.
+    pub status: PartitionLagStatus,
+}
+
+impl PartitionLag {
+    fn new(topic: &str, partition: i32, committed_offset: Option<i64>, log_offsets: &impl LogOffsets) -> Self {
+        let log_start_offset = log_offsets.log_start_offset(topic, partition);
+        let log_end_offset = log_offsets.log_end_offset(topic, partition);
+
+        let (lag, status) = match (committed_offset, log_end_offset) {
+            (None, _) => (None, PartitionLagStatus::NoCommit),
+            (Some(_), None) => (None, PartitionLagStatus::UnknownLogEndOffset),
+            (Some(committed), Some(end)) if committed > end => (Some(0), PartitionLagStatus::AheadOfLogEnd),
+            (Some(committed), Some(end)) => match log_start_offset {
+                Some(start) if committed < start => (Some(end - start), PartitionLagStatus::BelowLogStart),
+                _ => (Some(end - committed), PartitionLagStatus::Ok),
+            },
+        };
+
+        Self {
+            topic: topic.to_string(),
+            partition,
+            committed_offset,
+            log_start_offset,
+            log_end_offset,
+            lag,
+            status,
+        }
+    }
+}
+
+/// Lag of a consumer group on a topic.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopicLag {
+    /// Topic.
+    pub topic: String,
+
+    /// Sum of the [`PartitionLag::lag`] of [`Self::partitions`] (where it could be determined).
+    pub total_lag: i64,
+
+    /// Largest [`PartitionLag::lag`] of [`Self::partitions`] (where it could be determined).
+    pub max_lag: i64,
+
+    /// Lag of each partition of the topic, sorted by partition.
+    pub partitions: Vec<PartitionLag>,
+}
+
+impl TopicLag {
+    fn new(topic: &str, partitions: Vec<PartitionLag>) -> Self {
+        let lags = partitions.iter().filter_map(|p| p.lag);
+
+        Self {
+            topic: topic.to_string(),
+            total_lag: lags.clone().sum(),
+            max_lag: lags.max().unwrap_or_default(),
+            partitions,
+        }
+    }
+}
+
+/// Lag of a consumer group.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupLag {
+    /// Group.
+    pub group: String,
+
+    /// Sum of the [`TopicLag::total_lag`] of [`Self::topics`].
+    pub total_lag: i64,
+
+    /// Largest [`TopicLag::max_lag`] of [`Self::topics`].
+    pub max_lag: i64,
+
+    /// Lag of each topic the group has committed offsets for, sorted by topic.
+    pub topics: Vec<TopicLag>,
+}
+
+impl GroupLag {
+    fn new(group: &str, topics: Vec<TopicLag>) -> Self {
+        Self {
+            group: group.to_string(),
+            total_lag: topics.iter().map(|t| t.total_lag).sum(),
+            max_lag: topics.iter().map(|t| t.max_lag).max().unwrap_or_default(),
+            topics,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::utils::{commit, is_thread_safe};
+
+    #[test]
+    fn test_lag_statuses() {
+        let mut committed = CommittedOffsets::new();
+        committed.update(&commit("g", "t", 0, 90, 0));
+        committed.update(&commit("g", "t", 1, 120, 0));
+        committed.update(&commit("g", "t", 2, 5, 0));
+        committed.update(&commit("g", "t", 4, 10, 0));
+
+        let log_offsets: HashMap<(String, i32), Range<i64>> = HashMap::from([
+            (("t".to_string(), 0), 0..100),
+            (("t".to_string(), 1), 0..100),
+            (("t".to_string(), 2), 10..100),
+            (("t".to_string(), 3), 0..100),
+        ]);
+
+        let lag = committed.group_lag("g", &log_offsets).unwrap();
+        assert_eq!(lag.topics.len(), 1);
+
+        let statuses: Vec<(i32, Option<i64>, PartitionLagStatus)> =
+            lag.topics[0].partitions.iter().map(|p| (p.partition, p.lag, p.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (0, Some(10), PartitionLagStatus::Ok),
+                (1, Some(0), PartitionLagStatus::AheadOfLogEnd),
+                (2, Some(90), PartitionLagStatus::BelowLogStart),
+                (3, None, PartitionLagStatus::NoCommit),
+                (4, None, PartitionLagStatus::UnknownLogEndOffset),
+            ]
+        );
+        assert_eq!(lag.topics[0].total_lag, 100);
+        assert_eq!(lag.topics[0].max_lag, 90);
+        assert_eq!(lag.total_lag, 100);
+    }
+
+    #[test]
+    fn test_tombstone_removes_committed_offset() {
+        let mut committed = CommittedOffsets::new();
+        committed.update(&commit("g", "t", 0, 90, 0));
+        assert_eq!(committed.get("g", "t", 0), Some(90));
+
+        let mut tombstone = commit("g", "t", 0, 0, 0);
+        tombstone.is_tombstone = true;
+        committed.update(&tombstone);
+        assert_eq!(committed.get("g", "t", 0), None);
+        assert_eq!(committed.groups().count(), 0);
+
+        let log_end_offsets: HashMap<(String, i32), i64> = HashMap::from([(("t".to_string(), 0), 100)]);
+        assert!(committed.group_lag("g", &log_end_offsets).is_none());
+        assert!(committed.lag(&log_end_offsets).is_empty());
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<CommittedOffsets>();
+        is_thread_safe::<PartitionLagStatus>();
+        is_thread_safe::<PartitionLag>();
+        is_thread_safe::<TopicLag>();
+        is_thread_safe::<GroupLag>();
+    }
+}
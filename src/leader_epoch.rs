@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+
+use crate::assignor::group_by_topic;
+use crate::group_metadata::TopicPartitions;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// An [`OffsetCommit`] carrying a leader epoch, reduced to what [`LeaderEpochAnalyzer`] needs.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EpochCommit {
+    /// [`OffsetCommit::group`].
+    pub group: String,
+
+    /// [`OffsetCommit::offset`].
+    pub offset: i64,
+
+    /// [`OffsetCommit::leader_epoch`].
+    pub leader_epoch: i32,
+
+    /// [`OffsetCommit::commit_timestamp`], in milliseconds since Unix Epoch.
+    pub commit_timestamp: i64,
+}
+
+/// Kind of [`LeaderEpochAnomaly`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LeaderEpochAnomalyKind {
+    /// A group committed a lower leader epoch than before, without moving its offset backwards.
+    ///
+    /// The records it consumed since the previous commit belong to an older epoch than those it had
+    /// consumed before: it was reading from a log that diverged, like the one of an unclean leader.
+    EpochRegression,
+
+    /// A group committed a higher leader epoch than before, but a lower offset.
+    ///
+    /// This is what a consumer does when it detects log truncation ([KIP-320]): it resets its position
+    /// to where the log of the new leader diverged. The records after that offset may have been lost.
+    ///
+    /// [KIP-320]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-320%3A+Allow+fetchers+to+detect+and+handle+log+truncation
+    Truncation,
+
+    /// Commits of different groups that can't come from the same log.
+    ///
+    /// Leader epochs only grow along a log, but a group committed a lower epoch at a higher offset
+    /// (or vice versa) than another group: one of them consumed records that were later truncated,
+    /// or that an unclean leader never had.
+    DivergentLog,
+}
+
+/// A pair of commits, for the same topic partition, whose leader epochs suggest unclean leader
+/// election or log truncation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LeaderEpochAnomaly {
+    /// Kind of anomaly.
+    pub kind: LeaderEpochAnomalyKind,
+
+    /// Topic.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Earlier commit the anomaly was detected against.
+    ///
+    /// For [`LeaderEpochAnomalyKind::DivergentLog`] this belongs to another group.
+    pub previous: EpochCommit,
+
+    /// Commit that revealed the anomaly.
+    pub current: EpochCommit,
+}
+
+/// Commits with the lowest and highest offset seen for a leader epoch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct EpochRange {
+    lowest: EpochCommit,
+    highest: EpochCommit,
+}
+
+/// Detects unclean leader elections and log truncations, from [`OffsetCommit::leader_epoch`].
+///
+/// Since schema version `3`, an [`OffsetCommit`] records the leader epoch of the last record
+/// the group consumed. Within a partition log, leader epochs never decrease as offsets grow:
+/// commits that violate this, for the same group or across groups, are reported as
+/// [`LeaderEpochAnomaly`]. After a Broker incident, [`Self::affected_groups`] tells which
+/// consumers may have skipped or re-read data.
+///
+/// Commits with no leader epoch (i.e. negative) are ignored.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct LeaderEpochAnalyzer {
+    last_commits: BTreeMap<(String, String, i32), EpochCommit>,
+    epochs: BTreeMap<(String, i32), BTreeMap<i32, EpochRange>>,
+    anomalies: Vec<LeaderEpochAnomaly>,
+}
+
+impl LeaderEpochAnalyzer {
+    /// Create an empty [`Self`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    ///
+    /// Returns the anomalies revealed by this commit, if any: they are also retained,
+    /// and returned by [`Self::anomalies`].
+    ///
+    /// If the [`OffsetCommit`] is a tombstone, the last commit of its `(group, topic, partition)`
+    /// is forgotten.
+    pub fn update(&mut self, offset_commit: &OffsetCommit) -> Vec<LeaderEpochAnomaly> {
+        let key = (offset_commit.group.clone(), offset_commit.topic.clone(), offset_commit.partition);
+        if offset_commit.is_tombstone {
+            self.last_commits.remove(&key);
+            return Vec::new();
+        }
+        if offset_commit.leader_epoch < 0 {
+            return Vec::new();
+        }
+
+        let current = EpochCommit {
+            group: offset_commit.group.clone(),
+            offset: offset_commit.offset,
+            leader_epoch: offset_commit.leader_epoch,
+            commit_timestamp: timestamp_millis(&offset_commit.commit_timestamp),
+        };
+        let anomaly = |kind, previous: &EpochCommit| LeaderEpochAnomaly {
+            kind,
+            topic: offset_commit.topic.clone(),
+            partition: offset_commit.partition,
+            previous: previous.clone(),
+            current: current.clone(),
+        };
+
+        let mut found = Vec::new();
+
+        // Same group
+        if let Some(previous) = self.last_commits.get(&key) {
+            if current.leader_epoch < previous.leader_epoch && current.offset >= previous.offset {
+                found.push(anomaly(LeaderEpochAnomalyKind::EpochRegression, previous));
+            } else if current.leader_epoch > previous.leader_epoch && current.offset < previous.offset {
+                found.push(anomaly(LeaderEpochAnomalyKind::Truncation, previous));
+            }
+        }
+
+        // Other groups: an older epoch reaching past this offset, or a newer one starting before it
+        let epochs = self.epochs.entry((offset_commit.topic.clone(), offset_commit.partition)).or_default();
+        if found.is_empty() {
+            let older = epochs
+                .range(..current.leader_epoch)
+                .map(|(_, r)| &r.highest)
+                .filter(|c| c.offset > current.offset)
+                .max_by_key(|c| c.offset);
+            let newer = epochs
+                .range((Bound::Excluded(current.leader_epoch), Bound::Unbounded))
+                .map(|(_, r)| &r.lowest)
+                .filter(|c| c.offset < current.offset)
+                .min_by_key(|c| c.offset);
+            if let Some(previous) = older.or(newer).filter(|c| c.group != current.group) {
+                found.push(anomaly(LeaderEpochAnomalyKind::DivergentLog, previous));
+            }
+        }
+
+        let range = epochs.entry(current.leader_epoch).or_insert_with(|| EpochRange {
+            lowest: current.clone(),
+            highest: current.clone(),
+        });
+        if current.offset < range.lowest.offset {
+            range.lowest = current.clone();
+        }
+        if current.offset > range.highest.offset {
+            range.highest = current.clone();
+        }
+
+        self.last_commits.insert(key, current);
+        self.anomalies.extend(found.iter().cloned());
+        found
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::OffsetCommit`] is relevant: other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) -> Vec<LeaderEpochAnomaly> {
+        match data {
+            KonsumerOffsetsData::OffsetCommit(offset_commit) => self.update(offset_commit),
+            _ => Vec::new(),
+        }
+    }
+
+    /// All the anomalies detected so far, in the order they were detected.
+    pub fn anomalies(&self) -> &[LeaderEpochAnomaly] {
+        &self.anomalies
+    }
+
+    /// Groups involved in the anomalies detected so far, with the affected partitions.
+    ///
+    /// For [`LeaderEpochAnomalyKind::DivergentLog`], both groups are affected: it's not possible
+    /// to tell which one consumed the log that was lost.
+    pub fn affected_groups(&self) -> BTreeMap<String, Vec<TopicPartitions>> {
+        let mut affected: BTreeMap<&str, BTreeSet<(&str, i32)>> = BTreeMap::new();
+        for a in &self.anomalies {
+            for group in [&a.previous.group, &a.current.group] {
+                affected.entry(group).or_default().insert((&a.topic, a.partition));
+            }
+        }
+
+        affected.into_iter().map(|(group, partitions)| (group.to_string(), group_by_topic(partitions))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{commit, is_thread_safe};
+
+    fn commit_at_epoch(group: &str, partition: i32, offset: i64, leader_epoch: i32) -> OffsetCommit {
+        OffsetCommit {
+            leader_epoch,
+            ..commit(group, "t1", partition, offset, offset)
+        }
+    }
+
+    fn kinds(anomalies: &[LeaderEpochAnomaly]) -> Vec<LeaderEpochAnomalyKind> {
+        anomalies.iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_healthy() {
+        let mut analyzer = LeaderEpochAnalyzer::new();
+        for c in [
+            commit_at_epoch("g1", 0, 10, 1),
+            commit_at_epoch("g2", 0, 5, 1),
+            commit_at_epoch("g1", 0, 20, 2),
+            commit_at_epoch("g2", 0, 25, 2),
+        ] {
+            assert_eq!(analyzer.update(&c), vec![]);
+        }
+
+        // Seeking back to older data is fine
+        assert_eq!(analyzer.update(&commit_at_epoch("g1", 0, 8, 1)), vec![]);
+        // No leader epoch
+        assert_eq!(analyzer.update(&commit_at_epoch("g1", 0, 100, -1)), vec![]);
+        assert!(analyzer.affected_groups().is_empty());
+    }
+
+    #[test]
+    fn test_same_group() {
+        let mut analyzer = LeaderEpochAnalyzer::new();
+        analyzer.update(&commit_at_epoch("g1", 0, 10, 3));
+
+        let found = analyzer.update(&commit_at_epoch("g1", 0, 15, 2));
+        assert_eq!(kinds(&found), vec![LeaderEpochAnomalyKind::EpochRegression]);
+        assert_eq!(found[0].previous.offset, 10);
+        assert_eq!(found[0].current.leader_epoch, 2);
+
+        let found = analyzer.update(&commit_at_epoch("g1", 0, 12, 4));
+        assert_eq!(kinds(&found), vec![LeaderEpochAnomalyKind::Truncation]);
+        assert_eq!(analyzer.anomalies().len(), 2);
+    }
+
+    #[test]
+    fn test_divergent_log() {
+        let mut analyzer = LeaderEpochAnalyzer::new();
+        analyzer.update(&commit_at_epoch("g1", 0, 100, 5));
+        analyzer.update(&commit_at_epoch("g2", 1, 50, 4));
+
+        // g2 read past offset 100 on epoch 4, but g1 saw epoch 5 already at offset 100
+        let found = analyzer.update(&commit_at_epoch("g2", 0, 150, 4));
+        assert_eq!(kinds(&found), vec![LeaderEpochAnomalyKind::DivergentLog]);
+        assert_eq!(found[0].previous.group, "g1");
+
+        // Newer epoch at a lower offset than an older epoch reached
+        let found = analyzer.update(&commit_at_epoch("g3", 0, 120, 6));
+        assert_eq!(kinds(&found), vec![LeaderEpochAnomalyKind::DivergentLog]);
+        assert_eq!(found[0].previous.group, "g2");
+
+        let affected = analyzer.affected_groups();
+        assert_eq!(affected.keys().collect::<Vec<_>>(), vec!["g1", "g2", "g3"]);
+        assert_eq!(
+            affected["g2"],
+            vec![TopicPartitions {
+                topic: "t1".to_string(),
+                partitions: vec![0],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_max_leader_epoch() {
+        let mut analyzer = LeaderEpochAnalyzer::new();
+        assert_eq!(analyzer.update(&commit_at_epoch("g1", 0, 10, i32::MAX)), vec![]);
+        assert_eq!(analyzer.update(&commit_at_epoch("g2", 0, 20, i32::MAX)), vec![]);
+        assert_eq!(analyzer.update(&commit_at_epoch("g3", 0, 5, i32::MAX - 1)), vec![]);
+    }
+
+    #[test]
+    fn test_tombstone() {
+        let mut analyzer = LeaderEpochAnalyzer::new();
+        analyzer.update(&commit_at_epoch("g1", 0, 10, 3));
+        analyzer.update_from(&KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+            is_tombstone: true,
+            ..commit_at_epoch("g1", 0, 0, -1)
+        }));
+
+        // Group starts over: a lower offset on a lower epoch is consistent with its previous commit
+        assert_eq!(analyzer.update(&commit_at_epoch("g1", 0, 5, 2)), vec![]);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<EpochCommit>();
+        is_thread_safe::<LeaderEpochAnomalyKind>();
+        is_thread_safe::<LeaderEpochAnomaly>();
+        is_thread_safe::<LeaderEpochAnalyzer>();
+    }
+}
@@ -17,6 +17,18 @@
 //! * `ts_chrono`: use [`chrono::DateTime<Utc>`] to represent Unix Timestamps (conflicts with `ts_int` and `ts_time`)
 //! * `ts_time`: use [`time::OffsetDateTime`] to represent Unix Timestamps (conflicts with `ts_chrono` and `ts_int`)
 //! * `serde`: support serialization/deserialization for all types exposed by this crate via the [serde] crate
+//! * `json_schema`: generate the [JSON Schema] of the `serde` representation of [`KonsumerOffsetsData`]
+//!   and `JsonEnvelope`, via `json_schema()` and `json_envelope_schema()` (implies `serde`)
+//! * `protobuf`: convert the data model from and to [Protobuf] messages, via the `proto` module
+//! * `avro`: convert the data model from and to [Avro] datums, via the `avro` module
+//! * `cli`: build the `konsumer-offsets` command line tool, to decode (or explain) records from hex, base64,
+//!   files or line-oriented dumps, and print them as text, JSON or JSON Lines (implies `serde`)
+//! * `lz4`: support LZ4 block compression in captures (see `CaptureWriter`)
+//! * `dump_log`: read the output of `kafka-dump-log.sh --offsets-decoder` back into records, via `DumpLogReader`
+//! * `arrow`: batch parsed records into [Apache Arrow] tables, via `ArrowBatchBuilder`
+//! * `parquet`: write those tables to [Apache Parquet] files, via `ParquetExporter` (implies `arrow`)
+//! * `sqlite`: write parsed records, and the consumer groups state derived from them, to a [SQLite] database,
+//!   via `SqliteExporter`
 //!
 //! Default features: `ts_int`.
 //!
@@ -115,6 +127,79 @@
 //! This is because some Kafka clients might return the `(key,payload)` _tuple_ as `[u8]`,
 //! others as `Vec<u8>`.
 //!
+//! To see _how_ the bytes of a record map to each field, [`explain()`] produces an annotated byte map
+//! of it (even for records that fail to parse, up to the failure point).
+//!
+//! To compare with the output of Kafka own tooling, [`OffsetCommit::to_offsets_message_format`] and
+//! [`GroupMetadata::to_group_metadata_message_format`] reproduce, byte for byte, what the
+//! `OffsetsMessageFormatter` and `GroupMetadataMessageFormatter` of `kafka-console-consumer.sh` print.
+//!
+//! When the coordinates of the record (partition, offset and timestamp) are also relevant,
+//! [`KonsumerOffsetsRecord`] wraps [`KonsumerOffsetsData`] together with them. When only the text output
+//! of `kafka-dump-log.sh --offsets-decoder` is available, `DumpLogReader` (`dump_log` feature) reads it
+//! back into [`KonsumerOffsetsRecord`]s.
+//!
+//! To record a window of [`__consumer_offsets`] traffic and replay it later (ex. in tests),
+//! [`CaptureWriter`] and [`CaptureReader`] stream raw records to and from a compact, versioned
+//! binary _capture_ format, with optional block compression and an index. [`Replayer`] then emits
+//! records at their original pace (or at a speed factor), driven by a [`ReplayClock`]: use
+//! [`VirtualClock`] for deterministic tests, [`SystemClock`] for real time.
+//!
+//! For analytics, `ArrowBatchBuilder` (`arrow` feature) turns records into columnar tables with stable
+//! schemas, flattening [`GroupMetadata`] into members and assignments, and `ParquetExporter`
+//! (`parquet` feature) writes them to files that query engines like DuckDB or Spark can read.
+//! For ad-hoc investigations with plain SQL, `SqliteExporter` (`sqlite` feature) appends records
+//! (offset commits, groups, members, assignments and tombstones) to a SQLite database with a documented schema.
+//!
+//! To emit parsed records as JSON for consumers written in other languages, [`JsonEnvelope`] wraps them
+//! in a stable, versioned envelope, that also states how timestamps are represented. The [JSON Schema]
+//! of each timestamp representation is published in the `schemas/` directory of this crate.
+//! To publish records to topics whose consumers expect Protobuf or Avro instead, the `proto` module
+//! (`protobuf` feature) and the `avro` module (`avro` feature) convert them both ways,
+//! following the `konsumer_offsets.proto` and `konsumer_offsets.avsc` definitions found in the same directory.
+//!
+//! ## Beyond parsing
+//!
+//! Once parsed, the content of [`__consumer_offsets`] can answer many questions about
+//! the consumer groups of a cluster. This crate provides some building blocks for that:
+//!
+//! * [`CommittedOffsets`]: tracks the latest committed offsets and, given the [`LogOffsets`]
+//!   of the consumed topic partitions, computes consumer lag per partition, topic and group
+//! * [`group_partition`]: computes which partition of [`__consumer_offsets`] (and so which
+//!   [Group Coordinator]) owns a group, and [`validate_partitioning`] flags records found elsewhere
+//! * [`LogCompactionSimulator`]: simulates what the log cleaner retains of [`__consumer_offsets`],
+//!   applying the [Log Compaction] rules to records keyed by [`CompactionKey`]
+//! * [`OffsetExpirationSimulator`]: predicts which committed offsets the Broker will expire, and when,
+//!   given its [`OffsetRetentionConfig`]
+//! * [`TimeLagEstimator`]: turns offset lag into an approximate _time_ lag, interpolating
+//!   periodic samples of log-end offsets
+//! * [`RebalanceTracker`]: compares successive [`GroupMetadata`] of a group, reporting the
+//!   [`RebalanceEvent`]s (members joining and leaving, partitions moving, ...) of each [`GroupRebalance`]
+//! * [`AssignmentAudit`]: checks the partition assignment of a [`GroupMetadata`] for skew, partitions
+//!   assigned twice or to nobody, members without partitions and subscribed topics without assignment
+//! * [`PartitionAssignor`]: Kafka's standard assignors ([`RangeAssignor`], [`RoundRobinAssignor`],
+//!   [`StickyAssignor`], [`CooperativeStickyAssignor`]) for _"what if"_ analysis, with
+//!   [`AssignmentComparison`] to diff their result against the actual assignment
+//! * [`RackAnalysis`]: given the [`PartitionRacks`] of the consumed partitions, reports the cross-rack
+//!   fetch traffic of each member of a group, and suggests a rack-aligned assignment
+//! * [`MemberLinter`]: checks the configuration of the members of a group against built-in
+//!   (and custom) [`LintRule`]s, producing [`LintFinding`]s of different [`LintSeverity`]
+//! * [`ConsumerHealthEvaluator`]: detects stalled, stopped and regressing consumers from the
+//!   sequence of their [`OffsetCommit`]s, reporting each [`ConsumerIssue`] with its evidence
+//! * [`LeaderEpochAnalyzer`]: flags commits whose [`OffsetCommit::leader_epoch`] suggests unclean
+//!   leader election or log truncation, listing the affected groups and partitions
+//! * [`ConsumptionRateTracker`]: computes messages per second processed by each group, topic and
+//!   partition over a sliding window, and infers from commit frequency the [`CommitMode`] of consumers
+//! * [`GroupClassifier`]: tells the [`GroupKind`] of each group (classic or [KIP-848] consumer, simple,
+//!   Connect, Streams, Schema Registry, share), together with the [`ClassificationEvidence`] used
+//! * [`ClientInventory`]: lists the members of all groups by [`ClientHost`], `client.id` and inferred
+//!   [`ClientLibrary`] and version, to find outdated clients and unexpected hosts consuming a topic
+//!
+//! **NOTE:** All the timestamps these building blocks take as input (ex. `now_ms`), hold, report
+//! and export (ex. [`KonsumerOffsetsRecord::timestamp`], Arrow and SQLite columns, Protobuf and Avro
+//! messages) are milliseconds since Unix Epoch, and all durations are milliseconds, independently of
+//! the `ts_*` feature in use.
+//!
 //! ## A few words about parsing Kafka _entrails_
 //!
 //! Kafka runs on the JVM, so it's limited to what the JVM supports.
@@ -198,18 +283,86 @@
 //! [`chrono::DateTime<Utc>`]: https://docs.rs/chrono/latest/chrono/struct.DateTime.html#method.from_utc
 //! [`time::OffsetDateTime`]: https://time-rs.github.io/api/time/struct.OffsetDateTime.html#method.from_unix_timestamp_nanos
 //! [serde]: https://crates.io/crates/serde
+//! [Apache Arrow]: https://arrow.apache.org/
+//! [Apache Parquet]: https://parquet.apache.org/
+//! [SQLite]: https://sqlite.org/
+//! [JSON Schema]: https://json-schema.org/
+//! [Protobuf]: https://protobuf.dev/
+//! [Avro]: https://avro.apache.org/
+//! [KIP-848]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-848%3A+The+Next+Generation+of+the+Consumer+Rebalance+Protocol
 //!
 
+mod assignment_audit;
+mod assignor;
+#[cfg(feature = "avro")]
+pub mod avro;
+mod capture;
+mod classification;
+#[cfg(feature = "arrow")]
+mod columnar;
+mod compaction;
+mod consumer_health;
+mod coordinator;
+#[cfg(feature = "dump_log")]
+mod dump_log;
 mod errors;
+mod expiration;
+mod explain;
 mod group_metadata;
+mod inventory;
+mod json_envelope;
+#[cfg(feature = "json_schema")]
+mod json_schema;
+mod kafka_format;
 mod konsumer_offsets_data;
+mod lag;
+mod leader_epoch;
+mod lint;
 mod offset_commit;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+mod rack;
+mod rate;
+mod rebalance;
+mod record;
+mod replay;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod time_lag;
 mod utils;
 
+pub use assignment_audit::*;
+pub use assignor::*;
+pub use capture::*;
+pub use classification::*;
+#[cfg(feature = "arrow")]
+pub use columnar::*;
+pub use compaction::*;
+pub use consumer_health::*;
+pub use coordinator::*;
+#[cfg(feature = "dump_log")]
+pub use dump_log::*;
 pub use errors::*;
+pub use expiration::*;
+pub use explain::*;
 pub use group_metadata::*;
+pub use inventory::*;
+pub use json_envelope::*;
+#[cfg(feature = "json_schema")]
+pub use json_schema::*;
 pub use konsumer_offsets_data::*;
+pub use lag::*;
+pub use leader_epoch::*;
+pub use lint::*;
 pub use offset_commit::*;
+pub use rack::*;
+pub use rate::*;
+pub use rebalance::*;
+pub use record::*;
+pub use replay::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+pub use time_lag::*;
 
 #[cfg(any(
     all(feature = "ts_int", feature = "ts_chrono"),
@@ -0,0 +1,474 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use crate::group_metadata::{GroupMetadata, MemberMetadata};
+
+/// How serious a [`LintFinding`] is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LintSeverity {
+    /// Worth knowing, but likely intended.
+    Info,
+
+    /// Likely a misconfiguration.
+    Warning,
+
+    /// A misconfiguration that breaks (or will break) the group.
+    Error,
+}
+
+/// A problem found by a [`LintRule`] in the configuration of a group.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LintFinding {
+    /// [`LintRule::name`] of the rule that produced the finding.
+    pub rule: String,
+
+    /// Severity.
+    pub severity: LintSeverity,
+
+    /// Group.
+    pub group: String,
+
+    /// [`MemberMetadata::id`] of the member the finding is about: `None` if about the whole group.
+    pub member_id: Option<String>,
+
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+/// A check over the members of a group, as found in its [`GroupMetadata`].
+///
+/// Implement this to add custom rules to a [`MemberLinter`].
+pub trait LintRule: Send + Sync {
+    /// Unique name of the rule (ex. `empty-client-id`).
+    fn name(&self) -> &str;
+
+    /// Checks the group, returning a [`LintFinding`] for every problem found.
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding>;
+}
+
+/// Flags members whose subscribed topics differ from those of the rest of the group.
+///
+/// All members of a consumer group are expected to subscribe to the same topics: when they don't
+/// (ex. during a rolling deploy), some partitions can end up unassigned.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct DifferentSubscriptionsRule;
+
+/// Flags members with a `session.timeout.ms` outside the bounds accepted by the Broker.
+///
+/// Bounds are the Broker `group.min.session.timeout.ms` and `group.max.session.timeout.ms`:
+/// a member outside them will be rejected the next time it (re)joins the group.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SessionTimeoutBoundsRule {
+    /// Broker `group.min.session.timeout.ms`.
+    pub min_ms: i32,
+
+    /// Broker `group.max.session.timeout.ms`.
+    pub max_ms: i32,
+}
+
+impl Default for SessionTimeoutBoundsRule {
+    /// Broker defaults: `6000` and `1800000` (30 minutes).
+    fn default() -> Self {
+        Self {
+            min_ms: 6_000,
+            max_ms: 1_800_000,
+        }
+    }
+}
+
+/// Flags static members that share a `group.instance.id` pattern across different hosts.
+///
+/// Instance ids are compared with their digits masked (ex. `orders-3` becomes `orders-#`).
+/// An instance id that is not pinned to a host can be claimed by a process on another host
+/// (ex. after a restart), fencing the instance still running.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct StaticMemberHostsRule;
+
+/// Flags members with an empty `client.id`, that makes them hard to tell apart in logs and metrics.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct EmptyClientIdRule;
+
+/// Flags groups whose members send different [`crate::ConsumerProtocolSubscription::schema_version`]s.
+///
+/// The version depends on the client library: different versions point to mixed client versions within the group.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct MixedSubscriptionVersionsRule;
+
+fn finding(
+    rule: &impl LintRule,
+    severity: LintSeverity,
+    group_metadata: &GroupMetadata,
+    member: Option<&MemberMetadata>,
+    message: String,
+) -> LintFinding {
+    LintFinding {
+        rule: rule.name().to_string(),
+        severity,
+        group: group_metadata.group.clone(),
+        member_id: member.map(|m| m.id.clone()),
+        message,
+    }
+}
+
+impl LintRule for DifferentSubscriptionsRule {
+    fn name(&self) -> &str {
+        "different-subscriptions"
+    }
+
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        let subscriptions: Vec<BTreeSet<&str>> = group_metadata
+            .members
+            .iter()
+            .map(|m| m.subscription.subscribed_topics.iter().map(String::as_str).collect())
+            .collect();
+
+        let mut occurrences: HashMap<&BTreeSet<&str>, usize> = HashMap::new();
+        for s in &subscriptions {
+            *occurrences.entry(s).or_default() += 1;
+        }
+        // The most common subscription: `max_by_key` returns the last among equals, hence `rev()`
+        let Some(common) = subscriptions.iter().rev().max_by_key(|s| occurrences[s]) else {
+            return Vec::new();
+        };
+
+        group_metadata
+            .members
+            .iter()
+            .zip(&subscriptions)
+            .filter(|(_, s)| *s != common)
+            .map(|(m, s)| {
+                let message = format!("subscribes to {s:?}, while most members subscribe to {common:?}");
+                finding(self, LintSeverity::Warning, group_metadata, Some(m), message)
+            })
+            .collect()
+    }
+}
+
+impl LintRule for SessionTimeoutBoundsRule {
+    fn name(&self) -> &str {
+        "session-timeout-bounds"
+    }
+
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        group_metadata
+            .members
+            .iter()
+            .filter(|m| !(self.min_ms..=self.max_ms).contains(&m.session_timeout))
+            .map(|m| {
+                let message = format!(
+                    "session timeout of {}ms is outside the Broker bounds [{}ms, {}ms]",
+                    m.session_timeout, self.min_ms, self.max_ms
+                );
+                finding(self, LintSeverity::Error, group_metadata, Some(m), message)
+            })
+            .collect()
+    }
+}
+
+impl LintRule for StaticMemberHostsRule {
+    fn name(&self) -> &str {
+        "static-member-hosts"
+    }
+
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        let mut hosts_by_pattern: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        for m in group_metadata.members.iter().filter(|m| !m.group_instance_id.is_empty()) {
+            hosts_by_pattern.entry(mask_digits(&m.group_instance_id)).or_default().insert(&m.client_host);
+        }
+
+        hosts_by_pattern
+            .into_iter()
+            .filter(|(_, hosts)| hosts.len() > 1)
+            .map(|(pattern, hosts)| {
+                let message = format!(
+                    "static members with instance id pattern `{pattern}` run on {} hosts: \
+                    make sure each instance id is pinned to a single host",
+                    hosts.len()
+                );
+                finding(self, LintSeverity::Info, group_metadata, None, message)
+            })
+            .collect()
+    }
+}
+
+impl LintRule for EmptyClientIdRule {
+    fn name(&self) -> &str {
+        "empty-client-id"
+    }
+
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        group_metadata
+            .members
+            .iter()
+            .filter(|m| m.client_id.is_empty())
+            .map(|m| finding(self, LintSeverity::Warning, group_metadata, Some(m), "client id is empty".to_string()))
+            .collect()
+    }
+}
+
+impl LintRule for MixedSubscriptionVersionsRule {
+    fn name(&self) -> &str {
+        "mixed-subscription-versions"
+    }
+
+    fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        let mut members_by_version: BTreeMap<i16, usize> = BTreeMap::new();
+        for m in &group_metadata.members {
+            *members_by_version.entry(m.subscription.schema_version).or_default() += 1;
+        }
+
+        if members_by_version.len() < 2 {
+            return Vec::new();
+        }
+
+        let versions: Vec<String> = members_by_version.iter().map(|(v, n)| format!("v{v} ({n} members)")).collect();
+        let message = format!("members use subscription versions {}: client versions are mixed", versions.join(", "));
+        vec![finding(self, LintSeverity::Info, group_metadata, None, message)]
+    }
+}
+
+/// Replaces every run of digits with `#`.
+fn mask_digits(s: &str) -> String {
+    let mut masked = String::with_capacity(s.len());
+    for c in s.chars() {
+        if !c.is_ascii_digit() {
+            masked.push(c);
+        } else if !masked.ends_with('#') {
+            masked.push('#');
+        }
+    }
+    masked
+}
+
+/// Lint engine, running a set of [`LintRule`]s over the members of groups.
+///
+/// [`MemberLinter::default`] comes with the built-in rules:
+///
+/// * [`DifferentSubscriptionsRule`]
+/// * [`SessionTimeoutBoundsRule`] (with the Broker default bounds)
+/// * [`StaticMemberHostsRule`]
+/// * [`EmptyClientIdRule`]
+/// * [`MixedSubscriptionVersionsRule`]
+///
+/// Rules can be added, removed and their severity overridden.
+pub struct MemberLinter {
+    rules: Vec<Box<dyn LintRule>>,
+    severities: HashMap<String, LintSeverity>,
+}
+
+impl Default for MemberLinter {
+    fn default() -> Self {
+        Self::empty()
+            .with_rule(DifferentSubscriptionsRule)
+            .with_rule(SessionTimeoutBoundsRule::default())
+            .with_rule(StaticMemberHostsRule)
+            .with_rule(EmptyClientIdRule)
+            .with_rule(MixedSubscriptionVersionsRule)
+    }
+}
+
+impl fmt::Debug for MemberLinter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemberLinter")
+            .field("rules", &self.rules().collect::<Vec<_>>())
+            .field("severities", &self.severities)
+            .finish()
+    }
+}
+
+impl MemberLinter {
+    /// Creates a [`Self`] with no rules.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            severities: HashMap::new(),
+        }
+    }
+
+    /// Adds a rule, replacing the one with the same [`LintRule::name`], if any.
+    pub fn with_rule(mut self, rule: impl LintRule + 'static) -> Self {
+        self.rules.retain(|r| r.name() != rule.name());
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Removes the rule with the given [`LintRule::name`].
+    pub fn without_rule(mut self, name: &str) -> Self {
+        self.rules.retain(|r| r.name() != name);
+        self
+    }
+
+    /// Overrides the severity of the findings of the rule with the given [`LintRule::name`].
+    pub fn with_severity(mut self, name: &str, severity: LintSeverity) -> Self {
+        self.severities.insert(name.to_string(), severity);
+        self
+    }
+
+    /// Names of the rules, in the order they run.
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|r| r.name())
+    }
+
+    /// Runs all the rules over the given [`GroupMetadata`].
+    ///
+    /// Findings are sorted by descending severity, then in the order rules run.
+    /// Tombstones (i.e. groups that were removed) have no findings.
+    pub fn lint(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+        if group_metadata.is_tombstone {
+            return Vec::new();
+        }
+
+        let mut findings: Vec<LintFinding> = self
+            .rules
+            .iter()
+            .flat_map(|r| r.check(group_metadata))
+            .map(|mut f| {
+                if let Some(severity) = self.severities.get(&f.rule) {
+                    f.severity = *severity;
+                }
+                f
+            })
+            .collect();
+        findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{group, is_thread_safe, member};
+
+    fn lint_member(id: &str, instance_id: &str, host: &str, topics: &[&str], version: i16) -> MemberMetadata {
+        let mut m = member(id, topics, &[]);
+        m.group_instance_id = instance_id.to_string();
+        m.client_host = host.to_string();
+        m.rebalance_timeout = 300_000;
+        m.session_timeout = 45_000;
+        m.subscription.schema_version = version;
+        m
+    }
+
+    fn rules_of(findings: &[LintFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_clean_group() {
+        let gm = group(
+            "g1",
+            vec![lint_member("a", "", "/10.0.0.1", &["t1"], 3), lint_member("b", "", "/10.0.0.2", &["t1"], 3)],
+        );
+        assert_eq!(MemberLinter::default().lint(&gm), vec![]);
+    }
+
+    #[test]
+    fn test_built_in_rules() {
+        let mut misconfigured = lint_member("c", "orders-1", "/10.0.0.2", &["t1", "t2"], 1);
+        misconfigured.client_id = String::new();
+        misconfigured.session_timeout = 3_000;
+        let gm = group(
+            "g1",
+            vec![
+                lint_member("a", "orders-0", "/10.0.0.1", &["t1"], 3),
+                lint_member("b", "", "/10.0.0.1", &["t1"], 3),
+                misconfigured,
+            ],
+        );
+
+        let findings = MemberLinter::default().lint(&gm);
+        assert_eq!(
+            rules_of(&findings),
+            vec![
+                "session-timeout-bounds",
+                "different-subscriptions",
+                "empty-client-id",
+                "static-member-hosts",
+                "mixed-subscription-versions"
+            ]
+        );
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+        assert_eq!(findings[0].member_id.as_deref(), Some("c"));
+        assert_eq!(findings[1].member_id.as_deref(), Some("c"));
+        assert!(findings[3].message.contains("`orders-#`"));
+        assert_eq!(findings[4].member_id, None);
+        assert!(findings[4].message.contains("v1 (1 members), v3 (2 members)"));
+    }
+
+    struct NoLegacyProtocolRule;
+
+    impl LintRule for NoLegacyProtocolRule {
+        fn name(&self) -> &str {
+            "no-range"
+        }
+
+        fn check(&self, group_metadata: &GroupMetadata) -> Vec<LintFinding> {
+            if group_metadata.protocol != "range" {
+                return Vec::new();
+            }
+            vec![LintFinding {
+                rule: self.name().to_string(),
+                severity: LintSeverity::Info,
+                group: group_metadata.group.clone(),
+                member_id: None,
+                message: "consider cooperative-sticky".to_string(),
+            }]
+        }
+    }
+
+    #[test]
+    fn test_custom_rules_and_severities() {
+        let mut gm = group("g1", vec![lint_member("a", "", "/10.0.0.1", &["t1"], 3)]);
+        gm.protocol = "range".to_string();
+        gm.members[0].client_id = String::new();
+
+        let linter = MemberLinter::empty()
+            .with_rule(EmptyClientIdRule)
+            .with_rule(NoLegacyProtocolRule)
+            .with_severity("no-range", LintSeverity::Error);
+        assert_eq!(linter.rules().collect::<Vec<_>>(), vec!["empty-client-id", "no-range"]);
+
+        let findings = linter.lint(&gm);
+        assert_eq!(rules_of(&findings), vec!["no-range", "empty-client-id"]);
+        assert_eq!(findings[0].severity, LintSeverity::Error);
+
+        let linter = linter.without_rule("empty-client-id");
+        assert_eq!(rules_of(&linter.lint(&gm)), vec!["no-range"]);
+
+        gm.is_tombstone = true;
+        assert_eq!(linter.lint(&gm), vec![]);
+    }
+
+    #[test]
+    fn test_session_timeout_bounds() {
+        let mut gm = group("g1", vec![lint_member("a", "", "/10.0.0.1", &["t1"], 3)]);
+        gm.members[0].session_timeout = 2_000_000;
+        assert_eq!(SessionTimeoutBoundsRule::default().check(&gm).len(), 1);
+
+        let rule = SessionTimeoutBoundsRule {
+            min_ms: 6_000,
+            max_ms: 3_600_000,
+        };
+        assert_eq!(rule.check(&gm), vec![]);
+    }
+
+    #[test]
+    fn test_mask_digits() {
+        assert_eq!(mask_digits("orders-consumer-12"), "orders-consumer-#");
+        assert_eq!(mask_digits("az1-pod-3"), "az#-pod-#");
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<LintSeverity>();
+        is_thread_safe::<LintFinding>();
+        is_thread_safe::<DifferentSubscriptionsRule>();
+        is_thread_safe::<SessionTimeoutBoundsRule>();
+        is_thread_safe::<StaticMemberHostsRule>();
+        is_thread_safe::<EmptyClientIdRule>();
+        is_thread_safe::<MixedSubscriptionVersionsRule>();
+        is_thread_safe::<MemberLinter>();
+    }
+}
@@ -28,6 +28,7 @@ use crate::utils::{parse_i16, parse_i32, parse_i64, parse_str};
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(feature = "ts_int", feature = "ts_chrono"), derive(Default))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
 pub struct OffsetCommit {
     /// **`(KEY)`** First 2-bytes integers in the original `__consumer_offsets`, identifying this data type.
     ///
@@ -89,8 +90,10 @@ pub struct OffsetCommit {
     #[cfg(feature = "ts_int")]
     pub commit_timestamp: i64,
     #[cfg(feature = "ts_chrono")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub commit_timestamp: chrono::DateTime<chrono::Utc>,
     #[cfg(feature = "ts_time")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub commit_timestamp: time::OffsetDateTime,
 
     /// **`(PAYLOAD)`** Timestamp of when the offset will fall from topic retention.
@@ -102,8 +105,10 @@ pub struct OffsetCommit {
     #[cfg(feature = "ts_int")]
     pub expire_timestamp: i64,
     #[cfg(feature = "ts_chrono")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub expire_timestamp: chrono::DateTime<chrono::Utc>,
     #[cfg(feature = "ts_time")]
+    #[cfg_attr(feature = "json_schema", schemars(schema_with = "crate::json_schema::timestamp_schema"))]
     pub expire_timestamp: time::OffsetDateTime,
 }
 
@@ -0,0 +1,443 @@
+//! [Protobuf] messages mirroring the data model of this crate, and conversions from and to it.
+//!
+//! The messages are defined in [`PROTO_DEFINITION`] (also published as `schemas/konsumer_offsets.proto`),
+//! and each of them converts:
+//!
+//! * from the corresponding type of this crate, via [`From`]
+//! * to the corresponding type of this crate, via [`TryFrom`], failing with [`ConversionError`]
+//!
+//! [`encode`] and [`decode`] take care of both conversion and (de)serialization of a whole
+//! [`crate::KonsumerOffsetsData`]:
+//!
+//! ```
+//! # use konsumer_offsets::{proto, KonsumerOffsetsData, GroupMetadata};
+//! let data = KonsumerOffsetsData::GroupMetadata(GroupMetadata::default());
+//! let bytes = proto::encode(&data);
+//! assert_eq!(proto::decode(&bytes).unwrap(), data);
+//! ```
+//!
+//! **NOTE:** Timestamps are encoded as milliseconds since Unix Epoch.
+//!
+//! [Protobuf]: https://protobuf.dev/
+
+use prost::Message;
+
+use crate::errors::ConversionError;
+use crate::utils::{timestamp_millis, try_timestamp_from_millis};
+
+/// Protobuf definition of the messages of this module.
+pub const PROTO_DEFINITION: &str = include_str!("../schemas/konsumer_offsets.proto");
+
+/// Mirrors [`crate::KonsumerOffsetsData`].
+#[derive(Clone, PartialEq, Message)]
+pub struct KonsumerOffsetsData {
+    /// Either an [`OffsetCommit`] or a [`GroupMetadata`].
+    #[prost(oneof = "konsumer_offsets_data::Data", tags = "1, 2")]
+    pub data: Option<konsumer_offsets_data::Data>,
+}
+
+/// Nested types of [`KonsumerOffsetsData`].
+pub mod konsumer_offsets_data {
+    /// Possible content of [`super::KonsumerOffsetsData`].
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Data {
+        /// Mirrors [`crate::KonsumerOffsetsData::OffsetCommit`].
+        #[prost(message, tag = "1")]
+        OffsetCommit(super::OffsetCommit),
+
+        /// Mirrors [`crate::KonsumerOffsetsData::GroupMetadata`].
+        #[prost(message, tag = "2")]
+        GroupMetadata(super::GroupMetadata),
+    }
+}
+
+/// Mirrors [`crate::OffsetCommit`].
+#[derive(Clone, PartialEq, Message)]
+pub struct OffsetCommit {
+    #[prost(int32, tag = "1")]
+    pub message_version: i32,
+    #[prost(string, tag = "2")]
+    pub group: String,
+    #[prost(string, tag = "3")]
+    pub topic: String,
+    #[prost(int32, tag = "4")]
+    pub partition: i32,
+    #[prost(bool, tag = "5")]
+    pub is_tombstone: bool,
+    #[prost(int32, tag = "6")]
+    pub schema_version: i32,
+    #[prost(int64, tag = "7")]
+    pub offset: i64,
+    #[prost(int32, tag = "8")]
+    pub leader_epoch: i32,
+    #[prost(string, tag = "9")]
+    pub metadata: String,
+    /// Milliseconds since Unix Epoch.
+    #[prost(int64, tag = "10")]
+    pub commit_timestamp: i64,
+    /// Milliseconds since Unix Epoch.
+    #[prost(int64, tag = "11")]
+    pub expire_timestamp: i64,
+}
+
+/// Mirrors [`crate::GroupMetadata`].
+#[derive(Clone, PartialEq, Message)]
+pub struct GroupMetadata {
+    #[prost(int32, tag = "1")]
+    pub message_version: i32,
+    #[prost(string, tag = "2")]
+    pub group: String,
+    #[prost(bool, tag = "3")]
+    pub is_tombstone: bool,
+    #[prost(int32, tag = "4")]
+    pub schema_version: i32,
+    #[prost(string, tag = "5")]
+    pub protocol_type: String,
+    #[prost(int32, tag = "6")]
+    pub generation: i32,
+    #[prost(string, tag = "7")]
+    pub protocol: String,
+    #[prost(string, tag = "8")]
+    pub leader: String,
+    /// Milliseconds since Unix Epoch.
+    #[prost(int64, tag = "9")]
+    pub current_state_timestamp: i64,
+    #[prost(message, repeated, tag = "10")]
+    pub members: Vec<MemberMetadata>,
+}
+
+/// Mirrors [`crate::MemberMetadata`].
+#[derive(Clone, PartialEq, Message)]
+pub struct MemberMetadata {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub group_instance_id: String,
+    #[prost(string, tag = "3")]
+    pub client_id: String,
+    #[prost(string, tag = "4")]
+    pub client_host: String,
+    #[prost(int32, tag = "5")]
+    pub rebalance_timeout: i32,
+    #[prost(int32, tag = "6")]
+    pub session_timeout: i32,
+    #[prost(message, optional, tag = "7")]
+    pub subscription: Option<ConsumerProtocolSubscription>,
+    #[prost(message, optional, tag = "8")]
+    pub assignment: Option<ConsumerProtocolAssignment>,
+}
+
+/// Mirrors [`crate::ConsumerProtocolSubscription`].
+#[derive(Clone, PartialEq, Message)]
+pub struct ConsumerProtocolSubscription {
+    #[prost(int32, tag = "1")]
+    pub schema_version: i32,
+    #[prost(string, repeated, tag = "2")]
+    pub subscribed_topics: Vec<String>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub user_data: Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub owned_topic_partitions: Vec<TopicPartitions>,
+    #[prost(int32, tag = "5")]
+    pub generation_id: i32,
+    #[prost(string, tag = "6")]
+    pub rack_id: String,
+}
+
+/// Mirrors [`crate::TopicPartitions`].
+#[derive(Clone, PartialEq, Message)]
+pub struct TopicPartitions {
+    #[prost(string, tag = "1")]
+    pub topic: String,
+    #[prost(int32, repeated, tag = "2")]
+    pub partitions: Vec<i32>,
+}
+
+/// Mirrors [`crate::ConsumerProtocolAssignment`].
+#[derive(Clone, PartialEq, Message)]
+pub struct ConsumerProtocolAssignment {
+    #[prost(int32, tag = "1")]
+    pub schema_version: i32,
+    #[prost(message, repeated, tag = "2")]
+    pub assigned_topic_partitions: Vec<TopicPartitions>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub user_data: Vec<u8>,
+}
+
+/// Encodes a [`crate::KonsumerOffsetsData`] as a Protobuf [`KonsumerOffsetsData`] message.
+pub fn encode(data: &crate::KonsumerOffsetsData) -> Vec<u8> {
+    KonsumerOffsetsData::from(data).encode_to_vec()
+}
+
+/// Decodes a Protobuf [`KonsumerOffsetsData`] message into a [`crate::KonsumerOffsetsData`].
+pub fn decode(bytes: &[u8]) -> Result<crate::KonsumerOffsetsData, ConversionError> {
+    KonsumerOffsetsData::decode(bytes).map_err(|e| ConversionError::Decoding(e.to_string()))?.try_into()
+}
+
+fn to_i16(field: &'static str, value: i32) -> Result<i16, ConversionError> {
+    i16::try_from(value).map_err(|_| ConversionError::OutOfRange(field, value as i64))
+}
+
+impl From<&crate::KonsumerOffsetsData> for KonsumerOffsetsData {
+    fn from(data: &crate::KonsumerOffsetsData) -> Self {
+        let data = match data {
+            crate::KonsumerOffsetsData::OffsetCommit(oc) => konsumer_offsets_data::Data::OffsetCommit(oc.into()),
+            crate::KonsumerOffsetsData::GroupMetadata(gm) => konsumer_offsets_data::Data::GroupMetadata(gm.into()),
+        };
+        Self {
+            data: Some(data),
+        }
+    }
+}
+
+impl TryFrom<KonsumerOffsetsData> for crate::KonsumerOffsetsData {
+    type Error = ConversionError;
+
+    fn try_from(data: KonsumerOffsetsData) -> Result<Self, Self::Error> {
+        Ok(match data.data.ok_or(ConversionError::MissingField("KonsumerOffsetsData.data"))? {
+            konsumer_offsets_data::Data::OffsetCommit(oc) => Self::OffsetCommit(oc.try_into()?),
+            konsumer_offsets_data::Data::GroupMetadata(gm) => Self::GroupMetadata(gm.try_into()?),
+        })
+    }
+}
+
+impl From<&crate::OffsetCommit> for OffsetCommit {
+    fn from(oc: &crate::OffsetCommit) -> Self {
+        Self {
+            message_version: oc.message_version as i32,
+            group: oc.group.clone(),
+            topic: oc.topic.clone(),
+            partition: oc.partition,
+            is_tombstone: oc.is_tombstone,
+            schema_version: oc.schema_version as i32,
+            offset: oc.offset,
+            leader_epoch: oc.leader_epoch,
+            metadata: oc.metadata.clone(),
+            commit_timestamp: timestamp_millis(&oc.commit_timestamp),
+            expire_timestamp: timestamp_millis(&oc.expire_timestamp),
+        }
+    }
+}
+
+impl TryFrom<OffsetCommit> for crate::OffsetCommit {
+    type Error = ConversionError;
+
+    fn try_from(oc: OffsetCommit) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message_version: to_i16("OffsetCommit.message_version", oc.message_version)?,
+            group: oc.group,
+            topic: oc.topic,
+            partition: oc.partition,
+            is_tombstone: oc.is_tombstone,
+            schema_version: to_i16("OffsetCommit.schema_version", oc.schema_version)?,
+            offset: oc.offset,
+            leader_epoch: oc.leader_epoch,
+            metadata: oc.metadata,
+            commit_timestamp: try_timestamp_from_millis(oc.commit_timestamp)?,
+            expire_timestamp: try_timestamp_from_millis(oc.expire_timestamp)?,
+        })
+    }
+}
+
+impl From<&crate::GroupMetadata> for GroupMetadata {
+    fn from(gm: &crate::GroupMetadata) -> Self {
+        Self {
+            message_version: gm.message_version as i32,
+            group: gm.group.clone(),
+            is_tombstone: gm.is_tombstone,
+            schema_version: gm.schema_version as i32,
+            protocol_type: gm.protocol_type.clone(),
+            generation: gm.generation,
+            protocol: gm.protocol.clone(),
+            leader: gm.leader.clone(),
+            current_state_timestamp: timestamp_millis(&gm.current_state_timestamp),
+            members: gm.members.iter().map(MemberMetadata::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<GroupMetadata> for crate::GroupMetadata {
+    type Error = ConversionError;
+
+    fn try_from(gm: GroupMetadata) -> Result<Self, Self::Error> {
+        Ok(Self {
+            message_version: to_i16("GroupMetadata.message_version", gm.message_version)?,
+            group: gm.group,
+            is_tombstone: gm.is_tombstone,
+            schema_version: to_i16("GroupMetadata.schema_version", gm.schema_version)?,
+            protocol_type: gm.protocol_type,
+            generation: gm.generation,
+            protocol: gm.protocol,
+            leader: gm.leader,
+            current_state_timestamp: try_timestamp_from_millis(gm.current_state_timestamp)?,
+            members: gm.members.into_iter().map(TryInto::try_into).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&crate::MemberMetadata> for MemberMetadata {
+    fn from(member: &crate::MemberMetadata) -> Self {
+        Self {
+            id: member.id.clone(),
+            group_instance_id: member.group_instance_id.clone(),
+            client_id: member.client_id.clone(),
+            client_host: member.client_host.clone(),
+            rebalance_timeout: member.rebalance_timeout,
+            session_timeout: member.session_timeout,
+            subscription: Some((&member.subscription).into()),
+            assignment: Some((&member.assignment).into()),
+        }
+    }
+}
+
+impl TryFrom<MemberMetadata> for crate::MemberMetadata {
+    type Error = ConversionError;
+
+    fn try_from(member: MemberMetadata) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: member.id,
+            group_instance_id: member.group_instance_id,
+            client_id: member.client_id,
+            client_host: member.client_host,
+            rebalance_timeout: member.rebalance_timeout,
+            session_timeout: member.session_timeout,
+            subscription: member
+                .subscription
+                .ok_or(ConversionError::MissingField("MemberMetadata.subscription"))?
+                .try_into()?,
+            assignment: member
+                .assignment
+                .ok_or(ConversionError::MissingField("MemberMetadata.assignment"))?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<&crate::ConsumerProtocolSubscription> for ConsumerProtocolSubscription {
+    fn from(subscription: &crate::ConsumerProtocolSubscription) -> Self {
+        Self {
+            schema_version: subscription.schema_version as i32,
+            subscribed_topics: subscription.subscribed_topics.clone(),
+            user_data: subscription.user_data.clone(),
+            owned_topic_partitions: subscription.owned_topic_partitions.iter().map(TopicPartitions::from).collect(),
+            generation_id: subscription.generation_id,
+            rack_id: subscription.rack_id.clone(),
+        }
+    }
+}
+
+impl TryFrom<ConsumerProtocolSubscription> for crate::ConsumerProtocolSubscription {
+    type Error = ConversionError;
+
+    fn try_from(subscription: ConsumerProtocolSubscription) -> Result<Self, Self::Error> {
+        Ok(Self {
+            schema_version: to_i16("ConsumerProtocolSubscription.schema_version", subscription.schema_version)?,
+            subscribed_topics: subscription.subscribed_topics,
+            user_data: subscription.user_data,
+            owned_topic_partitions: subscription.owned_topic_partitions.into_iter().map(Into::into).collect(),
+            generation_id: subscription.generation_id,
+            rack_id: subscription.rack_id,
+        })
+    }
+}
+
+impl From<&crate::TopicPartitions> for TopicPartitions {
+    fn from(tp: &crate::TopicPartitions) -> Self {
+        Self {
+            topic: tp.topic.clone(),
+            partitions: tp.partitions.clone(),
+        }
+    }
+}
+
+impl From<TopicPartitions> for crate::TopicPartitions {
+    fn from(tp: TopicPartitions) -> Self {
+        Self {
+            topic: tp.topic,
+            partitions: tp.partitions,
+        }
+    }
+}
+
+impl From<&crate::ConsumerProtocolAssignment> for ConsumerProtocolAssignment {
+    fn from(assignment: &crate::ConsumerProtocolAssignment) -> Self {
+        Self {
+            schema_version: assignment.schema_version as i32,
+            assigned_topic_partitions: assignment.assigned_topic_partitions.iter().map(TopicPartitions::from).collect(),
+            user_data: assignment.user_data.clone(),
+        }
+    }
+}
+
+impl TryFrom<ConsumerProtocolAssignment> for crate::ConsumerProtocolAssignment {
+    type Error = ConversionError;
+
+    fn try_from(assignment: ConsumerProtocolAssignment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            schema_version: to_i16("ConsumerProtocolAssignment.schema_version", assignment.schema_version)?,
+            assigned_topic_partitions: assignment.assigned_topic_partitions.into_iter().map(Into::into).collect(),
+            user_data: assignment.user_data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{is_thread_safe, read_fixtures};
+
+    #[test]
+    fn test_round_trip() {
+        for data in read_fixtures() {
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(decode(&[0xFF]), Err(ConversionError::Decoding(_))));
+        assert_eq!(
+            decode(&KonsumerOffsetsData::default().encode_to_vec()),
+            Err(ConversionError::MissingField("KonsumerOffsetsData.data"))
+        );
+
+        let msg = KonsumerOffsetsData {
+            data: Some(konsumer_offsets_data::Data::OffsetCommit(OffsetCommit {
+                schema_version: 40000,
+                ..Default::default()
+            })),
+        };
+        assert_eq!(
+            decode(&msg.encode_to_vec()),
+            Err(ConversionError::OutOfRange("OffsetCommit.schema_version", 40000))
+        );
+    }
+
+    #[test]
+    fn test_definition_mirrors_messages() {
+        for message in [
+            "KonsumerOffsetsData",
+            "OffsetCommit",
+            "GroupMetadata",
+            "MemberMetadata",
+            "ConsumerProtocolSubscription",
+            "TopicPartitions",
+            "ConsumerProtocolAssignment",
+        ] {
+            assert!(PROTO_DEFINITION.contains(&format!("message {message} {{")), "{message}");
+        }
+        assert!(PROTO_DEFINITION.contains("int64 current_state_timestamp = 9;"));
+        assert!(PROTO_DEFINITION.contains("ConsumerProtocolAssignment assignment = 8;"));
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<KonsumerOffsetsData>();
+        is_thread_safe::<OffsetCommit>();
+        is_thread_safe::<GroupMetadata>();
+        is_thread_safe::<MemberMetadata>();
+        is_thread_safe::<ConsumerProtocolSubscription>();
+        is_thread_safe::<TopicPartitions>();
+        is_thread_safe::<ConsumerProtocolAssignment>();
+    }
+}
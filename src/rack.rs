@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::assignor::group_by_topic;
+use crate::group_metadata::{ConsumerProtocolAssignment, GroupMetadata, MemberMetadata, TopicPartitions};
+
+/// Source of the racks where the replicas of topic partitions live, used for rack-aware analysis.
+///
+/// [`crate::ConsumerProtocolSubscription::rack_id`] tells us the rack (or availability zone)
+/// of each consumer, but the racks of the partition replicas are **not** in `__consumer_offsets`:
+/// they have to be fetched from the Kafka cluster (ex. via a `MetadataRequest` and
+/// the `broker.rack` of each Broker), and are supplied by the caller by implementing this trait.
+///
+/// Implementations are provided for:
+///
+/// * `HashMap<(String, i32), String>`: `(topic, partition)` to leader rack
+/// * `HashMap<(String, i32), ReplicaRacks>`: `(topic, partition)` to leader and replica racks
+pub trait PartitionRacks {
+    /// Rack of the leader replica of a topic partition.
+    ///
+    /// Returns `None` if not known.
+    fn leader_rack(&self, topic: &str, partition: i32) -> Option<&str>;
+
+    /// Racks of the replicas a consumer can fetch from (leader included) of a topic partition.
+    ///
+    /// Returns only the [`Self::leader_rack`], if known: this is the default.
+    fn replica_racks(&self, topic: &str, partition: i32) -> Vec<&str> {
+        self.leader_rack(topic, partition).into_iter().collect()
+    }
+}
+
+/// Racks of the replicas of a topic partition.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplicaRacks {
+    /// Rack of the leader replica.
+    pub leader: String,
+
+    /// Racks of the in-sync follower replicas.
+    pub followers: Vec<String>,
+}
+
+impl PartitionRacks for HashMap<(String, i32), String> {
+    fn leader_rack(&self, topic: &str, partition: i32) -> Option<&str> {
+        self.get(&(topic.to_string(), partition)).map(String::as_str)
+    }
+}
+
+impl PartitionRacks for HashMap<(String, i32), ReplicaRacks> {
+    fn leader_rack(&self, topic: &str, partition: i32) -> Option<&str> {
+        self.get(&(topic.to_string(), partition)).map(|r| r.leader.as_str())
+    }
+
+    fn replica_racks(&self, topic: &str, partition: i32) -> Vec<&str> {
+        self.get(&(topic.to_string(), partition))
+            .map(|r| std::iter::once(&r.leader).chain(&r.followers).map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Which replica consumers fetch from.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FetchPolicy {
+    /// Consumers always fetch from the leader: this is Kafka's default.
+    #[default]
+    Leader,
+
+    /// Consumers fetch from a replica in their own rack, if any ([KIP-392]).
+    ///
+    /// This requires Brokers configured with `replica.selector.class=RackAwareReplicaSelector`.
+    ///
+    /// [KIP-392]: https://cwiki.apache.org/confluence/display/KAFKA/KIP-392%3A+Allow+consumers+to+fetch+from+closest+replica
+    ClosestReplica,
+}
+
+/// Whether a consumer fetches a partition from its own rack.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RackLocality {
+    /// The consumer fetches from a replica in its own rack.
+    Local,
+
+    /// The consumer fetches from a replica in another rack.
+    CrossRack,
+
+    /// Either the rack of the consumer, or the racks of the partition replicas, are not known.
+    Unknown,
+}
+
+impl RackLocality {
+    /// Locality of a partition for a consumer in the given rack (empty if not configured).
+    pub fn of(
+        racks: &impl PartitionRacks,
+        policy: FetchPolicy,
+        consumer_rack: &str,
+        topic: &str,
+        partition: i32,
+    ) -> RackLocality {
+        let replica_racks = match policy {
+            FetchPolicy::Leader => racks.leader_rack(topic, partition).into_iter().collect(),
+            FetchPolicy::ClosestReplica => racks.replica_racks(topic, partition),
+        };
+
+        if consumer_rack.is_empty() || replica_racks.is_empty() {
+            RackLocality::Unknown
+        } else if replica_racks.contains(&consumer_rack) {
+            RackLocality::Local
+        } else {
+            RackLocality::CrossRack
+        }
+    }
+}
+
+/// Rack locality of the partitions assigned to a member of a group.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberRackTraffic {
+    /// [`MemberMetadata::id`] of the member.
+    pub member_id: String,
+
+    /// [`MemberMetadata::client_id`] of the member.
+    pub client_id: String,
+
+    /// [`MemberMetadata::client_host`] of the member.
+    pub client_host: String,
+
+    /// [`crate::ConsumerProtocolSubscription::rack_id`] of the member: `None` if not configured.
+    pub rack: Option<String>,
+
+    /// Assigned partitions fetched from the member's own rack.
+    pub local: Vec<TopicPartitions>,
+
+    /// Assigned partitions fetched from another rack.
+    pub cross_rack: Vec<TopicPartitions>,
+
+    /// Assigned partitions of [`RackLocality::Unknown`] locality.
+    pub unknown: Vec<TopicPartitions>,
+}
+
+impl MemberRackTraffic {
+    /// Share of the partitions of known locality that are fetched from another rack.
+    ///
+    /// Returns `None` if the locality of no partition is known.
+    pub fn cross_rack_ratio(&self) -> Option<f64> {
+        let local = count(&self.local);
+        let cross_rack = count(&self.cross_rack);
+        (local + cross_rack > 0).then(|| cross_rack as f64 / (local + cross_rack) as f64)
+    }
+}
+
+/// Rack-aware analysis of the consumption of a group, as found in its [`GroupMetadata`].
+///
+/// Traffic is measured in _number of partitions_: weigh each partition by its throughput
+/// to estimate the cross-rack transfer in bytes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RackAnalysis {
+    /// Group.
+    pub group: String,
+
+    /// [`FetchPolicy`] the analysis assumes.
+    pub policy: FetchPolicy,
+
+    /// Locality of the partitions assigned to each member, in the order members appear in the [`GroupMetadata`].
+    pub members: Vec<MemberRackTraffic>,
+
+    /// Number of assigned partitions fetched from the consumer's own rack, across the group.
+    pub local_partitions: usize,
+
+    /// Number of assigned partitions fetched from another rack, across the group.
+    pub cross_rack_partitions: usize,
+
+    /// Number of assigned partitions of [`RackLocality::Unknown`] locality, across the group.
+    pub unknown_partitions: usize,
+
+    /// Suggested rack-aligned assignment of the same partitions, keyed by member id.
+    ///
+    /// Each partition goes to a member it is local to, when that doesn't leave the assignment unbalanced
+    /// (i.e. no member gets more than its fair share of partitions), preferring the current owner.
+    pub suggested_assignment: BTreeMap<String, ConsumerProtocolAssignment>,
+
+    /// Number of partitions that would still be fetched from another rack with [`Self::suggested_assignment`].
+    pub suggested_cross_rack_partitions: usize,
+}
+
+impl RackAnalysis {
+    /// Analyses the rack locality of the current assignment of the given [`GroupMetadata`].
+    ///
+    /// # Arguments
+    ///
+    /// * `group_metadata` - Group to analyse.
+    /// * `racks` - Racks of the partition replicas (see [`PartitionRacks`]).
+    /// * `policy` - Which replica consumers fetch from.
+    pub fn new(group_metadata: &GroupMetadata, racks: &impl PartitionRacks, policy: FetchPolicy) -> Self {
+        let locality = |member: &MemberMetadata, topic: &str, partition: i32| {
+            RackLocality::of(racks, policy, &member.subscription.rack_id, topic, partition)
+        };
+
+        let members: Vec<MemberRackTraffic> = group_metadata
+            .members
+            .iter()
+            .map(|m| {
+                let mut by_locality: HashMap<RackLocality, Vec<(&str, i32)>> = HashMap::new();
+                for (topic, partition) in assigned(m) {
+                    by_locality.entry(locality(m, topic, partition)).or_default().push((topic, partition));
+                }
+                let mut take = |l| group_by_topic(by_locality.remove(&l).unwrap_or_default());
+
+                MemberRackTraffic {
+                    member_id: m.id.clone(),
+                    client_id: m.client_id.clone(),
+                    client_host: m.client_host.clone(),
+                    rack: Some(m.subscription.rack_id.clone()).filter(|r| !r.is_empty()),
+                    local: take(RackLocality::Local),
+                    cross_rack: take(RackLocality::CrossRack),
+                    unknown: take(RackLocality::Unknown),
+                }
+            })
+            .collect();
+
+        let suggested = suggest(group_metadata, &locality);
+        let suggested_cross_rack_partitions = suggested
+            .iter()
+            .flat_map(|(m, partitions)| partitions.iter().map(move |(t, p)| locality(m, t, *p)))
+            .filter(|l| *l == RackLocality::CrossRack)
+            .count();
+
+        Self {
+            group: group_metadata.group.clone(),
+            policy,
+            local_partitions: members.iter().map(|m| count(&m.local)).sum(),
+            cross_rack_partitions: members.iter().map(|m| count(&m.cross_rack)).sum(),
+            unknown_partitions: members.iter().map(|m| count(&m.unknown)).sum(),
+            members,
+            suggested_assignment: suggested
+                .into_iter()
+                .map(|(m, partitions)| {
+                    let assignment = ConsumerProtocolAssignment {
+                        assigned_topic_partitions: group_by_topic(partitions),
+                        ..Default::default()
+                    };
+                    (m.id.clone(), assignment)
+                })
+                .collect(),
+            suggested_cross_rack_partitions,
+        }
+    }
+
+    /// Share of the assigned partitions of known locality that are fetched from another rack.
+    ///
+    /// Returns `None` if the locality of no partition is known.
+    pub fn cross_rack_ratio(&self) -> Option<f64> {
+        let known = self.local_partitions + self.cross_rack_partitions;
+        (known > 0).then(|| self.cross_rack_partitions as f64 / known as f64)
+    }
+}
+
+fn count(topic_partitions: &[TopicPartitions]) -> usize {
+    topic_partitions.iter().map(|tp| tp.partitions.len()).sum()
+}
+
+fn assigned(member: &MemberMetadata) -> impl Iterator<Item = (&str, i32)> {
+    member
+        .assignment
+        .assigned_topic_partitions
+        .iter()
+        .flat_map(|tp| tp.partitions.iter().map(|p| (tp.topic.as_str(), *p)))
+}
+
+/// Greedy rack-aligned assignment of the partitions currently assigned to the group.
+///
+/// Partitions with the fewest local candidates are placed first; each goes to the least loaded
+/// local candidate below the fair share (the current owner first), or else to the least loaded subscribed member.
+fn suggest<'a>(
+    group_metadata: &'a GroupMetadata,
+    locality: &impl Fn(&MemberMetadata, &str, i32) -> RackLocality,
+) -> Vec<(&'a MemberMetadata, Vec<(&'a str, i32)>)> {
+    let members = &group_metadata.members;
+    let mut partitions: Vec<((&str, i32), usize)> =
+        members.iter().enumerate().flat_map(|(i, m)| assigned(m).map(move |tp| (tp, i))).collect();
+    partitions.sort_unstable();
+    partitions.dedup_by_key(|(tp, _)| *tp);
+
+    let member_count = members.len().max(1);
+    let fair_share = (partitions.len() + member_count - 1) / member_count;
+    let candidates = |topic: &str| -> Vec<usize> {
+        (0..members.len()).filter(|i| members[*i].subscription.subscribed_topics.iter().any(|t| t == topic)).collect()
+    };
+    let local_candidates = |(topic, partition): (&str, i32)| -> Vec<usize> {
+        candidates(topic)
+            .into_iter()
+            .filter(|i| locality(&members[*i], topic, partition) == RackLocality::Local)
+            .collect()
+    };
+    partitions.sort_by_cached_key(|(tp, _)| local_candidates(*tp).len());
+
+    let mut suggested: Vec<Vec<(&str, i32)>> = vec![Vec::new(); members.len()];
+    for (tp, owner) in partitions {
+        // Members subscribed to the topic; the current owner, if it isn't any more
+        let mut all = candidates(tp.0);
+        if all.is_empty() {
+            all.push(owner);
+        }
+        let pick = |choices: &[usize]| {
+            choices
+                .iter()
+                .filter(|i| suggested[**i].len() < fair_share)
+                .min_by_key(|i| (**i != owner, suggested[**i].len(), **i))
+                .copied()
+        };
+
+        let chosen = pick(&local_candidates(tp))
+            .or_else(|| pick(&all))
+            .or_else(|| all.iter().min_by_key(|i| suggested[**i].len()).copied())
+            .unwrap();
+        suggested[chosen].push(tp);
+    }
+
+    members.iter().zip(suggested).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{group, is_thread_safe, member};
+
+    fn rack_member(id: &str, rack: &str, assigned: &[i32]) -> MemberMetadata {
+        let mut m = member(id, &["t1"], &[("t1", assigned)]);
+        m.subscription.rack_id = rack.to_string();
+        m
+    }
+
+    fn tps(partitions: &[i32]) -> Vec<TopicPartitions> {
+        vec![TopicPartitions {
+            topic: "t1".to_string(),
+            partitions: partitions.to_vec(),
+        }]
+    }
+
+    /// Partitions `0..4` of `t1`, led alternately from `az-a` and `az-b`, replicated in the other one.
+    fn racks() -> HashMap<(String, i32), ReplicaRacks> {
+        (0..4)
+            .map(|p| {
+                let (leader, follower) = if p % 2 == 0 {
+                    ("az-a", "az-b")
+                } else {
+                    ("az-b", "az-a")
+                };
+                let racks = ReplicaRacks {
+                    leader: leader.to_string(),
+                    followers: vec![follower.to_string()],
+                };
+                (("t1".to_string(), p), racks)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_leader_fetching() {
+        let gm = group("g1", vec![rack_member("a", "az-a", &[0, 1]), rack_member("b", "az-b", &[2, 3])]);
+        let analysis = RackAnalysis::new(&gm, &racks(), FetchPolicy::Leader);
+
+        assert_eq!(analysis.members[0].rack.as_deref(), Some("az-a"));
+        assert_eq!(analysis.members[0].local, tps(&[0]));
+        assert_eq!(analysis.members[0].cross_rack, tps(&[1]));
+        assert_eq!(analysis.members[1].local, tps(&[3]));
+        assert_eq!(analysis.members[1].cross_rack_ratio(), Some(0.5));
+        assert_eq!((analysis.local_partitions, analysis.cross_rack_partitions), (2, 2));
+        assert_eq!(analysis.cross_rack_ratio(), Some(0.5));
+
+        // Suggestion aligns partitions to the rack of their leader
+        assert_eq!(analysis.suggested_assignment["a"].assigned_topic_partitions, tps(&[0, 2]));
+        assert_eq!(analysis.suggested_assignment["b"].assigned_topic_partitions, tps(&[1, 3]));
+        assert_eq!(analysis.suggested_cross_rack_partitions, 0);
+    }
+
+    #[test]
+    fn test_closest_replica_fetching() {
+        let gm = group("g1", vec![rack_member("a", "az-a", &[0, 1]), rack_member("b", "az-b", &[2, 3])]);
+        let analysis = RackAnalysis::new(&gm, &racks(), FetchPolicy::ClosestReplica);
+
+        assert_eq!(analysis.cross_rack_partitions, 0);
+        assert_eq!(analysis.cross_rack_ratio(), Some(0.0));
+
+        // Everything is local already: the suggestion keeps the current owners
+        assert_eq!(analysis.suggested_assignment["a"].assigned_topic_partitions, tps(&[0, 1]));
+        assert_eq!(analysis.suggested_assignment["b"].assigned_topic_partitions, tps(&[2, 3]));
+    }
+
+    #[test]
+    fn test_unknown_locality() {
+        let leaders: HashMap<(String, i32), String> = HashMap::from([(("t1".to_string(), 0), "az-a".to_string())]);
+        let gm = group("g1", vec![rack_member("a", "", &[0]), rack_member("b", "az-b", &[0, 1])]);
+        let analysis = RackAnalysis::new(&gm, &leaders, FetchPolicy::ClosestReplica);
+
+        assert_eq!(analysis.members[0].rack, None);
+        assert_eq!(analysis.members[0].unknown, tps(&[0]));
+        assert_eq!(analysis.members[0].cross_rack_ratio(), None);
+        assert_eq!(analysis.members[1].cross_rack, tps(&[0]));
+        assert_eq!(analysis.members[1].unknown, tps(&[1]));
+        assert_eq!(analysis.unknown_partitions, 2);
+
+        // Partitions are not duplicated in the suggestion
+        let suggested: usize =
+            analysis.suggested_assignment.values().map(|a| count(&a.assigned_topic_partitions)).sum();
+        assert_eq!(suggested, 2);
+    }
+
+    #[test]
+    fn test_suggestion_stays_balanced() {
+        // All partitions are led from az-a, where only one member is
+        let leaders: HashMap<(String, i32), String> =
+            (0..4).map(|p| (("t1".to_string(), p), "az-a".to_string())).collect();
+        let gm = group("g1", vec![rack_member("a", "az-b", &[0, 1]), rack_member("b", "az-a", &[2, 3])]);
+        let analysis = RackAnalysis::new(&gm, &leaders, FetchPolicy::Leader);
+
+        assert_eq!(analysis.cross_rack_partitions, 2);
+        assert_eq!(analysis.suggested_cross_rack_partitions, 2);
+        for assignment in analysis.suggested_assignment.values() {
+            assert_eq!(count(&assignment.assigned_topic_partitions), 2);
+        }
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ReplicaRacks>();
+        is_thread_safe::<FetchPolicy>();
+        is_thread_safe::<RackLocality>();
+        is_thread_safe::<MemberRackTraffic>();
+        is_thread_safe::<RackAnalysis>();
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::consumer_health::CommitSample;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// Configuration of [`ConsumptionRateTracker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsumptionRateConfig {
+    /// Length of the sliding window rates are computed over.
+    pub window_ms: i64,
+
+    /// Minimum number of intervals between commits, to infer the [`CommitMode`].
+    pub min_commit_intervals: usize,
+
+    /// Maximum _coefficient of variation_ (i.e. standard deviation over mean) of the intervals between
+    /// commits, for them to be considered regular, and so produced by auto-commit.
+    pub auto_commit_max_variation: f64,
+}
+
+impl Default for ConsumptionRateConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 5 * 60 * 1000,
+            min_commit_intervals: 3,
+            auto_commit_max_variation: 0.1,
+        }
+    }
+}
+
+/// How a consumer commits offsets, inferred from the intervals between its commits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommitMode {
+    /// Commits arrive at a regular interval: the consumer uses `enable.auto.commit`,
+    /// with an `auto.commit.interval.ms` close to the one reported.
+    Auto {
+        /// Average interval between commits, in milliseconds.
+        interval_ms: f64,
+    },
+
+    /// Commits arrive at irregular intervals: the consumer commits manually.
+    Manual,
+
+    /// Not enough commits to tell.
+    Unknown,
+}
+
+/// Statistics of the intervals between the commits of a partition, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitIntervals {
+    /// Number of intervals (i.e. commits minus one).
+    pub count: usize,
+
+    /// Average interval.
+    pub mean_ms: f64,
+
+    /// Standard deviation of the intervals.
+    pub stddev_ms: f64,
+}
+
+impl CommitIntervals {
+    /// Commits per second.
+    pub fn commits_per_sec(&self) -> f64 {
+        if self.mean_ms > 0.0 {
+            1000.0 / self.mean_ms
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Consumption rate of a consumer group on a single topic partition.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionRate {
+    /// Topic the partition belongs to.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Messages processed per second over the window: `None` if less than 2 commits fall in it.
+    ///
+    /// Only forward movements of the committed offset count: offsets moving backwards
+    /// (ex. after a reset) are not "negative consumption".
+    pub messages_per_sec: Option<f64>,
+
+    /// Intervals between the commits in the window: `None` if less than 2 commits fall in it.
+    pub commit_intervals: Option<CommitIntervals>,
+
+    /// How the consumer commits offsets for this partition.
+    pub commit_mode: CommitMode,
+}
+
+/// Consumption rate of a consumer group on a topic.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopicRate {
+    /// Topic.
+    pub topic: String,
+
+    /// Sum of the [`PartitionRate::messages_per_sec`] of [`Self::partitions`] (where known).
+    pub messages_per_sec: f64,
+
+    /// Rate of each partition of the topic, sorted by partition.
+    pub partitions: Vec<PartitionRate>,
+}
+
+/// Consumption rate of a consumer group.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupRate {
+    /// Group.
+    pub group: String,
+
+    /// Sum of the [`TopicRate::messages_per_sec`] of [`Self::topics`].
+    pub messages_per_sec: f64,
+
+    /// How the group commits offsets: [`CommitMode::Auto`] only if all its partitions of known
+    /// [`PartitionRate::commit_mode`] are, with the average of their intervals.
+    pub commit_mode: CommitMode,
+
+    /// Rate of each topic the group has committed offsets for, sorted by topic.
+    pub topics: Vec<TopicRate>,
+}
+
+/// Tracks the consumption rate of consumer groups, from consecutive [`OffsetCommit`]s.
+///
+/// For each `(group, topic, partition)`, it retains the commits that fall in a sliding window
+/// (see [`ConsumptionRateConfig::window_ms`]): how far the committed offset moves in that time is
+/// the rate at which the group processes messages. The regularity of the commits tells apart
+/// consumers that use auto-commit from those that commit manually (see [`CommitMode`]).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConsumptionRateTracker {
+    config: ConsumptionRateConfig,
+    commits: BTreeMap<String, BTreeMap<String, BTreeMap<i32, VecDeque<CommitSample>>>>,
+}
+
+impl ConsumptionRateTracker {
+    /// Create a [`Self`] with the given [`ConsumptionRateConfig`].
+    pub fn new(config: ConsumptionRateConfig) -> Self {
+        Self {
+            config,
+            commits: BTreeMap::new(),
+        }
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    ///
+    /// Commits are expected in chronological order: those older than the window are discarded.
+    /// If the [`OffsetCommit`] is a tombstone, the commits for its `(group, topic, partition)`
+    /// are forgotten.
+    pub fn update(&mut self, offset_commit: &OffsetCommit) {
+        if offset_commit.is_tombstone {
+            if let Some(topics) = self.commits.get_mut(&offset_commit.group) {
+                if let Some(partitions) = topics.get_mut(&offset_commit.topic) {
+                    partitions.remove(&offset_commit.partition);
+                    if partitions.is_empty() {
+                        topics.remove(&offset_commit.topic);
+                    }
+                }
+                if topics.is_empty() {
+                    self.commits.remove(&offset_commit.group);
+                }
+            }
+            return;
+        }
+
+        let window = self
+            .commits
+            .entry(offset_commit.group.clone())
+            .or_default()
+            .entry(offset_commit.topic.clone())
+            .or_default()
+            .entry(offset_commit.partition)
+            .or_default();
+
+        let commit_timestamp = timestamp_millis(&offset_commit.commit_timestamp);
+        while window.front().is_some_and(|c| c.commit_timestamp < commit_timestamp - self.config.window_ms) {
+            window.pop_front();
+        }
+        window.push_back(CommitSample {
+            offset: offset_commit.offset,
+            commit_timestamp,
+        });
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::OffsetCommit`] is relevant: other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        if let KonsumerOffsetsData::OffsetCommit(offset_commit) = data {
+            self.update(offset_commit);
+        }
+    }
+
+    /// Computes the [`GroupRate`] of the given group, over the window ending at `now_ms`.
+    ///
+    /// Returns `None` if the group has no commits.
+    pub fn group_rate(&self, group: &str, now_ms: i64) -> Option<GroupRate> {
+        let topics: Vec<TopicRate> = self
+            .commits
+            .get(group)?
+            .iter()
+            .map(|(topic, partitions)| {
+                let partitions: Vec<PartitionRate> =
+                    partitions.iter().map(|(p, commits)| self.partition_rate(topic, *p, commits, now_ms)).collect();
+
+                TopicRate {
+                    topic: topic.clone(),
+                    messages_per_sec: partitions.iter().filter_map(|p| p.messages_per_sec).sum(),
+                    partitions,
+                }
+            })
+            .collect();
+
+        let modes: Vec<CommitMode> = topics
+            .iter()
+            .flat_map(|t| &t.partitions)
+            .map(|p| p.commit_mode)
+            .filter(|m| *m != CommitMode::Unknown)
+            .collect();
+        let commit_mode = if modes.is_empty() {
+            CommitMode::Unknown
+        } else if modes.contains(&CommitMode::Manual) {
+            CommitMode::Manual
+        } else {
+            let intervals = modes.iter().filter_map(|m| match m {
+                CommitMode::Auto {
+                    interval_ms,
+                } => Some(interval_ms),
+                _ => None,
+            });
+            CommitMode::Auto {
+                interval_ms: intervals.sum::<f64>() / modes.len() as f64,
+            }
+        };
+
+        Some(GroupRate {
+            group: group.to_string(),
+            messages_per_sec: topics.iter().map(|t| t.messages_per_sec).sum(),
+            commit_mode,
+            topics,
+        })
+    }
+
+    /// Computes the [`GroupRate`] of all the groups in [`Self`], over the window ending at `now_ms`.
+    pub fn rates(&self, now_ms: i64) -> Vec<GroupRate> {
+        self.commits.keys().filter_map(|group| self.group_rate(group, now_ms)).collect()
+    }
+
+    fn partition_rate(
+        &self,
+        topic: &str,
+        partition: i32,
+        commits: &VecDeque<CommitSample>,
+        now_ms: i64,
+    ) -> PartitionRate {
+        let in_window: Vec<&CommitSample> =
+            commits.iter().filter(|c| c.commit_timestamp >= now_ms - self.config.window_ms).collect();
+        let pairs = || in_window.iter().zip(in_window.iter().skip(1));
+
+        let (messages_per_sec, commit_intervals) = match (in_window.first(), in_window.last()) {
+            (Some(first), Some(last)) if in_window.len() > 1 => {
+                let elapsed_ms = last.commit_timestamp - first.commit_timestamp;
+                let consumed: i64 = pairs().map(|(a, b)| (b.offset - a.offset).max(0)).sum();
+                let rate = if elapsed_ms > 0 {
+                    consumed as f64 * 1000.0 / elapsed_ms as f64
+                } else {
+                    0.0
+                };
+
+                let count = in_window.len() - 1;
+                let mean_ms = elapsed_ms as f64 / count as f64;
+                let variance = pairs()
+                    .map(|(a, b)| ((b.commit_timestamp - a.commit_timestamp) as f64 - mean_ms).powi(2))
+                    .sum::<f64>()
+                    / count as f64;
+                let intervals = CommitIntervals {
+                    count,
+                    mean_ms,
+                    stddev_ms: variance.sqrt(),
+                };
+                (Some(rate), Some(intervals))
+            },
+            _ => (None, None),
+        };
+
+        let commit_mode = match commit_intervals {
+            Some(i) if i.count >= self.config.min_commit_intervals && i.mean_ms > 0.0 => {
+                if i.stddev_ms / i.mean_ms <= self.config.auto_commit_max_variation {
+                    CommitMode::Auto {
+                        interval_ms: i.mean_ms,
+                    }
+                } else {
+                    CommitMode::Manual
+                }
+            },
+            _ => CommitMode::Unknown,
+        };
+
+        PartitionRate {
+            topic: topic.to_string(),
+            partition,
+            messages_per_sec,
+            commit_intervals,
+            commit_mode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{commit, is_thread_safe};
+
+    const SECOND: i64 = 1000;
+
+    #[test]
+    fn test_auto_commit() {
+        let mut tracker = ConsumptionRateTracker::default();
+        for i in 0..6 {
+            tracker.update(&commit("g1", "t1", 0, i * 500, i * 5 * SECOND));
+            tracker.update(&commit("g1", "t1", 1, i * 100, i * 5 * SECOND));
+        }
+
+        let rate = tracker.group_rate("g1", 25 * SECOND).unwrap();
+        assert_eq!(rate.messages_per_sec, 120.0);
+        assert_eq!(
+            rate.commit_mode,
+            CommitMode::Auto {
+                interval_ms: 5000.0
+            }
+        );
+
+        let partition = &rate.topics[0].partitions[0];
+        assert_eq!(partition.messages_per_sec, Some(100.0));
+        let intervals = partition.commit_intervals.unwrap();
+        assert_eq!((intervals.count, intervals.stddev_ms), (5, 0.0));
+        assert_eq!(intervals.commits_per_sec(), 0.2);
+    }
+
+    #[test]
+    fn test_manual_commit() {
+        let mut tracker = ConsumptionRateTracker::default();
+        for (offset, ts) in [(0, 0), (10, SECOND), (20, 7 * SECOND), (30, 8 * SECOND), (25, 20 * SECOND)] {
+            tracker.update(&commit("g1", "t1", 0, offset, ts));
+        }
+
+        let rate = &tracker.rates(20 * SECOND)[0];
+        assert_eq!(rate.commit_mode, CommitMode::Manual);
+        // Offsets moving backwards don't count
+        assert_eq!(rate.messages_per_sec, 1.5);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let mut tracker = ConsumptionRateTracker::new(ConsumptionRateConfig {
+            window_ms: 10 * SECOND,
+            ..Default::default()
+        });
+        for (offset, ts) in [(0, 0), (1000, 5 * SECOND), (1010, 15 * SECOND), (1020, 20 * SECOND)] {
+            tracker.update(&commit("g1", "t1", 0, offset, ts));
+        }
+
+        let partition = &tracker.group_rate("g1", 20 * SECOND).unwrap().topics[0].partitions[0];
+        assert_eq!(partition.messages_per_sec, Some(2.0));
+        assert_eq!(partition.commit_mode, CommitMode::Unknown);
+
+        // Only one commit left in the window
+        let rate = tracker.group_rate("g1", 29 * SECOND).unwrap();
+        assert_eq!(rate.topics[0].partitions[0].messages_per_sec, None);
+        assert_eq!(rate.messages_per_sec, 0.0);
+        assert_eq!(rate.commit_mode, CommitMode::Unknown);
+
+        tracker.update_from(&KonsumerOffsetsData::OffsetCommit(OffsetCommit {
+            is_tombstone: true,
+            ..commit("g1", "t1", 0, 0, 0)
+        }));
+        assert_eq!(tracker.group_rate("g1", 20 * SECOND), None);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<ConsumptionRateConfig>();
+        is_thread_safe::<CommitMode>();
+        is_thread_safe::<CommitIntervals>();
+        is_thread_safe::<PartitionRate>();
+        is_thread_safe::<TopicRate>();
+        is_thread_safe::<GroupRate>();
+        is_thread_safe::<ConsumptionRateTracker>();
+    }
+}
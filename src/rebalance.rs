@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::group_metadata::{GroupMetadata, MemberMetadata};
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::utils::timestamp_millis;
+
+/// A change observed between two successive [`GroupMetadata`] snapshots of the same group.
+///
+/// Members are identified by their [`MemberMetadata::id`]; leader and protocol are `None`
+/// when not set (i.e. when the group has no members).
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RebalanceEvent {
+    /// A member left the group.
+    MemberLeft {
+        /// [`MemberMetadata::id`] of the member.
+        member_id: String,
+        /// [`MemberMetadata::client_id`] of the member.
+        client_id: String,
+        /// [`MemberMetadata::client_host`] of the member.
+        client_host: String,
+    },
+
+    /// A member joined the group.
+    MemberJoined {
+        /// [`MemberMetadata::id`] of the member.
+        member_id: String,
+        /// [`MemberMetadata::client_id`] of the member.
+        client_id: String,
+        /// [`MemberMetadata::client_host`] of the member.
+        client_host: String,
+    },
+
+    /// The [`GroupMetadata::leader`] changed.
+    LeaderChanged {
+        /// Previous leader.
+        from: Option<String>,
+        /// New leader.
+        to: Option<String>,
+    },
+
+    /// The [`GroupMetadata::protocol`] (i.e. the assignor) changed.
+    ProtocolChanged {
+        /// Previous protocol.
+        from: Option<String>,
+        /// New protocol.
+        to: Option<String>,
+    },
+
+    /// Partitions of a topic moved from one member to another.
+    PartitionsMoved {
+        /// Topic the partitions belong to.
+        topic: String,
+        /// Partitions that moved, sorted.
+        partitions: Vec<i32>,
+        /// Member the partitions were assigned to: `None` if they were not assigned.
+        from: Option<String>,
+        /// Member the partitions are now assigned to: `None` if they are no longer assigned.
+        to: Option<String>,
+    },
+
+    /// The group had members, and now has none.
+    GroupEmptied,
+}
+
+/// Differences between two successive [`GroupMetadata`] snapshots of the same group.
+///
+/// Created by [`GroupRebalance::diff`], or by a [`RebalanceTracker`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupRebalance {
+    /// Group.
+    pub group: String,
+
+    /// [`GroupMetadata::generation`] of the previous snapshot.
+    pub from_generation: i32,
+
+    /// [`GroupMetadata::generation`] of the new snapshot.
+    pub to_generation: i32,
+
+    /// Events that explain the differences between the snapshots, in this order:
+    /// members left, members joined, leader and protocol changes, partition moves and group emptied.
+    pub events: Vec<RebalanceEvent>,
+
+    /// When the group transitioned to its new state (milliseconds since Unix Epoch), if known.
+    ///
+    /// This is the [`GroupMetadata::current_state_timestamp`] of the new snapshot:
+    /// it's known only from schema version `2`.
+    pub completed_at: Option<i64>,
+
+    /// Time between the state transitions recorded by the two snapshots (milliseconds), if known.
+    ///
+    /// This is the difference of their [`GroupMetadata::current_state_timestamp`], and it's **not**
+    /// the duration of the rebalance: the [Group Coordinator] doesn't record when a rebalance starts.
+    /// For a group that was stable for a day, and then rebalanced, this is about a day.
+    ///
+    /// [Group Coordinator]: https://github.com/apache/kafka/blob/trunk/core/src/main/scala/kafka/coordinator/group/GroupCoordinator.scala
+    pub since_previous_state_ms: Option<i64>,
+}
+
+impl GroupRebalance {
+    /// Compares two successive [`GroupMetadata`] snapshots of the same group.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Previous snapshot.
+    /// * `to` - New snapshot.
+    pub fn diff(from: &GroupMetadata, to: &GroupMetadata) -> Self {
+        let mut events = Vec::new();
+
+        let from_ids: HashSet<&str> = from.members.iter().map(|m| m.id.as_str()).collect();
+        let to_ids: HashSet<&str> = to.members.iter().map(|m| m.id.as_str()).collect();
+        events.extend(from.members.iter().filter(|m| !to_ids.contains(m.id.as_str())).map(|m| {
+            RebalanceEvent::MemberLeft {
+                member_id: m.id.clone(),
+                client_id: m.client_id.clone(),
+                client_host: m.client_host.clone(),
+            }
+        }));
+        events.extend(to.members.iter().filter(|m| !from_ids.contains(m.id.as_str())).map(|m| {
+            RebalanceEvent::MemberJoined {
+                member_id: m.id.clone(),
+                client_id: m.client_id.clone(),
+                client_host: m.client_host.clone(),
+            }
+        }));
+
+        if from.leader != to.leader {
+            events.push(RebalanceEvent::LeaderChanged {
+                from: non_empty(&from.leader),
+                to: non_empty(&to.leader),
+            });
+        }
+
+        if from.protocol != to.protocol {
+            events.push(RebalanceEvent::ProtocolChanged {
+                from: non_empty(&from.protocol),
+                to: non_empty(&to.protocol),
+            });
+        }
+
+        events.extend(partitions_moved(&from.members, &to.members));
+
+        if !from.members.is_empty() && to.members.is_empty() {
+            events.push(RebalanceEvent::GroupEmptied);
+        }
+
+        let from_ts = (from.schema_version >= 2).then(|| timestamp_millis(&from.current_state_timestamp));
+        let to_ts = (to.schema_version >= 2).then(|| timestamp_millis(&to.current_state_timestamp));
+
+        Self {
+            group: to.group.clone(),
+            from_generation: from.generation,
+            to_generation: to.generation,
+            events,
+            completed_at: to_ts,
+            since_previous_state_ms: from_ts.zip(to_ts).map(|(from, to)| to - from).filter(|d| *d >= 0),
+        }
+    }
+
+    /// Whether the group went through (at least) one rebalance, i.e. its generation changed.
+    pub fn is_rebalance(&self) -> bool {
+        self.from_generation != self.to_generation
+    }
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// Owner of each `(topic, partition)` assigned to the given members.
+fn owners(members: &[MemberMetadata]) -> HashMap<(&str, i32), &str> {
+    members
+        .iter()
+        .flat_map(|m| {
+            m.assignment
+                .assigned_topic_partitions
+                .iter()
+                .flat_map(move |tp| tp.partitions.iter().map(move |p| ((tp.topic.as_str(), *p), m.id.as_str())))
+        })
+        .collect()
+}
+
+/// Partitions of a topic that moved from a member (if any) to another (if any).
+type Move<'a> = (&'a str, Option<&'a str>, Option<&'a str>);
+
+fn partitions_moved(from: &[MemberMetadata], to: &[MemberMetadata]) -> Vec<RebalanceEvent> {
+    let from_owners = owners(from);
+    let to_owners = owners(to);
+
+    // Grouped by (topic, from, to), so that events are sorted deterministically
+    let mut moves: BTreeMap<Move, Vec<i32>> = BTreeMap::new();
+    for (&(topic, partition), &from_owner) in &from_owners {
+        let to_owner = to_owners.get(&(topic, partition)).copied();
+        if to_owner != Some(from_owner) {
+            moves.entry((topic, Some(from_owner), to_owner)).or_default().push(partition);
+        }
+    }
+    for (&(topic, partition), &to_owner) in &to_owners {
+        if !from_owners.contains_key(&(topic, partition)) {
+            moves.entry((topic, None, Some(to_owner))).or_default().push(partition);
+        }
+    }
+
+    moves
+        .into_iter()
+        .map(|((topic, from, to), mut partitions)| {
+            partitions.sort_unstable();
+            RebalanceEvent::PartitionsMoved {
+                topic: topic.to_string(),
+                partitions,
+                from: from.map(str::to_string),
+                to: to.map(str::to_string),
+            }
+        })
+        .collect()
+}
+
+/// Tracks the latest [`GroupMetadata`] of every group, and reports how each new one differs from it.
+///
+/// This is built by feeding it the [`GroupMetadata`]s read out of `__consumer_offsets`, in the
+/// order they were read. A _tombstone_ forgets the group.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct RebalanceTracker {
+    latest: HashMap<String, GroupMetadata>,
+}
+
+impl RebalanceTracker {
+    /// Create an empty [`Self`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates [`Self`] with a [`GroupMetadata`].
+    ///
+    /// Returns how it differs from the previous snapshot of the same group: `None` if this is the
+    /// first snapshot of the group, if it's a tombstone, or if nothing changed.
+    pub fn update(&mut self, group_metadata: &GroupMetadata) -> Option<GroupRebalance> {
+        if group_metadata.is_tombstone {
+            self.latest.remove(&group_metadata.group);
+            return None;
+        }
+
+        let previous = self.latest.insert(group_metadata.group.clone(), group_metadata.clone())?;
+        let rebalance = GroupRebalance::diff(&previous, group_metadata);
+        (rebalance.is_rebalance() || !rebalance.events.is_empty()).then_some(rebalance)
+    }
+
+    /// Updates [`Self`] with a [`KonsumerOffsetsData`]: only [`GroupMetadata`] is relevant.
+    ///
+    /// See [`Self::update`].
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) -> Option<GroupRebalance> {
+        match data {
+            KonsumerOffsetsData::GroupMetadata(gm) => self.update(gm),
+            KonsumerOffsetsData::OffsetCommit(_) => None,
+        }
+    }
+
+    /// Latest (non tombstone) [`GroupMetadata`] of the given group, if any.
+    pub fn get(&self, group: &str) -> Option<&GroupMetadata> {
+        self.latest.get(group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{group, is_thread_safe, member, timestamp_from_millis};
+
+    fn snapshot(
+        generation: i32,
+        leader: &str,
+        current_state_timestamp: i64,
+        members: Vec<MemberMetadata>,
+    ) -> GroupMetadata {
+        GroupMetadata {
+            schema_version: 3,
+            protocol: if members.is_empty() {
+                ""
+            } else {
+                "range"
+            }
+            .to_string(),
+            generation,
+            leader: leader.to_string(),
+            current_state_timestamp: timestamp_from_millis(current_state_timestamp),
+            ..group("g1", members)
+        }
+    }
+
+    #[test]
+    fn test_member_joined() {
+        let from = snapshot(1, "a", 1_000, vec![member("a", &["t1"], &[("t1", &[0, 1, 2, 3])])]);
+        let to = snapshot(
+            2,
+            "a",
+            4_500,
+            vec![member("a", &["t1"], &[("t1", &[0, 1])]), member("b", &["t1"], &[("t1", &[2, 3])])],
+        );
+
+        let rebalance = GroupRebalance::diff(&from, &to);
+        assert!(rebalance.is_rebalance());
+        assert_eq!(rebalance.completed_at, Some(4_500));
+        assert_eq!(rebalance.since_previous_state_ms, Some(3_500));
+        assert_eq!(
+            rebalance.events,
+            vec![
+                RebalanceEvent::MemberJoined {
+                    member_id: "b".to_string(),
+                    client_id: "client-b".to_string(),
+                    client_host: "/10.0.0.1".to_string(),
+                },
+                RebalanceEvent::PartitionsMoved {
+                    topic: "t1".to_string(),
+                    partitions: vec![2, 3],
+                    from: Some("a".to_string()),
+                    to: Some("b".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leader_left() {
+        let from =
+            snapshot(5, "a", 0, vec![member("a", &["t1"], &[("t1", &[0])]), member("b", &["t1"], &[("t1", &[1])])]);
+        let mut to = snapshot(6, "b", 0, vec![member("b", &["t1"], &[("t1", &[0, 1])])]);
+        to.protocol = "roundrobin".to_string();
+
+        let events = GroupRebalance::diff(&from, &to).events;
+        assert_eq!(events.len(), 4);
+        assert!(matches!(&events[0], RebalanceEvent::MemberLeft { member_id, .. } if member_id == "a"));
+        assert_eq!(
+            events[1],
+            RebalanceEvent::LeaderChanged {
+                from: Some("a".to_string()),
+                to: Some("b".to_string()),
+            }
+        );
+        assert_eq!(
+            events[2],
+            RebalanceEvent::ProtocolChanged {
+                from: Some("range".to_string()),
+                to: Some("roundrobin".to_string()),
+            }
+        );
+        assert!(matches!(&events[3], RebalanceEvent::PartitionsMoved { partitions, to, .. }
+            if partitions == &vec![0] && to.as_deref() == Some("b")));
+    }
+
+    #[test]
+    fn test_group_emptied() {
+        let from = snapshot(3, "a", 0, vec![member("a", &["t1"], &[("t1", &[0])])]);
+        let to = snapshot(4, "", 0, vec![]);
+
+        let events = GroupRebalance::diff(&from, &to).events;
+        assert_eq!(events.last(), Some(&RebalanceEvent::GroupEmptied));
+        assert!(events.contains(&RebalanceEvent::PartitionsMoved {
+            topic: "t1".to_string(),
+            partitions: vec![0],
+            from: Some("a".to_string()),
+            to: None,
+        }));
+        assert!(events.contains(&RebalanceEvent::LeaderChanged {
+            from: Some("a".to_string()),
+            to: None,
+        }));
+    }
+
+    #[test]
+    fn test_tracker() {
+        let mut tracker = RebalanceTracker::new();
+        let g1 = snapshot(1, "a", 0, vec![member("a", &["t1"], &[("t1", &[0])])]);
+
+        assert_eq!(tracker.update_from(&KonsumerOffsetsData::GroupMetadata(g1.clone())), None);
+        assert_eq!(tracker.update(&g1), None);
+
+        let g2 = snapshot(2, "a", 10, vec![member("a", &["t1"], &[("t1", &[0])])]);
+        let rebalance = tracker.update(&g2).unwrap();
+        assert!(rebalance.events.is_empty());
+        assert_eq!((rebalance.from_generation, rebalance.to_generation), (1, 2));
+
+        let mut tombstone = g2.clone();
+        tombstone.is_tombstone = true;
+        assert_eq!(tracker.update(&tombstone), None);
+        assert_eq!(tracker.get("g1"), None);
+    }
+
+    #[test]
+    fn test_stable_then_rebalance() {
+        let day_ms = 24 * 60 * 60 * 1000;
+        let from = snapshot(1, "a", 0, vec![member("a", &["t1"], &[("t1", &[0, 1])])]);
+        let to = snapshot(
+            2,
+            "a",
+            day_ms + 200,
+            vec![member("a", &["t1"], &[("t1", &[0])]), member("b", &["t1"], &[("t1", &[1])])],
+        );
+
+        // The previous state lasted a day: that's not how long the rebalance took.
+        let rebalance = GroupRebalance::diff(&from, &to);
+        assert_eq!(rebalance.completed_at, Some(day_ms + 200));
+        assert_eq!(rebalance.since_previous_state_ms, Some(day_ms + 200));
+    }
+
+    #[test]
+    fn test_unknown_state_timestamps_before_schema_v2() {
+        let mut from = snapshot(1, "a", 0, vec![]);
+        from.schema_version = 1;
+        let to = snapshot(2, "a", 100, vec![]);
+
+        let rebalance = GroupRebalance::diff(&from, &to);
+        assert_eq!(rebalance.completed_at, Some(100));
+        assert_eq!(rebalance.since_previous_state_ms, None);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<RebalanceEvent>();
+        is_thread_safe::<GroupRebalance>();
+        is_thread_safe::<RebalanceTracker>();
+    }
+}
@@ -0,0 +1,65 @@
+use crate::errors::KonsumerOffsetsError;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+
+/// A [`KonsumerOffsetsData`], together with the coordinates of the record it was parsed from.
+///
+/// [`KonsumerOffsetsData`] only describes the _content_ of a record of `__consumer_offsets`:
+/// many analysis need to also know _where_ (partition and offset) and _when_ (timestamp)
+/// it was read from. This _envelope_ carries both.
+///
+/// **NOTE:** [`Self::timestamp`] is milliseconds since Unix Epoch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "json_schema", derive(schemars::JsonSchema))]
+pub struct KonsumerOffsetsRecord {
+    /// Partition of `__consumer_offsets` the record was read from.
+    pub partition: i32,
+
+    /// Offset of the record in [`Self::partition`].
+    pub offset: i64,
+
+    /// Timestamp of the record.
+    pub timestamp: i64,
+
+    /// Data parsed from the key and payload of the record.
+    pub data: KonsumerOffsetsData,
+}
+
+impl KonsumerOffsetsRecord {
+    /// Create [`Self`] by parsing the key and payload of a record, read at the given coordinates.
+    ///
+    /// See [`KonsumerOffsetsData::try_from_bytes`] for details about parsing.
+    ///
+    /// # Arguments
+    ///
+    /// * `partition` - Partition of `__consumer_offsets` the record was read from.
+    /// * `offset` - Offset of the record.
+    /// * `timestamp` - Timestamp of the record (milliseconds since Unix Epoch).
+    /// * `key` - Key of the record.
+    /// * `payload` - Payload of the record: `None` if the record is a tombstone.
+    pub fn try_from_bytes(
+        partition: i32,
+        offset: i64,
+        timestamp: i64,
+        key: Option<&[u8]>,
+        payload: Option<&[u8]>,
+    ) -> Result<Self, KonsumerOffsetsError> {
+        Ok(Self {
+            partition,
+            offset,
+            timestamp,
+            data: KonsumerOffsetsData::try_from_bytes(key, payload)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::is_thread_safe;
+    use crate::KonsumerOffsetsRecord;
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<KonsumerOffsetsRecord>();
+    }
+}
@@ -0,0 +1,438 @@
+use std::iter::Peekable;
+use std::time::{Duration, Instant};
+
+use crate::record::KonsumerOffsetsRecord;
+
+/// Source of time for a [`Replayer`].
+///
+/// **NOTE:** Unlike the other timestamps of this crate, the origin of [`Self::now`] is up to
+/// the implementation: a [`Replayer`] only cares about the time elapsed between calls.
+pub trait ReplayClock {
+    /// Current time.
+    fn now(&self) -> i64;
+
+    /// Waits until [`Self::now`] has advanced by (at least) `millis`.
+    fn sleep(&mut self, millis: i64);
+}
+
+/// [`ReplayClock`] based on the system monotonic clock: [`ReplayClock::sleep`] blocks the current thread.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    /// Create [`Self`], with origin at the current instant.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayClock for SystemClock {
+    fn now(&self) -> i64 {
+        self.origin.elapsed().as_millis() as i64
+    }
+
+    fn sleep(&mut self, millis: i64) {
+        if millis > 0 {
+            std::thread::sleep(Duration::from_millis(millis as u64));
+        }
+    }
+}
+
+/// [`ReplayClock`] that only moves when told to: [`ReplayClock::sleep`] advances it instantly.
+///
+/// Use it to replay deterministically, for example in tests.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct VirtualClock {
+    now_ms: i64,
+}
+
+impl VirtualClock {
+    /// Create [`Self`], starting at the given time.
+    pub fn new(now_ms: i64) -> Self {
+        Self {
+            now_ms,
+        }
+    }
+
+    /// Advances the clock by `millis`.
+    pub fn advance(&mut self, millis: i64) {
+        self.now_ms += millis.max(0);
+    }
+}
+
+impl ReplayClock for VirtualClock {
+    fn now(&self) -> i64 {
+        self.now_ms
+    }
+
+    fn sleep(&mut self, millis: i64) {
+        self.advance(millis);
+    }
+}
+
+/// Replays a stream of [`KonsumerOffsetsRecord`]s, at the pace given by their timestamps.
+///
+/// The first record is due immediately; each following record is due when, since the first,
+/// as much time has passed on the [`ReplayClock`] as between their timestamps (divided by the speed factor).
+/// Records with a timestamp earlier than the ones already replayed are due immediately.
+///
+/// Records can be consumed in two ways:
+///
+/// * [`Self::poll`] returns the next record only if it is due, never waiting:
+///   this is what an event loop, that also does other things, would use
+/// * iterating over [`Self`] waits (via [`ReplayClock::sleep`]) until the next record is due
+///
+/// The replay can be paused, sped up or slowed down, and moved to a timestamp, at any point:
+/// the pace of the following records is adjusted accordingly.
+pub struct Replayer<I, C>
+where
+    I: Iterator<Item = KonsumerOffsetsRecord>,
+{
+    records: Peekable<I>,
+    clock: C,
+    speed: f64,
+    // Replay time is anchored as (clock time, stream time) pair: `None` until the first record is reached.
+    anchor: Option<(i64, i64)>,
+    paused_at: Option<i64>,
+}
+
+impl<I, C> Replayer<I, C>
+where
+    I: Iterator<Item = KonsumerOffsetsRecord>,
+    C: ReplayClock,
+{
+    /// Create [`Self`], replaying `records` at their original pace, according to `clock`.
+    pub fn new(records: impl IntoIterator<IntoIter = I>, clock: C) -> Self {
+        Self {
+            records: records.into_iter().peekable(),
+            clock,
+            speed: 1.0,
+            anchor: None,
+            paused_at: None,
+        }
+    }
+
+    /// Set the speed factor (see [`Self::set_speed`]).
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.set_speed(speed);
+        self
+    }
+
+    /// Speed factor.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Set the speed factor: `2.0` replays twice as fast as the original, `0.5` half as fast,
+    /// [`f64::INFINITY`] makes every record due immediately.
+    ///
+    /// A `speed` that is not strictly positive (including NaN) is ignored, keeping the current speed:
+    /// use [`Self::pause`] to stop the replay.
+    pub fn set_speed(&mut self, speed: f64) {
+        if speed.is_nan() || speed <= 0.0 {
+            return;
+        }
+
+        if let Some(position) = self.position() {
+            self.anchor = Some((self.clock.now(), position));
+        }
+        self.speed = speed;
+    }
+
+    /// The [`ReplayClock`] driving the replay.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Mutable access to the [`ReplayClock`] driving the replay (ex. to advance a [`VirtualClock`]).
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Current position of the replay in stream time (i.e. in terms of record timestamps).
+    ///
+    /// Returns `None` if the replay hasn't started yet.
+    /// At infinite speed, stream time doesn't flow: it stays where the replay was last anchored
+    /// (ex. by [`Self::set_speed`] or [`Self::seek`]).
+    pub fn stream_time(&self) -> Option<i64> {
+        if let Some(paused_at) = self.paused_at {
+            return Some(paused_at);
+        }
+
+        self.anchor.map(|(clock_anchor, stream_anchor)| {
+            if self.speed.is_infinite() {
+                return stream_anchor;
+            }
+
+            let elapsed = (self.clock.now() - clock_anchor) as f64 * self.speed;
+            stream_anchor.saturating_add(elapsed.min(i64::MAX as f64) as i64)
+        })
+    }
+
+    /// Pauses the replay: no record is due until [`Self::resume`] is called.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.position().unwrap_or_else(|| self.peek_timestamp().unwrap_or_default()));
+        }
+    }
+
+    /// Resumes the replay from where it was paused.
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.anchor = Some((self.clock.now(), paused_at));
+        }
+    }
+
+    /// Whether the replay is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Moves the replay to `timestamp`: records with an earlier timestamp are skipped,
+    /// and the ones after are due relative to `timestamp`.
+    ///
+    /// Only forward seeking is possible, as replayed records are not retained:
+    /// to seek backward, create a new [`Replayer`] (ex. from a [`crate::CaptureReader`],
+    /// using [`crate::CaptureReader::seek_timestamp`]).
+    ///
+    /// Returns the number of skipped records.
+    pub fn seek(&mut self, timestamp: i64) -> usize {
+        let mut skipped = 0;
+        while self.records.next_if(|r| r.timestamp < timestamp).is_some() {
+            skipped += 1;
+        }
+
+        if self.paused_at.is_some() {
+            self.paused_at = Some(timestamp);
+        }
+        self.anchor = Some((self.clock.now(), timestamp));
+
+        skipped
+    }
+
+    /// Clock time at which the next record is due.
+    ///
+    /// Returns `None` if there are no more records, or the replay is paused.
+    pub fn next_due(&mut self) -> Option<i64> {
+        if self.paused_at.is_some() {
+            return None;
+        }
+
+        let timestamp = self.peek_timestamp()?;
+        let (clock_anchor, stream_anchor) = *self.anchor.get_or_insert((self.clock.now(), timestamp));
+
+        let delay = (timestamp - stream_anchor).max(0) as f64 / self.speed;
+        Some(clock_anchor.saturating_add(delay.ceil().min(i64::MAX as f64) as i64))
+    }
+
+    /// Returns the next record, if it is due; otherwise, returns immediately `None`.
+    pub fn poll(&mut self) -> Option<KonsumerOffsetsRecord> {
+        if self.next_due()? <= self.clock.now() {
+            self.records.next()
+        } else {
+            None
+        }
+    }
+
+    /// Whether all records have been replayed.
+    pub fn is_finished(&mut self) -> bool {
+        self.records.peek().is_none()
+    }
+
+    // Position to anchor the replay at, when its pace changes: at infinite speed every record is due,
+    // so the replay is at the next record (if any), not at the stream time elapsed so far.
+    fn position(&mut self) -> Option<i64> {
+        if self.paused_at.is_some() || !self.speed.is_infinite() {
+            return self.stream_time();
+        }
+
+        let (_, stream_anchor) = self.anchor?;
+        Some(self.peek_timestamp().map_or(stream_anchor, |timestamp| timestamp.max(stream_anchor)))
+    }
+
+    fn peek_timestamp(&mut self) -> Option<i64> {
+        self.records.peek().map(|r| r.timestamp)
+    }
+}
+
+impl<I, C> Iterator for Replayer<I, C>
+where
+    I: Iterator<Item = KonsumerOffsetsRecord>,
+    C: ReplayClock,
+{
+    type Item = KonsumerOffsetsRecord;
+
+    /// Waits until the next record is due, and returns it.
+    ///
+    /// Returns `None` when there are no more records, or when the replay is paused.
+    fn next(&mut self) -> Option<Self::Item> {
+        let due = self.next_due()?;
+
+        let now = self.clock.now();
+        if due > now {
+            self.clock.sleep(due - now);
+        }
+
+        self.records.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::is_thread_safe;
+    use crate::{
+        GroupMetadata, KonsumerOffsetsData, KonsumerOffsetsRecord, ReplayClock, Replayer, SystemClock, VirtualClock,
+    };
+
+    fn records(timestamps: &[i64]) -> Vec<KonsumerOffsetsRecord> {
+        timestamps
+            .iter()
+            .enumerate()
+            .map(|(offset, &timestamp)| KonsumerOffsetsRecord {
+                partition: 0,
+                offset: offset as i64,
+                timestamp,
+                data: KonsumerOffsetsData::GroupMetadata(GroupMetadata {
+                    group: format!("g{offset}"),
+                    ..Default::default()
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_original_pace() {
+        let mut replayer = Replayer::new(records(&[10_000, 10_500, 13_000]), VirtualClock::new(0));
+        assert_eq!(replayer.stream_time(), None);
+
+        assert_eq!(replayer.poll().unwrap().offset, 0);
+        assert_eq!(replayer.poll(), None);
+        assert_eq!(replayer.next_due(), Some(500));
+        assert_eq!(replayer.stream_time(), Some(10_000));
+
+        replayer.clock_mut().advance(499);
+        assert_eq!(replayer.poll(), None);
+        replayer.clock_mut().advance(1);
+        assert_eq!(replayer.poll().unwrap().offset, 1);
+
+        // Iterating waits on the clock.
+        assert_eq!(replayer.next().unwrap().offset, 2);
+        assert_eq!(replayer.clock().now(), 3000);
+        assert!(replayer.is_finished());
+        assert_eq!(replayer.next(), None);
+    }
+
+    #[test]
+    fn test_speed() {
+        let mut replayer = Replayer::new(records(&[0, 1000, 3000]), VirtualClock::new(100)).with_speed(4.0);
+        assert_eq!(replayer.by_ref().map(|r| r.offset).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(replayer.clock().now(), 850);
+
+        // Changing speed mid-replay keeps the position.
+        let mut replayer = Replayer::new(records(&[0, 1000, 3000]), VirtualClock::new(0));
+        replayer.next();
+        replayer.next();
+        replayer.set_speed(0.5);
+        assert_eq!(replayer.stream_time(), Some(1000));
+        assert_eq!(replayer.next_due(), Some(1000 + 4000));
+
+        let mut replayer = Replayer::new(records(&[0, 1000, 3000]), VirtualClock::new(0)).with_speed(f64::INFINITY);
+        assert_eq!(replayer.by_ref().count(), 3);
+        assert_eq!(replayer.clock().now(), 0);
+    }
+
+    #[test]
+    fn test_from_infinite_speed() {
+        let mut replayer =
+            Replayer::new(records(&[0, 1000, 3000, 6000]), VirtualClock::new(0)).with_speed(f64::INFINITY);
+        replayer.next();
+        replayer.next();
+        replayer.clock_mut().advance(500);
+        assert_eq!(replayer.stream_time(), Some(0));
+
+        // Going back to a finite speed resumes from the next record.
+        replayer.set_speed(1.0);
+        assert_eq!(replayer.stream_time(), Some(3000));
+        assert_eq!(replayer.next_due(), Some(500));
+        assert_eq!(replayer.next().unwrap().offset, 2);
+        assert_eq!(replayer.next_due(), Some(3500));
+
+        // Same when pausing at infinite speed.
+        replayer.set_speed(f64::INFINITY);
+        replayer.pause();
+        assert_eq!(replayer.stream_time(), Some(6000));
+        replayer.set_speed(1.0);
+        replayer.resume();
+        assert_eq!(replayer.next_due(), Some(500));
+        assert_eq!(replayer.next().unwrap().offset, 3);
+    }
+
+    #[test]
+    fn test_out_of_order_timestamps() {
+        let mut replayer = Replayer::new(records(&[1000, 500, 2000]), VirtualClock::new(0));
+        assert_eq!(replayer.poll().unwrap().offset, 0);
+        assert_eq!(replayer.poll().unwrap().offset, 1);
+        assert_eq!(replayer.poll(), None);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut replayer = Replayer::new(records(&[0, 1000, 2000]), VirtualClock::new(0));
+        replayer.next();
+        replayer.clock_mut().advance(400);
+        replayer.pause();
+        assert!(replayer.is_paused());
+
+        replayer.clock_mut().advance(10_000);
+        assert_eq!(replayer.stream_time(), Some(400));
+        assert_eq!(replayer.poll(), None);
+        assert_eq!(replayer.next(), None);
+
+        replayer.resume();
+        assert!(!replayer.is_paused());
+        assert_eq!(replayer.next_due(), Some(10_400 + 600));
+        assert_eq!(replayer.next().unwrap().offset, 1);
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut replayer = Replayer::new(records(&[0, 1000, 2000, 3000]), VirtualClock::new(0));
+        assert_eq!(replayer.seek(1500), 2);
+        assert_eq!(replayer.stream_time(), Some(1500));
+        assert_eq!(replayer.next_due(), Some(500));
+        assert_eq!(replayer.next().unwrap().offset, 2);
+
+        // Seeking while paused moves the paused position.
+        replayer.pause();
+        assert_eq!(replayer.seek(3000), 0);
+        assert_eq!(replayer.stream_time(), Some(3000));
+        replayer.resume();
+        assert_eq!(replayer.poll().unwrap().offset, 3);
+    }
+
+    #[test]
+    fn test_invalid_speed() {
+        let mut replayer = Replayer::new(records(&[0]), VirtualClock::new(0)).with_speed(2.0);
+        for speed in [0.0, -1.0, f64::NEG_INFINITY, f64::NAN] {
+            replayer.set_speed(speed);
+            assert_eq!(replayer.speed(), 2.0);
+        }
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<VirtualClock>();
+        is_thread_safe::<SystemClock>();
+        is_thread_safe::<Replayer<std::vec::IntoIter<KonsumerOffsetsRecord>, VirtualClock>>();
+    }
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection, Transaction};
+
+use crate::errors::SqliteExportError;
+use crate::group_metadata::GroupMetadata;
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::record::KonsumerOffsetsRecord;
+use crate::utils::timestamp_millis;
+
+/// Version of [`SQLITE_SCHEMA`], stored in the database `user_version` pragma.
+pub const SQLITE_SCHEMA_VERSION: i32 = 1;
+
+/// Schema of the database written by [`SqliteExporter`].
+///
+/// History tables have one row per appended record, with its coordinates (`record_*` columns,
+/// null when the data was appended without them). State tables are derived from the history,
+/// and hold the latest known state per key.
+///
+/// All timestamps are milliseconds since Unix Epoch (UTC).
+/// Values not set in the record (ex. `expire_timestamp` for schema versions other than `1`,
+/// empty `group_instance_id` or `rack_id`, negative `leader_epoch`) are stored as `NULL`.
+pub const SQLITE_SCHEMA: &str = r#"
+-- History: one row per (non tombstone) OffsetCommit.
+CREATE TABLE IF NOT EXISTS offset_commits (
+    id                      INTEGER PRIMARY KEY,
+    record_partition        INTEGER,
+    record_offset           INTEGER,
+    record_timestamp        INTEGER,
+    group_id                TEXT NOT NULL,
+    topic                   TEXT NOT NULL,
+    partition               INTEGER NOT NULL,
+    message_version         INTEGER NOT NULL,
+    schema_version          INTEGER NOT NULL,
+    offset                  INTEGER NOT NULL,
+    leader_epoch            INTEGER,
+    metadata                TEXT NOT NULL,
+    commit_timestamp        INTEGER NOT NULL,
+    expire_timestamp        INTEGER
+);
+CREATE INDEX IF NOT EXISTS offset_commits_group_topic ON offset_commits (group_id, topic, partition);
+
+-- History: one row per (non tombstone) GroupMetadata.
+CREATE TABLE IF NOT EXISTS group_metadata (
+    id                      INTEGER PRIMARY KEY,
+    record_partition        INTEGER,
+    record_offset           INTEGER,
+    record_timestamp        INTEGER,
+    group_id                TEXT NOT NULL,
+    message_version         INTEGER NOT NULL,
+    schema_version          INTEGER NOT NULL,
+    generation              INTEGER NOT NULL,
+    protocol_type           TEXT NOT NULL,
+    protocol                TEXT NOT NULL,
+    leader                  TEXT NOT NULL,
+    current_state_timestamp INTEGER
+);
+CREATE INDEX IF NOT EXISTS group_metadata_group ON group_metadata (group_id, generation);
+
+-- History: one row per member of each row of group_metadata.
+CREATE TABLE IF NOT EXISTS members (
+    id                      INTEGER PRIMARY KEY,
+    group_metadata_id       INTEGER NOT NULL REFERENCES group_metadata (id),
+    group_id                TEXT NOT NULL,
+    generation              INTEGER NOT NULL,
+    member_id               TEXT NOT NULL,
+    group_instance_id       TEXT,
+    client_id               TEXT NOT NULL,
+    client_host             TEXT NOT NULL,
+    rebalance_timeout_ms    INTEGER NOT NULL,
+    session_timeout_ms      INTEGER NOT NULL,
+    subscription_version    INTEGER NOT NULL,
+    rack_id                 TEXT
+);
+CREATE INDEX IF NOT EXISTS members_group_metadata ON members (group_metadata_id);
+CREATE INDEX IF NOT EXISTS members_client ON members (client_host, client_id);
+
+-- History: one row per topic subscribed by each row of members.
+CREATE TABLE IF NOT EXISTS subscriptions (
+    member_row_id           INTEGER NOT NULL REFERENCES members (id),
+    topic                   TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS subscriptions_member ON subscriptions (member_row_id);
+CREATE INDEX IF NOT EXISTS subscriptions_topic ON subscriptions (topic);
+
+-- History: one row per partition assigned to each row of members.
+CREATE TABLE IF NOT EXISTS assignments (
+    member_row_id           INTEGER NOT NULL REFERENCES members (id),
+    topic                   TEXT NOT NULL,
+    partition               INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS assignments_member ON assignments (member_row_id);
+CREATE INDEX IF NOT EXISTS assignments_topic ON assignments (topic, partition);
+
+-- History: one row per tombstone, of either kind ('offset_commit' or 'group_metadata').
+-- topic and partition are NULL for 'group_metadata' tombstones.
+CREATE TABLE IF NOT EXISTS tombstones (
+    id                      INTEGER PRIMARY KEY,
+    record_partition        INTEGER,
+    record_offset           INTEGER,
+    record_timestamp        INTEGER,
+    kind                    TEXT NOT NULL,
+    group_id                TEXT NOT NULL,
+    topic                   TEXT,
+    partition               INTEGER
+);
+CREATE INDEX IF NOT EXISTS tombstones_group ON tombstones (group_id);
+
+-- State: latest committed offset per group, topic and partition (removed by tombstones).
+CREATE TABLE IF NOT EXISTS committed_offsets (
+    group_id                TEXT NOT NULL,
+    topic                   TEXT NOT NULL,
+    partition               INTEGER NOT NULL,
+    offset                  INTEGER NOT NULL,
+    leader_epoch            INTEGER,
+    metadata                TEXT NOT NULL,
+    commit_timestamp        INTEGER NOT NULL,
+    expire_timestamp        INTEGER,
+    offset_commit_id        INTEGER NOT NULL REFERENCES offset_commits (id),
+    PRIMARY KEY (group_id, topic, partition)
+);
+
+-- State: latest known state per group; is_deleted is set by group tombstones.
+CREATE TABLE IF NOT EXISTS groups (
+    group_id                TEXT PRIMARY KEY,
+    generation              INTEGER,
+    protocol_type           TEXT,
+    protocol                TEXT,
+    leader                  TEXT,
+    member_count            INTEGER NOT NULL,
+    is_deleted              INTEGER NOT NULL,
+    last_record_timestamp   INTEGER,
+    group_metadata_id       INTEGER REFERENCES group_metadata (id)
+);
+"#;
+
+/// Writes parsed records, and the state derived from them, into a [SQLite](https://sqlite.org/) database.
+///
+/// The database follows [`SQLITE_SCHEMA`]: tables are created if missing, so the same database
+/// can be appended to incrementally, across runs. Use [`Self::last_offsets`] to know where to resume
+/// consuming `__consumer_offsets` from.
+///
+/// Once written, the database can answer questions with plain SQL. For example,
+/// _"which groups read topic `X` from host `Y` on a given day"_:
+///
+/// ```sql
+/// SELECT DISTINCT m.group_id
+/// FROM assignments a
+///   JOIN members m ON m.id = a.member_row_id
+///   JOIN group_metadata gm ON gm.id = m.group_metadata_id
+/// WHERE a.topic = 'X' AND m.client_host = '/Y'
+///   AND gm.record_timestamp BETWEEN 1672790400000 AND 1672876799999;
+/// ```
+///
+/// **NOTE:** Unlike the other types of this crate, this is `Send` but not `Sync`,
+/// as the underlying SQLite connection is not.
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Create [`Self`] from an open [`Connection`], creating the tables of [`SQLITE_SCHEMA`] if missing.
+    ///
+    /// Fails with [`SqliteExportError::UnsupportedSchemaVersion`] if the database
+    /// was created with a different [`SQLITE_SCHEMA_VERSION`].
+    pub fn new(conn: Connection) -> Result<Self, SqliteExportError> {
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        match version {
+            0 => {
+                conn.execute_batch(SQLITE_SCHEMA)?;
+                conn.pragma_update(None, "user_version", SQLITE_SCHEMA_VERSION)?;
+            },
+            SQLITE_SCHEMA_VERSION => {},
+            v => return Err(SqliteExportError::UnsupportedSchemaVersion(v)),
+        }
+
+        Ok(Self {
+            conn,
+        })
+    }
+
+    /// Create [`Self`], opening (or creating) the database file at the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SqliteExportError> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Appends a [`KonsumerOffsetsRecord`], filling the `record_*` columns with its coordinates.
+    pub fn append_record(&mut self, record: &KonsumerOffsetsRecord) -> Result<(), SqliteExportError> {
+        self.append_records(std::iter::once(record))
+    }
+
+    /// Appends a [`KonsumerOffsetsData`]: the `record_*` columns are left null.
+    pub fn append(&mut self, data: &KonsumerOffsetsData) -> Result<(), SqliteExportError> {
+        let tx = self.conn.transaction()?;
+        insert(&tx, None, data)?;
+        Ok(tx.commit()?)
+    }
+
+    /// Appends multiple [`KonsumerOffsetsRecord`]s, in a single transaction.
+    ///
+    /// Prefer this to [`Self::append_record`] when possible: it's significantly faster.
+    pub fn append_records<'a, I>(&mut self, records: I) -> Result<(), SqliteExportError>
+    where
+        I: IntoIterator<Item = &'a KonsumerOffsetsRecord>,
+    {
+        let tx = self.conn.transaction()?;
+        for record in records {
+            insert(&tx, Some(record), &record.data)?;
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Highest record offset appended so far, per partition of `__consumer_offsets`.
+    ///
+    /// Only records appended with their coordinates are taken into account.
+    pub fn last_offsets(&self) -> Result<HashMap<i32, i64>, SqliteExportError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT record_partition, MAX(record_offset) FROM (
+                SELECT record_partition, record_offset FROM offset_commits
+                UNION ALL SELECT record_partition, record_offset FROM group_metadata
+                UNION ALL SELECT record_partition, record_offset FROM tombstones
+            ) WHERE record_partition IS NOT NULL GROUP BY record_partition",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Reference to the underlying [`Connection`], to query the database.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Consumes [`Self`], returning the underlying [`Connection`].
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+}
+
+fn insert(
+    tx: &Transaction,
+    record: Option<&KonsumerOffsetsRecord>,
+    data: &KonsumerOffsetsData,
+) -> Result<(), rusqlite::Error> {
+    let coordinates = (record.map(|r| r.partition), record.map(|r| r.offset), record.map(|r| r.timestamp));
+
+    match data {
+        KonsumerOffsetsData::OffsetCommit(oc) if oc.is_tombstone => {
+            tx.prepare_cached(
+                "INSERT INTO tombstones (record_partition, record_offset, record_timestamp, kind, group_id, topic, partition)
+                VALUES (?1, ?2, ?3, 'offset_commit', ?4, ?5, ?6)",
+            )?
+            .execute(params![coordinates.0, coordinates.1, coordinates.2, oc.group, oc.topic, oc.partition])?;
+            tx.prepare_cached("DELETE FROM committed_offsets WHERE group_id = ?1 AND topic = ?2 AND partition = ?3")?
+                .execute(params![oc.group, oc.topic, oc.partition])?;
+        },
+        KonsumerOffsetsData::OffsetCommit(oc) => insert_offset_commit(tx, coordinates, oc)?,
+        KonsumerOffsetsData::GroupMetadata(gm) if gm.is_tombstone => {
+            tx.prepare_cached(
+                "INSERT INTO tombstones (record_partition, record_offset, record_timestamp, kind, group_id)
+                VALUES (?1, ?2, ?3, 'group_metadata', ?4)",
+            )?
+            .execute(params![coordinates.0, coordinates.1, coordinates.2, gm.group])?;
+            tx.prepare_cached(
+                "INSERT INTO groups (group_id, member_count, is_deleted, last_record_timestamp)
+                VALUES (?1, 0, 1, ?2)
+                ON CONFLICT (group_id) DO UPDATE SET
+                    member_count = 0, is_deleted = 1, last_record_timestamp = excluded.last_record_timestamp",
+            )?
+            .execute(params![gm.group, coordinates.2])?;
+        },
+        KonsumerOffsetsData::GroupMetadata(gm) => insert_group_metadata(tx, coordinates, gm)?,
+    }
+
+    Ok(())
+}
+
+type Coordinates = (Option<i32>, Option<i64>, Option<i64>);
+
+fn insert_offset_commit(tx: &Transaction, coordinates: Coordinates, oc: &OffsetCommit) -> Result<(), rusqlite::Error> {
+    let leader_epoch = Some(oc.leader_epoch).filter(|le| *le >= 0);
+    let commit_timestamp = timestamp_millis(&oc.commit_timestamp);
+    let expire_timestamp = Some(timestamp_millis(&oc.expire_timestamp)).filter(|ts| oc.schema_version == 1 && *ts >= 0);
+
+    tx.prepare_cached(
+        "INSERT INTO offset_commits (
+            record_partition, record_offset, record_timestamp, group_id, topic, partition, message_version,
+            schema_version, offset, leader_epoch, metadata, commit_timestamp, expire_timestamp
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    )?
+    .execute(params![
+        coordinates.0,
+        coordinates.1,
+        coordinates.2,
+        oc.group,
+        oc.topic,
+        oc.partition,
+        oc.message_version,
+        oc.schema_version,
+        oc.offset,
+        leader_epoch,
+        oc.metadata,
+        commit_timestamp,
+        expire_timestamp,
+    ])?;
+    let offset_commit_id = tx.last_insert_rowid();
+
+    tx.prepare_cached(
+        "INSERT OR REPLACE INTO committed_offsets (
+            group_id, topic, partition, offset, leader_epoch, metadata, commit_timestamp, expire_timestamp,
+            offset_commit_id
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?
+    .execute(params![
+        oc.group,
+        oc.topic,
+        oc.partition,
+        oc.offset,
+        leader_epoch,
+        oc.metadata,
+        commit_timestamp,
+        expire_timestamp,
+        offset_commit_id,
+    ])?;
+
+    Ok(())
+}
+
+fn insert_group_metadata(
+    tx: &Transaction,
+    coordinates: Coordinates,
+    gm: &GroupMetadata,
+) -> Result<(), rusqlite::Error> {
+    let current_state_timestamp = (gm.schema_version >= 2).then(|| timestamp_millis(&gm.current_state_timestamp));
+
+    tx.prepare_cached(
+        "INSERT INTO group_metadata (
+            record_partition, record_offset, record_timestamp, group_id, message_version, schema_version,
+            generation, protocol_type, protocol, leader, current_state_timestamp
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    )?
+    .execute(params![
+        coordinates.0,
+        coordinates.1,
+        coordinates.2,
+        gm.group,
+        gm.message_version,
+        gm.schema_version,
+        gm.generation,
+        gm.protocol_type,
+        gm.protocol,
+        gm.leader,
+        current_state_timestamp,
+    ])?;
+    let group_metadata_id = tx.last_insert_rowid();
+
+    for member in &gm.members {
+        tx.prepare_cached(
+            "INSERT INTO members (
+                group_metadata_id, group_id, generation, member_id, group_instance_id, client_id, client_host,
+                rebalance_timeout_ms, session_timeout_ms, subscription_version, rack_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?
+        .execute(params![
+            group_metadata_id,
+            gm.group,
+            gm.generation,
+            member.id,
+            Some(&member.group_instance_id).filter(|id| !id.is_empty()),
+            member.client_id,
+            member.client_host,
+            member.rebalance_timeout,
+            member.session_timeout,
+            member.subscription.schema_version,
+            Some(&member.subscription.rack_id).filter(|id| !id.is_empty()),
+        ])?;
+        let member_row_id = tx.last_insert_rowid();
+
+        let mut subscription_stmt =
+            tx.prepare_cached("INSERT INTO subscriptions (member_row_id, topic) VALUES (?1, ?2)")?;
+        for topic in &member.subscription.subscribed_topics {
+            subscription_stmt.execute(params![member_row_id, topic])?;
+        }
+
+        let mut assignment_stmt =
+            tx.prepare_cached("INSERT INTO assignments (member_row_id, topic, partition) VALUES (?1, ?2, ?3)")?;
+        for tp in &member.assignment.assigned_topic_partitions {
+            for partition in &tp.partitions {
+                assignment_stmt.execute(params![member_row_id, tp.topic, partition])?;
+            }
+        }
+    }
+
+    tx.prepare_cached(
+        "INSERT OR REPLACE INTO groups (
+            group_id, generation, protocol_type, protocol, leader, member_count, is_deleted,
+            last_record_timestamp, group_metadata_id
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7, ?8)",
+    )?
+    .execute(params![
+        gm.group,
+        gm.generation,
+        gm.protocol_type,
+        gm.protocol,
+        gm.leader,
+        gm.members.len() as i64,
+        coordinates.2,
+        group_metadata_id,
+    ])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use rusqlite::Connection;
+
+    use crate::utils::read_fixture;
+    use crate::{KonsumerOffsetsData, KonsumerOffsetsRecord, SqliteExportError, SqliteExporter, SQLITE_SCHEMA_VERSION};
+
+    fn record(offset: i64, data: KonsumerOffsetsData) -> KonsumerOffsetsRecord {
+        KonsumerOffsetsRecord {
+            partition: 7,
+            offset,
+            timestamp: 1672871009300 + offset,
+            data,
+        }
+    }
+
+    fn count(exporter: &SqliteExporter, table: &str) -> i64 {
+        exporter.connection().query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_offset_commits_and_tombstones() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let KonsumerOffsetsData::OffsetCommit(oc) = read_fixture("offset_commit", 1) else {
+            panic!("Returned wrong enum value!");
+        };
+        let mut tombstone = oc.clone();
+        tombstone.is_tombstone = true;
+
+        exporter.append_record(&record(1, KonsumerOffsetsData::OffsetCommit(oc.clone()))).unwrap();
+        assert_eq!(count(&exporter, "offset_commits"), 1);
+        let committed: i64 = exporter
+            .connection()
+            .query_row(
+                "SELECT offset FROM committed_offsets WHERE group_id = ?1 AND topic = ?2 AND partition = ?3",
+                (&oc.group, &oc.topic, oc.partition),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(committed, oc.offset);
+
+        exporter.append_record(&record(2, KonsumerOffsetsData::OffsetCommit(tombstone))).unwrap();
+        assert_eq!(count(&exporter, "offset_commits"), 1);
+        assert_eq!(count(&exporter, "tombstones"), 1);
+        assert_eq!(count(&exporter, "committed_offsets"), 0);
+    }
+
+    #[test]
+    fn test_group_metadata() {
+        let mut exporter = SqliteExporter::new(Connection::open_in_memory().unwrap()).unwrap();
+
+        let KonsumerOffsetsData::GroupMetadata(gm) = read_fixture("group_metadata", 4) else {
+            panic!("Returned wrong enum value!");
+        };
+        exporter.append_records(&[record(10, KonsumerOffsetsData::GroupMetadata(gm.clone()))]).unwrap();
+
+        assert_eq!(count(&exporter, "group_metadata"), 1);
+        assert_eq!(count(&exporter, "members"), gm.members.len() as i64);
+        let assigned: usize =
+            gm.members.iter().flat_map(|m| &m.assignment.assigned_topic_partitions).map(|tp| tp.partitions.len()).sum();
+        assert_eq!(count(&exporter, "assignments"), assigned as i64);
+
+        let member = &gm.members[0];
+        let topic = &member.assignment.assigned_topic_partitions[0].topic;
+        let groups: Vec<String> = exporter
+            .connection()
+            .prepare(
+                "SELECT DISTINCT m.group_id FROM assignments a JOIN members m ON m.id = a.member_row_id
+                WHERE a.topic = ?1 AND m.client_host = ?2",
+            )
+            .unwrap()
+            .query_map((topic, &member.client_host), |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(groups, vec![gm.group.clone()]);
+
+        let mut tombstone = gm.clone();
+        tombstone.is_tombstone = true;
+        exporter.append_record(&record(11, KonsumerOffsetsData::GroupMetadata(tombstone))).unwrap();
+        let (is_deleted, member_count): (bool, i64) = exporter
+            .connection()
+            .query_row("SELECT is_deleted, member_count FROM groups WHERE group_id = ?1", [&gm.group], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(is_deleted);
+        assert_eq!(member_count, 0);
+    }
+
+    #[test]
+    fn test_incremental_appends() {
+        let path = std::env::temp_dir().join(format!("konsumer_offsets_sqlite_{}.db", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut exporter = SqliteExporter::open(&path).unwrap();
+        exporter.append_record(&record(1, read_fixture("offset_commit", 1))).unwrap();
+        exporter.append(&read_fixture("offset_commit", 2)).unwrap();
+        drop(exporter);
+
+        let mut exporter = SqliteExporter::open(&path).unwrap();
+        exporter.append_record(&record(5, read_fixture("group_metadata", 1))).unwrap();
+        assert_eq!(count(&exporter, "offset_commits"), 2);
+        assert_eq!(exporter.last_offsets().unwrap().get(&7), Some(&5));
+        drop(exporter);
+
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", SQLITE_SCHEMA_VERSION + 1).unwrap();
+        assert!(matches!(
+            SqliteExporter::new(conn),
+            Err(SqliteExportError::UnsupportedSchemaVersion(v)) if v == SQLITE_SCHEMA_VERSION + 1
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
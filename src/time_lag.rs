@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::konsumer_offsets_data::KonsumerOffsetsData;
+use crate::offset_commit::OffsetCommit;
+use crate::utils::timestamp_millis;
+
+/// Default number of log-end offset samples retained, per topic partition, by [`TimeLagEstimator`].
+pub const DEFAULT_TIME_LAG_SAMPLES_CAPACITY: usize = 60;
+
+/// Estimates consumer lag in _time_, instead of in _number of records_.
+///
+/// Offset lag (see [`crate::PartitionLag`]) means little for topics with uneven throughput:
+/// 1000 records behind can be a few milliseconds on a busy topic, or days on a quiet one.
+///
+/// This estimator uses the same approach of [Burrow] and [Kafka Lag Exporter]: it keeps,
+/// for each topic partition, an _interpolation table_ of `(log_end_offset, timestamp)` samples,
+/// i.e. _when_ the log of that partition reached a given offset. Given the latest
+/// [`OffsetCommit::offset`] of a group, it then looks up (interpolating between the 2 closest
+/// samples) _when_ the record at that offset was produced: the time lag is how long ago that was.
+///
+/// Log-end offsets are **not** in `__consumer_offsets`: the caller has to sample them periodically
+/// (ex. via a `ListOffsetsRequest`) and pass them to [`Self::record_log_end_offset`].
+///
+/// Memory is bounded: at most `capacity` samples are retained per topic partition,
+/// discarding the oldest first.
+///
+/// [Burrow]: https://github.com/linkedin/Burrow
+/// [Kafka Lag Exporter]: https://github.com/seglo/kafka-lag-exporter
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TimeLagEstimator {
+    capacity: usize,
+    samples: BTreeMap<(String, i32), VecDeque<(i64, i64)>>,
+    commits: BTreeMap<(String, String, i32), (i64, i64)>,
+}
+
+impl Default for TimeLagEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_TIME_LAG_SAMPLES_CAPACITY)
+    }
+}
+
+impl TimeLagEstimator {
+    /// Create a [`Self`], that retains at most `capacity` samples per topic partition.
+    ///
+    /// A `capacity` lower than `2` is raised to `2`: that is the minimum to interpolate.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(2),
+            samples: BTreeMap::new(),
+            commits: BTreeMap::new(),
+        }
+    }
+
+    /// Record that, at `timestamp_ms`, the log-end offset of a topic partition was `offset`.
+    ///
+    /// Samples are expected in chronological order. A sample is ignored if it doesn't advance
+    /// the log-end offset: the table keeps the _earliest_ time at which an offset was reached.
+    pub fn record_log_end_offset(&mut self, topic: &str, partition: i32, offset: i64, timestamp_ms: i64) {
+        let table = self.samples.entry((topic.to_string(), partition)).or_default();
+
+        if let Some(&(last_offset, last_timestamp)) = table.back() {
+            if offset <= last_offset || timestamp_ms < last_timestamp {
+                return;
+            }
+        }
+
+        if table.len() == self.capacity {
+            table.pop_front();
+        }
+        table.push_back((offset, timestamp_ms));
+    }
+
+    /// Update [`Self`] with an [`OffsetCommit`].
+    ///
+    /// If the [`OffsetCommit`] is a tombstone, the commit for its `(group, topic, partition)`
+    /// is forgotten.
+    pub fn update(&mut self, offset_commit: &OffsetCommit) {
+        let key = (offset_commit.group.clone(), offset_commit.topic.clone(), offset_commit.partition);
+
+        if offset_commit.is_tombstone {
+            self.commits.remove(&key);
+        } else {
+            self.commits.insert(key, (offset_commit.offset, timestamp_millis(&offset_commit.commit_timestamp)));
+        }
+    }
+
+    /// Update [`Self`] with a [`KonsumerOffsetsData`].
+    ///
+    /// Only [`KonsumerOffsetsData::OffsetCommit`] is relevant: other variants are ignored.
+    pub fn update_from(&mut self, data: &KonsumerOffsetsData) {
+        if let KonsumerOffsetsData::OffsetCommit(offset_commit) = data {
+            self.update(offset_commit);
+        }
+    }
+
+    /// Estimates at what time the record at `offset` was appended to the log of a topic partition.
+    ///
+    /// Returns `None` if no sample has been recorded for the topic partition, or if `offset`
+    /// is past the latest sampled log-end offset (i.e. the record is not known to exist yet).
+    ///
+    /// The second element of the returned tuple is `true` if the estimate was _extrapolated_,
+    /// because `offset` is older than the oldest sample retained.
+    pub fn offset_timestamp(&self, topic: &str, partition: i32, offset: i64) -> Option<(i64, bool)> {
+        let table = self.samples.get(&(topic.to_string(), partition))?;
+        let &(first_offset, first_timestamp) = table.front()?;
+        let &(last_offset, _) = table.back()?;
+
+        // A record at offset `o` is appended when the log-end offset moves from `o` to `o + 1`.
+        let target = offset + 1;
+        if target > last_offset {
+            return None;
+        }
+
+        if target < first_offset {
+            // Extrapolate using the oldest 2 samples, if available, otherwise the oldest sample is a lower bound.
+            return match table.get(1) {
+                Some(&(second_offset, second_timestamp)) => Some((
+                    interpolate((first_offset, first_timestamp), (second_offset, second_timestamp), target),
+                    true,
+                )),
+                None => Some((first_timestamp, true)),
+            };
+        }
+
+        let idx = table.partition_point(|&(o, _)| o < target);
+        let upper = table[idx];
+        if upper.0 == target || idx == 0 {
+            return Some((upper.1, false));
+        }
+
+        Some((interpolate(table[idx - 1], upper, target), false))
+    }
+
+    /// Estimates the time lag of a group on a topic partition, at the time `now_ms`.
+    ///
+    /// Returns `None` if the group has no committed offset for the topic partition,
+    /// or no log-end offset has been recorded for it.
+    pub fn estimate(&self, group: &str, topic: &str, partition: i32, now_ms: i64) -> Option<PartitionTimeLag> {
+        let &(committed_offset, commit_timestamp) =
+            self.commits.get(&(group.to_string(), topic.to_string(), partition))?;
+        let table = self.samples.get(&(topic.to_string(), partition))?;
+        let &(log_end_offset, _) = table.back()?;
+
+        let (time_lag, extrapolated) = if committed_offset >= log_end_offset {
+            (0, false)
+        } else {
+            let (produced_at, extrapolated) = self.offset_timestamp(topic, partition, committed_offset)?;
+            ((now_ms - produced_at).max(0), extrapolated)
+        };
+
+        Some(PartitionTimeLag {
+            group: group.to_string(),
+            topic: topic.to_string(),
+            partition,
+            committed_offset,
+            commit_timestamp,
+            log_end_offset,
+            offset_lag: (log_end_offset - committed_offset).max(0),
+            time_lag,
+            extrapolated,
+        })
+    }
+
+    /// Estimates the time lag of every group on every topic partition known to [`Self`], at the time `now_ms`.
+    ///
+    /// Results are sorted by `(group, topic, partition)`.
+    pub fn estimate_all(&self, now_ms: i64) -> Vec<PartitionTimeLag> {
+        self.commits.keys().filter_map(|(g, t, p)| self.estimate(g, t, *p, now_ms)).collect()
+    }
+}
+
+fn interpolate((o1, t1): (i64, i64), (o2, t2): (i64, i64), offset: i64) -> i64 {
+    if o1 == o2 {
+        return t1;
+    }
+
+    t1 + ((offset - o1) as f64 * (t2 - t1) as f64 / (o2 - o1) as f64).round() as i64
+}
+
+/// Time lag estimate of a consumer group on a single topic partition, produced by [`TimeLagEstimator`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionTimeLag {
+    /// Group.
+    pub group: String,
+
+    /// Topic.
+    pub topic: String,
+
+    /// Partition.
+    pub partition: i32,
+
+    /// Latest offset committed by the group.
+    pub committed_offset: i64,
+
+    /// Timestamp of when [`Self::committed_offset`] was committed.
+    pub commit_timestamp: i64,
+
+    /// Latest log-end offset sampled.
+    pub log_end_offset: i64,
+
+    /// Lag in number of records, at the time of the latest log-end offset sample.
+    pub offset_lag: i64,
+
+    /// Estimated lag in milliseconds: how long ago the next record to consume was appended to the log.
+    pub time_lag: i64,
+
+    /// `true` if [`Self::time_lag`] was extrapolated, because the committed offset is older than
+    /// the oldest log-end offset sample retained: treat it as an approximation.
+    pub extrapolated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{commit, is_thread_safe};
+
+    #[test]
+    fn test_interpolation() {
+        let mut estimator = TimeLagEstimator::new(10);
+        estimator.record_log_end_offset("t", 0, 100, 1_000);
+        estimator.record_log_end_offset("t", 0, 200, 2_000);
+        estimator.record_log_end_offset("t", 0, 200, 3_000);
+        estimator.record_log_end_offset("t", 0, 1200, 4_000);
+
+        // Record 149 appended when log-end moved to 150: half way between 1s and 2s.
+        estimator.update(&commit("g", "t", 0, 149, 4_500));
+        let lag = estimator.estimate("g", "t", 0, 5_000).unwrap();
+        assert_eq!(lag.offset_lag, 1051);
+        assert_eq!(lag.time_lag, 3_500);
+        assert_eq!(lag.commit_timestamp, 4_500);
+        assert!(!lag.extrapolated);
+
+        // Record 699 appended when log-end moved to 700: half way between 2s and 4s.
+        estimator.update(&commit("g", "t", 0, 699, 4_500));
+        assert_eq!(estimator.estimate("g", "t", 0, 5_000).unwrap().time_lag, 2_000);
+
+        // Caught up.
+        estimator.update(&commit("g", "t", 0, 1200, 4_500));
+        assert_eq!(estimator.estimate("g", "t", 0, 5_000).unwrap().time_lag, 0);
+    }
+
+    #[test]
+    fn test_bounded_samples_and_extrapolation() {
+        let mut estimator = TimeLagEstimator::new(2);
+        estimator.record_log_end_offset("t", 0, 100, 1_000);
+        estimator.record_log_end_offset("t", 0, 200, 2_000);
+        estimator.record_log_end_offset("t", 0, 300, 3_000);
+        assert_eq!(estimator.samples.get(&("t".to_string(), 0)).unwrap().len(), 2);
+
+        estimator.update(&commit("g", "t", 0, 99, 3_000));
+        let lag = estimator.estimate("g", "t", 0, 3_000).unwrap();
+        assert!(lag.extrapolated);
+        assert_eq!(lag.time_lag, 2_000);
+    }
+
+    #[test]
+    fn test_missing_data() {
+        let mut estimator = TimeLagEstimator::default();
+        estimator.update(&commit("g", "t", 0, 10, 0));
+        assert!(estimator.estimate("g", "t", 0, 0).is_none());
+        assert!(estimator.estimate_all(0).is_empty());
+
+        estimator.record_log_end_offset("t", 0, 100, 1_000);
+        assert_eq!(estimator.estimate_all(0).len(), 1);
+    }
+
+    #[test]
+    fn test_types_thread_safety() {
+        is_thread_safe::<TimeLagEstimator>();
+        is_thread_safe::<PartitionTimeLag>();
+    }
+}
@@ -11,7 +11,7 @@ use crate::errors::KonsumerOffsetsError;
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`&str`] we want to parse.
+///   at the beginning of the [`&str`] we want to parse.
 pub(crate) fn parse_str(parser: &mut BytesParser) -> Result<String, KonsumerOffsetsError> {
     let group_strlen = parse_i16(parser)?;
     if group_strlen < 0 {
@@ -28,7 +28,7 @@ pub(crate) fn parse_str(parser: &mut BytesParser) -> Result<String, KonsumerOffs
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`Vec<u8>`] we want to parse.
+///   at the beginning of the [`Vec<u8>`] we want to parse.
 pub(crate) fn parse_vec_bytes(parser: &mut BytesParser) -> Result<Vec<u8>, KonsumerOffsetsError> {
     let bytes_array_len = parse_i32(parser)?;
 
@@ -42,7 +42,7 @@ pub(crate) fn parse_vec_bytes(parser: &mut BytesParser) -> Result<Vec<u8>, Konsu
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`i16`] we want to parse.
+///   at the beginning of the [`i16`] we want to parse.
 pub(crate) fn parse_i16(parser: &mut BytesParser) -> Result<i16, KonsumerOffsetsError> {
     parser.parse_i16().map_err(KonsumerOffsetsError::ByteParsingError)
 }
@@ -52,7 +52,7 @@ pub(crate) fn parse_i16(parser: &mut BytesParser) -> Result<i16, KonsumerOffsets
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`i32`] we want to parse.
+///   at the beginning of the [`i32`] we want to parse.
 pub(crate) fn parse_i32(parser: &mut BytesParser) -> Result<i32, KonsumerOffsetsError> {
     parser.parse_i32().map_err(KonsumerOffsetsError::ByteParsingError)
 }
@@ -62,7 +62,7 @@ pub(crate) fn parse_i32(parser: &mut BytesParser) -> Result<i32, KonsumerOffsets
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`i64`] we want to parse.
+///   at the beginning of the [`i64`] we want to parse.
 pub(crate) fn parse_i64(parser: &mut BytesParser) -> Result<i64, KonsumerOffsetsError> {
     parser.parse_i64().map_err(KonsumerOffsetsError::ByteParsingError)
 }
@@ -72,15 +72,12 @@ pub(crate) fn parse_i64(parser: &mut BytesParser) -> Result<i64, KonsumerOffsets
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`i64`] we want to parse.
+///   at the beginning of the [`i64`] we want to parse.
 #[cfg(feature = "ts_chrono")]
 pub(crate) fn parse_chrono_datetime_utc(
     parser: &mut BytesParser,
 ) -> Result<chrono::DateTime<chrono::Utc>, KonsumerOffsetsError> {
-    let millis = parse_i64(parser)?;
-
-    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(millis)
-        .ok_or(KonsumerOffsetsError::ChronoDateTimeUtcParsingError(millis))
+    try_timestamp_from_millis(parse_i64(parser)?)
 }
 
 /// Wraps the result of [`parse_i64`] into a [`time::OffsetDateTime`].
@@ -88,13 +85,60 @@ pub(crate) fn parse_chrono_datetime_utc(
 /// # Arguments
 ///
 /// * `parser` - A [`BytesParser`] with its internal cursor pointing
-///     at the beginning of the [`i64`] we want to parse.
+///   at the beginning of the [`i64`] we want to parse.
 #[cfg(feature = "ts_time")]
 pub(crate) fn parse_time_offset_datetime(
     parser: &mut BytesParser,
 ) -> Result<time::OffsetDateTime, KonsumerOffsetsError> {
-    let millis = parse_i64(parser)?;
+    try_timestamp_from_millis(parse_i64(parser)?)
+}
+
+/// Converts a timestamp, as represented by the `ts_*` feature in use, to milliseconds since Unix Epoch.
+///
+/// This is used by the analysis parts of this crate, that do arithmetic on timestamps.
+#[cfg(feature = "ts_int")]
+pub(crate) fn timestamp_millis(ts: &i64) -> i64 {
+    *ts
+}
+
+/// Converts a timestamp, as represented by the `ts_*` feature in use, to milliseconds since Unix Epoch.
+///
+/// This is used by the analysis parts of this crate, that do arithmetic on timestamps.
+#[cfg(feature = "ts_chrono")]
+pub(crate) fn timestamp_millis(ts: &chrono::DateTime<chrono::Utc>) -> i64 {
+    ts.timestamp_millis()
+}
+
+/// Converts a timestamp, as represented by the `ts_*` feature in use, to milliseconds since Unix Epoch.
+///
+/// This is used by the analysis parts of this crate, that do arithmetic on timestamps.
+#[cfg(feature = "ts_time")]
+pub(crate) fn timestamp_millis(ts: &time::OffsetDateTime) -> i64 {
+    (ts.unix_timestamp_nanos() / 1_000_000) as i64 //< ns to ms
+}
 
+/// Converts milliseconds since Unix Epoch to a timestamp, as represented by the `ts_*` feature in use.
+///
+/// This is the inverse of [`timestamp_millis`].
+#[cfg(all(feature = "ts_int", any(feature = "protobuf", feature = "avro")))]
+pub(crate) fn try_timestamp_from_millis(millis: i64) -> Result<i64, KonsumerOffsetsError> {
+    Ok(millis)
+}
+
+/// Converts milliseconds since Unix Epoch to a timestamp, as represented by the `ts_*` feature in use.
+///
+/// This is the inverse of [`timestamp_millis`].
+#[cfg(feature = "ts_chrono")]
+pub(crate) fn try_timestamp_from_millis(millis: i64) -> Result<chrono::DateTime<chrono::Utc>, KonsumerOffsetsError> {
+    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(millis)
+        .ok_or(KonsumerOffsetsError::ChronoDateTimeUtcParsingError(millis))
+}
+
+/// Converts milliseconds since Unix Epoch to a timestamp, as represented by the `ts_*` feature in use.
+///
+/// This is the inverse of [`timestamp_millis`].
+#[cfg(feature = "ts_time")]
+pub(crate) fn try_timestamp_from_millis(millis: i64) -> Result<time::OffsetDateTime, KonsumerOffsetsError> {
     time::OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000) //< ms to ns
         .map_err(KonsumerOffsetsError::TimeOffsetDateTimeParsingError)
 }
@@ -111,7 +155,7 @@ pub(crate) fn parse_time_offset_datetime(
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// use konsumer_offsets::GroupMetadata;
 ///
 /// #[test]
@@ -121,3 +165,106 @@ pub(crate) fn parse_time_offset_datetime(
 /// ```
 #[cfg(test)]
 pub(crate) fn is_thread_safe<T: Sized + Send + Sync + Unpin>() {}
+
+/// Used in unit tests to create a timestamp, as represented by the `ts_*` feature in use,
+/// from milliseconds since Unix Epoch.
+#[cfg(all(test, feature = "ts_int"))]
+pub(crate) fn timestamp_from_millis(millis: i64) -> i64 {
+    millis
+}
+
+/// Used in unit tests to create a timestamp, as represented by the `ts_*` feature in use,
+/// from milliseconds since Unix Epoch.
+#[cfg(all(test, feature = "ts_chrono"))]
+pub(crate) fn timestamp_from_millis(millis: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(millis).unwrap()
+}
+
+/// Used in unit tests to create a timestamp, as represented by the `ts_*` feature in use,
+/// from milliseconds since Unix Epoch.
+#[cfg(all(test, feature = "ts_time"))]
+pub(crate) fn timestamp_from_millis(millis: i64) -> time::OffsetDateTime {
+    time::OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000).unwrap()
+}
+
+/// Used in unit tests to create an [`OffsetCommit`](crate::OffsetCommit), committed at
+/// `commit_timestamp` milliseconds since Unix Epoch.
+#[cfg(test)]
+pub(crate) fn commit(
+    group: &str,
+    topic: &str,
+    partition: i32,
+    offset: i64,
+    commit_timestamp: i64,
+) -> crate::OffsetCommit {
+    crate::OffsetCommit {
+        group: group.to_string(),
+        topic: topic.to_string(),
+        partition,
+        offset,
+        commit_timestamp: timestamp_from_millis(commit_timestamp),
+        ..Default::default()
+    }
+}
+
+/// Used in unit tests to create a [`MemberMetadata`](crate::MemberMetadata) of a Kafka Consumer,
+/// subscribed to the `subscribed` topics and assigned the given partitions of each topic.
+#[cfg(test)]
+pub(crate) fn member(id: &str, subscribed: &[&str], assigned: &[(&str, &[i32])]) -> crate::MemberMetadata {
+    crate::MemberMetadata {
+        id: id.to_string(),
+        client_id: format!("client-{id}"),
+        client_host: "/10.0.0.1".to_string(),
+        subscription: crate::ConsumerProtocolSubscription {
+            subscribed_topics: subscribed.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        },
+        assignment: crate::ConsumerProtocolAssignment {
+            assigned_topic_partitions: assigned
+                .iter()
+                .map(|(topic, partitions)| crate::TopicPartitions {
+                    topic: topic.to_string(),
+                    partitions: partitions.to_vec(),
+                })
+                .collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Used in unit tests to create a [`GroupMetadata`](crate::GroupMetadata) of a group of Kafka Consumers.
+#[cfg(test)]
+pub(crate) fn group(group: &str, members: Vec<crate::MemberMetadata>) -> crate::GroupMetadata {
+    crate::GroupMetadata {
+        group: group.to_string(),
+        protocol_type: "consumer".to_string(),
+        members,
+        ..Default::default()
+    }
+}
+
+/// Used in unit tests to read the key and payload bytes of one of the fixtures in `fixtures/tests/`.
+#[cfg(test)]
+pub(crate) fn read_fixture_bytes(fixture_name: &str, fixture_id: u16) -> (Vec<u8>, Vec<u8>) {
+    (
+        std::fs::read(format!("fixtures/tests/{fixture_name}/{fixture_id:02}.key")).unwrap(),
+        std::fs::read(format!("fixtures/tests/{fixture_name}/{fixture_id:02}.payload")).unwrap(),
+    )
+}
+
+/// Used in unit tests to parse one of the fixtures in `fixtures/tests/`.
+#[cfg(test)]
+pub(crate) fn read_fixture(fixture_name: &str, fixture_id: u16) -> crate::KonsumerOffsetsData {
+    let (key, payload) = read_fixture_bytes(fixture_name, fixture_id);
+    crate::KonsumerOffsetsData::try_from_bytes_vec(Some(key), Some(payload)).unwrap()
+}
+
+/// Used in unit tests to parse all the offset commit and group metadata fixtures in `fixtures/tests/`.
+#[cfg(all(test, any(feature = "json_schema", feature = "protobuf", feature = "avro")))]
+pub(crate) fn read_fixtures() -> Vec<crate::KonsumerOffsetsData> {
+    [("offset_commit", 1..=3), ("group_metadata", 1..=5)]
+        .into_iter()
+        .flat_map(|(name, ids)| ids.map(move |id| read_fixture(name, id)))
+        .collect()
+}